@@ -16,6 +16,22 @@ module.exports = {
                 vm: false,
             };
 
+            // The bio-rules package is WebAssembly, built with `npm run build:rules`.
+            // Keep .wasm files away from the catch-all asset loader so webpack
+            // instantiates them as modules.
+            webpackConfig.experiments = {
+                ...webpackConfig.experiments,
+                asyncWebAssembly: true,
+            };
+            webpackConfig.resolve.extensions.push('.wasm');
+            webpackConfig.module.rules.forEach((rule) => {
+                (rule.oneOf || []).forEach((loader) => {
+                    if (loader.type === 'asset/resource') {
+                        loader.exclude.push(/\.wasm$/);
+                    }
+                });
+            });
+
             webpackConfig.output = {
                 ...webpackConfig.output,
                 publicPath: '/',
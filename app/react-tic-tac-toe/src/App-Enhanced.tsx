@@ -141,10 +141,16 @@ const EnhancedBioCommanderApp: React.FC = () => {
         abilityIndex: params?.abilityIndex || 0
       };
       
+      // Play takes the acting unit and a target unit as two distinct entities
+      if (!params?.unitEntity || !params?.targetUnitEntity) {
+        throw new Error("Unit entities missing for the action");
+      }
       const transaction = await solanaService.playAction(
         publicKey,
         new PublicKey(gameState.gameId.toString()),
-        playAction
+        playAction,
+        new PublicKey(params.unitEntity),
+        new PublicKey(params.targetUnitEntity)
       );
       
       const signature = await sendTransaction(transaction, connection);
@@ -170,10 +176,17 @@ const EnhancedBioCommanderApp: React.FC = () => {
     
     setIsSubmitting(true);
     try {
+      // Expansions start from the zone on screen; a new zone gets a fresh entity
+      const sourceZone = zones[selectedZoneIndex];
+      if (!sourceZone) {
+        throw new Error("No zone to expand from");
+      }
       const transaction = await solanaService.expandZone(
-        publicKey,
         new PublicKey(gameState.gameId.toString()),
-        expansionType,
+        (currentPlayer?.faction ?? selectedFaction)!,
+        ExpansionType[expansionType] as 'CreateNewZone' | 'InfectionSpread' | 'ImmuneResponse' | 'ConquerZone',
+        new PublicKey(sourceZone.entity),
+        targetZone ? new PublicKey(targetZone.entity) : undefined,
         newZoneType
       );
       
@@ -187,7 +200,7 @@ const EnhancedBioCommanderApp: React.FC = () => {
     } finally {
       setIsSubmitting(false);
     }
-  }, [solanaService, publicKey, gameState, sendTransaction, connection]);
+  }, [solanaService, publicKey, gameState, zones, selectedZoneIndex, currentPlayer, selectedFaction, sendTransaction, connection]);
 
   const handleZoneSwitch = useCallback((direction: 'prev' | 'next') => {
     if (direction === 'prev') {
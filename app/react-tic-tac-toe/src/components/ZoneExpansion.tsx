@@ -1,6 +1,16 @@
 import React, { useState } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
-import { ZoneType, Faction, PlayerResources, Zone } from '../types/bioCommander';
+import {
+  ZoneType,
+  Faction,
+  PlayerResources,
+  ResourceCost,
+  Zone,
+  getInfectionSpreadCost,
+  getImmuneResponseCost,
+  getZoneCreationCost,
+  getConquestCost
+} from '../types/bioCommander';
 import './ZoneExpansion.scss';
 
 export enum ExpansionType {
@@ -16,12 +26,6 @@ interface ExpansionOption {
   icon: string;
   description: string;
   allowedFactions: Faction[];
-  cost: {
-    energy: number;
-    antibodies: number;
-    stemCells: number;
-    nutrients: number;
-  };
   requirements: string[];
 }
 
@@ -43,7 +47,6 @@ const EXPANSION_OPTIONS: ExpansionOption[] = [
     icon: "🦠",
     description: "Spread infection to adjacent zones, weakening enemy defenses",
     allowedFactions: [Faction.Pathogen],
-    cost: { energy: 100, antibodies: 0, stemCells: 0, nutrients: 50 },
     requirements: ["Adjacent zone must exist", "Must be pathogen faction"]
   },
   {
@@ -52,7 +55,6 @@ const EXPANSION_OPTIONS: ExpansionOption[] = [
     icon: "🛡️",
     description: "Establish immune staging area to boost defenses",
     allowedFactions: [Faction.ImmuneSystem],
-    cost: { energy: 80, antibodies: 40, stemCells: 10, nutrients: 30 },
     requirements: ["Adjacent zone must exist", "Must be immune system faction"]
   },
   {
@@ -61,7 +63,6 @@ const EXPANSION_OPTIONS: ExpansionOption[] = [
     icon: "🏗️",
     description: "Create a completely new zone to expand the battlefield",
     allowedFactions: [Faction.ImmuneSystem, Faction.Pathogen],
-    cost: { energy: 200, antibodies: 50, stemCells: 20, nutrients: 100 },
     requirements: ["Border zone required", "High resource cost"]
   },
   {
@@ -70,7 +71,6 @@ const EXPANSION_OPTIONS: ExpansionOption[] = [
     icon: "⚔️",
     description: "Take control of an enemy-controlled zone through force",
     allowedFactions: [Faction.ImmuneSystem, Faction.Pathogen],
-    cost: { energy: 300, antibodies: 100, stemCells: 15, nutrients: 200 },
     requirements: ["Adjacent enemy zone", "Strong military presence"]
   }
 ];
@@ -96,11 +96,28 @@ const ZoneExpansion: React.FC<ZoneExpansionProps> = ({
     );
   };
 
+  // Costs depend on the zone an expansion targets, or creates. Until a
+  // target is picked, the current zone's type stands in for it.
+  const getExpansionCost = (expansionType: ExpansionType): ResourceCost => {
+    const targetType = selectedTargetZone?.zoneType ?? currentZone.zoneType;
+    switch (expansionType) {
+      case ExpansionType.InfectionSpread:
+        return getInfectionSpreadCost(targetType);
+      case ExpansionType.ImmuneResponse:
+        return getImmuneResponseCost(targetType);
+      case ExpansionType.CreateNewZone:
+        return getZoneCreationCost(selectedNewZoneType, playerFaction);
+      case ExpansionType.ConquerZone:
+        return getConquestCost(targetType);
+    }
+  };
+
   const canAffordExpansion = (expansion: ExpansionOption): boolean => {
-    return playerResources.energy >= expansion.cost.energy &&
-           playerResources.antibodies >= expansion.cost.antibodies &&
-           playerResources.stemCells >= expansion.cost.stemCells &&
-           playerResources.nutrients >= expansion.cost.nutrients;
+    const cost = getExpansionCost(expansion.type);
+    return playerResources.energy >= cost.energy &&
+           playerResources.antibodies >= cost.antibodies &&
+           playerResources.stemCells >= cost.stemCells &&
+           playerResources.nutrients >= cost.nutrients;
   };
 
   const getValidTargetZones = (expansionType: ExpansionType): Zone[] => {
@@ -196,6 +213,8 @@ const ZoneExpansion: React.FC<ZoneExpansionProps> = ({
 
   if (!isVisible) return null;
 
+  const selectedCost = selectedExpansion !== null ? getExpansionCost(selectedExpansion) : null;
+
   return (
     <motion.div
       className="zone-expansion-overlay"
@@ -237,6 +256,7 @@ const ZoneExpansion: React.FC<ZoneExpansionProps> = ({
               <div className="options-grid">
                 {getAvailableExpansions().map((expansion) => {
                   const canAfford = canAffordExpansion(expansion);
+                  const cost = getExpansionCost(expansion.type);
                   
                   return (
                     <motion.div
@@ -256,10 +276,10 @@ const ZoneExpansion: React.FC<ZoneExpansionProps> = ({
                       <div className="option-cost">
                         <span className="cost-label">Cost:</span>
                         <div className="cost-items">
-                          {expansion.cost.energy > 0 && <span>⚡{expansion.cost.energy}</span>}
-                          {expansion.cost.antibodies > 0 && <span>💊{expansion.cost.antibodies}</span>}
-                          {expansion.cost.stemCells > 0 && <span>🧬{expansion.cost.stemCells}</span>}
-                          {expansion.cost.nutrients > 0 && <span>🍎{expansion.cost.nutrients}</span>}
+                          {cost.energy > 0 && <span>⚡{cost.energy}</span>}
+                          {cost.antibodies > 0 && <span>💊{cost.antibodies}</span>}
+                          {cost.stemCells > 0 && <span>🧬{cost.stemCells}</span>}
+                          {cost.nutrients > 0 && <span>🍎{cost.nutrients}</span>}
                         </div>
                       </div>
                       
@@ -386,12 +406,12 @@ const ZoneExpansion: React.FC<ZoneExpansionProps> = ({
               <div className="cost-summary">
                 <h4>Resource Cost:</h4>
                 <div className="cost-breakdown">
-                  {EXPANSION_OPTIONS.find(e => e.type === selectedExpansion)?.cost && (
+                  {selectedCost && (
                     <>
-                      <span>⚡ {EXPANSION_OPTIONS.find(e => e.type === selectedExpansion)!.cost.energy}</span>
-                      <span>💊 {EXPANSION_OPTIONS.find(e => e.type === selectedExpansion)!.cost.antibodies}</span>
-                      <span>🧬 {EXPANSION_OPTIONS.find(e => e.type === selectedExpansion)!.cost.stemCells}</span>
-                      <span>🍎 {EXPANSION_OPTIONS.find(e => e.type === selectedExpansion)!.cost.nutrients}</span>
+                      <span>⚡ {selectedCost.energy}</span>
+                      <span>💊 {selectedCost.antibodies}</span>
+                      <span>🧬 {selectedCost.stemCells}</span>
+                      <span>🍎 {selectedCost.nutrients}</span>
                     </>
                   )}
                 </div>
@@ -68,11 +68,27 @@ export const ZoneManager: React.FC<ZoneManagerProps> = ({
       setIsLoading(true);
       setError(null);
 
+      if (!selectedZone) {
+        setError('Please select a source zone first');
+        return;
+      }
+
+      // The new zone gets a fresh entity in the same transaction, linked with
+      // every other zone already next to its tile
+      const { x, y } = creationForm;
+      const neighbours = zones
+        .filter(zone => zone.entity !== selectedZone.entity && Math.abs(zone.x - x) + Math.abs(zone.y - y) === 1)
+        .map(zone => new PublicKey(zone.entity));
       const transaction = await solanaService.expandZone(
         gameId,
         playerFaction,
         creationForm.expansionType,
-        creationForm.zoneType
+        new PublicKey(selectedZone.entity),
+        undefined,
+        creationForm.zoneType,
+        { x, y },
+        undefined,
+        neighbours
       );
 
       // Sign and send transaction
@@ -113,9 +129,8 @@ export const ZoneManager: React.FC<ZoneManagerProps> = ({
         gameId,
         playerFaction,
         expansionType,
-        undefined,
-        selectedZone.zoneId,
-        targetZone.zoneId
+        new PublicKey(selectedZone.entity),
+        new PublicKey(targetZone.entity)
       );
 
       const signature = await solanaService.sendTransaction(transaction);
@@ -23,6 +23,7 @@ import {
   Game,
   Player,
   Zone,
+  ZoneResources,
   Unit
 } from '../types/bioCommander';
 
@@ -35,11 +36,15 @@ export class BioCommanderSolanaService {
   private readonly PLAYER_COMPONENT = new PublicKey("HLzXXTbMUjemRSQr5LHjtZgBvqyieuhY8wE29xYzhZSX");
   private readonly UNIT_COMPONENT = new PublicKey("UNiT111111111111111111111111111111111111111");
   private readonly GAME_COMPONENT = new PublicKey("GAMe111111111111111111111111111111111111111");
+  private readonly ZONE_INDEX_COMPONENT = new PublicKey("iNDX111111111111111111111111111111111111111");
   
   // System Program IDs
   private readonly JOIN_GAME = new PublicKey("7TsTc97MB21EKbh2RetcWsGWRJ4xuMkPKKD4DcMJ2Sms");
   private readonly PLAY = new PublicKey("EFLfG5icLgcUYwuSnuScoYptcrgh8WYLHx33M4wvTPFv");
   private readonly EXPAND_ZONE = new PublicKey("EXPa111111111111111111111111111111111111111");
+  private readonly CONQUER_ZONE = new PublicKey("CoNQ111111111111111111111111111111111111111");
+  private readonly CONNECT_ZONES = new PublicKey("CoNN111111111111111111111111111111111111111");
+  private readonly TRANSFER_RESOURCES = new PublicKey("TRaNS11111111111111111111111111111111111111");
   
   private readonly WORLD_INSTANCE_ID = 1721;
   
@@ -138,7 +143,8 @@ export class BioCommanderSolanaService {
           components: [
             { componentId: this.GAME_COMPONENT },
             { componentId: this.PLAYER_COMPONENT },
-            { componentId: this.ZONE_COMPONENT }
+            { componentId: this.ZONE_COMPONENT },
+            { componentId: this.ZONE_INDEX_COMPONENT }
           ]
         }
       ],
@@ -150,60 +156,24 @@ export class BioCommanderSolanaService {
     return applySystem.transaction;
   }
 
+  // `unitEntity` holds the acting unit, or a freshly initialized unit
+  // component for SpawnUnit. `targetUnitEntity` holds the unit under attack
+  // for AttackPosition, and otherwise a freshly initialized unit component,
+  // which Replication binds its child to. The two must differ: the program
+  // rejects the acting unit passed again as the target.
   async playAction(
     playerPublicKey: PublicKey,
     gameId: PublicKey,
-    action: PlayAction
+    action: PlayAction,
+    unitEntity: PublicKey,
+    targetUnitEntity: PublicKey
   ): Promise<Transaction> {
     const worldPda = FindWorldPda({ worldId: new BN(this.WORLD_INSTANCE_ID) });
-    
-    const components = [
-      { componentId: this.GAME_COMPONENT },
-      { componentId: this.PLAYER_COMPONENT },
-      { componentId: this.ZONE_COMPONENT }
-    ];
-    
-    // Add unit component if needed for unit actions
-    if (action.action === ActionType.MoveUnit || 
-        action.action === ActionType.AttackPosition || 
-        action.action === ActionType.UseSpecialAbility) {
-      components.push({ componentId: this.UNIT_COMPONENT });
-    }
 
     const applySystem = await ApplySystem({
       authority: playerPublicKey,
       systemId: this.PLAY,
       world: worldPda,
-      entities: [
-        {
-          entity: gameId,
-          components
-        }
-      ],
-      args: {
-        action: this.actionTypeToNumber(action.action),
-        x: action.x,
-        y: action.y,
-        unit_type: action.unitType,
-        ability_index: action.abilityIndex
-      }
-    });
-
-    return applySystem.transaction;
-  }
-
-  async expandZone(
-    playerPublicKey: PublicKey,
-    gameId: PublicKey,
-    expansionType: number,
-    newZoneType?: ZoneType
-  ): Promise<Transaction> {
-    const worldPda = FindWorldPda({ worldId: new BN(this.WORLD_INSTANCE_ID) });
-    
-    const applySystem = await ApplySystem({
-      authority: playerPublicKey,
-      systemId: this.EXPAND_ZONE,
-      world: worldPda,
       entities: [
         {
           entity: gameId,
@@ -212,11 +182,22 @@ export class BioCommanderSolanaService {
             { componentId: this.PLAYER_COMPONENT },
             { componentId: this.ZONE_COMPONENT }
           ]
+        },
+        {
+          entity: unitEntity,
+          components: [{ componentId: this.UNIT_COMPONENT }]
+        },
+        {
+          entity: targetUnitEntity,
+          components: [{ componentId: this.UNIT_COMPONENT }]
         }
       ],
       args: {
-        expansion_type: expansionType,
-        new_zone_type: newZoneType ? this.zoneTypeToNumber(newZoneType) : 0
+        action: this.actionTypeToNumber(action.action),
+        x: action.x,
+        y: action.y,
+        unit_type: action.unitType,
+        ability_index: action.abilityIndex
       }
     });
 
@@ -274,7 +255,7 @@ export class BioCommanderSolanaService {
       (accountInfo) => {
         if (this.zoneClient) {
           const parsedData = this.zoneClient.coder.accounts.decode("zone", accountInfo.data);
-          onZoneUpdate(this.parseZoneData(parsedData));
+          onZoneUpdate(this.parseZoneData(parsedData, gameId));
         }
       },
       'processed'
@@ -311,7 +292,7 @@ export class BioCommanderSolanaService {
       return {
         game: gameData.status === 'fulfilled' ? this.parseGameData(gameData.value) : undefined,
         player: playerData.status === 'fulfilled' ? this.parsePlayerData(playerData.value) : undefined,
-        zone: zoneData.status === 'fulfilled' ? this.parseZoneData(zoneData.value) : undefined
+        zone: zoneData.status === 'fulfilled' ? this.parseZoneData(zoneData.value, gameId) : undefined
       };
     } catch (error) {
       console.error("Failed to fetch game state:", error);
@@ -327,6 +308,7 @@ export class BioCommanderSolanaService {
       case ActionType.AttackPosition: return 2;
       case ActionType.UseSpecialAbility: return 3;
       case ActionType.EndTurn: return 4;
+      case ActionType.Harvest: return 5;
       default: return 0;
     }
   }
@@ -349,7 +331,7 @@ export class BioCommanderSolanaService {
 
   private parseGameData(data: any): Game {
     return {
-      gameId: data.gameId,
+      gameId: data.gameId.toBase58(),
       player1: data.player1.toBase58(),
       player2: data.player2.toBase58(),
       currentTurn: data.currentTurn,
@@ -357,12 +339,15 @@ export class BioCommanderSolanaService {
       mapWidth: data.mapWidth,
       mapHeight: data.mapHeight,
       totalZones: data.totalZones,
+      nextZoneId: data.nextZoneId,
       gameState: this.parseGameState(data.gameState),
       winner: data.winner.toBase58(),
       infectionLevel: data.infectionLevel,
       immuneResponseLevel: data.immuneResponseLevel,
       turnTimeLimit: data.turnTimeLimit,
-      lastTurnTimestamp: data.lastTurnTimestamp
+      lastTurnTimestamp: data.lastTurnTimestamp,
+      nextUnitId: data.nextUnitId,
+      seed: BigInt(data.seed.toString())
     };
   }
 
@@ -384,14 +369,15 @@ export class BioCommanderSolanaService {
     };
   }
 
-  private parseZoneData(data: any): Zone {
+  private parseZoneData(data: any, entity: PublicKey): Zone {
     return {
       zoneId: data.zoneId,
+      entity: entity.toBase58(),
       zoneType: this.parseZoneType(data.zoneType),
       x: data.x,
       y: data.y,
       owner: data.owner.toBase58(),
-      grid: this.parseGrid(data.grid),
+      grid: this.parseGrid(this.unpackGrid(data.cells, data.occupants)),
       resources: {
         energy: data.energy,
         antibodies: data.antibodies,
@@ -421,6 +407,35 @@ export class BioCommanderSolanaService {
     return ZoneType.Tissue;
   }
 
+  // The grid is stored packed: one nibble per cell giving its kind, plus an
+  // occupant entry with the id and health (or amount) of units and resource
+  // nodes. Kinds 8 to 11 are units that record their owner's seat: plus 2
+  // for a pathogen, plus the seat - 1. Kinds 1 and 2 are units of seat 0.
+  // This rebuilds the dense [x][y] grid the rest of the client reads.
+  private unpackGrid(cells: number[], occupants: { cell: number; id: number; amount: number }[]): any[][] {
+    const resourceTypes = ['energy', 'antibodies', 'stemCells', 'nutrients'];
+    const byCell = new Map(occupants.map(occupant => [occupant.cell, occupant]));
+
+    return Array.from({ length: 16 }, (_, x) =>
+      Array.from({ length: 16 }, (_, y) => {
+        const index = x * 16 + y;
+        const kind = index % 2 === 0 ? cells[index >> 1] & 0x0f : cells[index >> 1] >> 4;
+        const occupant = byCell.get(index) ?? { cell: index, id: 0, amount: 0 };
+        switch (kind) {
+          case 0: return null;
+          case 1: return { immuneCell: { unitId: occupant.id, health: occupant.amount, seat: 0 } };
+          case 2: return { pathogen: { unitId: occupant.id, health: occupant.amount, seat: 0 } };
+          case 3: return { obstacle: {} };
+        }
+        if (kind < 8) {
+          return { resource: { resourceType: { [resourceTypes[kind - 4]]: {} }, amount: occupant.amount } };
+        }
+        const unit = { unitId: occupant.id, health: occupant.amount, seat: (kind - 8) % 2 + 1 };
+        return kind < 10 ? { immuneCell: unit } : { pathogen: unit };
+      })
+    );
+  }
+
   private parseGrid(grid: any[][]): (import('../types/bioCommander').Cell | null)[][] {
     return grid.map(row => 
       row.map(cell => {
@@ -430,7 +445,7 @@ export class BioCommanderSolanaService {
             unitId: cell.immuneCell.unitId,
             health: cell.immuneCell.health,
             unitType: UnitType.TCell, // This would need proper parsing
-            owner: "1" // This would need proper parsing
+            owner: String(cell.immuneCell.seat) // Seat of the owner, "0" if unknown
           };
         }
         if (cell.pathogen) {
@@ -438,7 +453,7 @@ export class BioCommanderSolanaService {
             unitId: cell.pathogen.unitId,
             health: cell.pathogen.health,
             unitType: UnitType.Virus, // This would need proper parsing
-            owner: "2" // This would need proper parsing
+            owner: String(cell.pathogen.seat) // Seat of the owner, "0" if unknown
           };
         }
         return null;
@@ -470,20 +485,120 @@ export class BioCommanderSolanaService {
     }
   }
 
-  // Expand zone with different expansion types
+  // Expand zone with different expansion types. The source and target zones
+  // are two distinct entities; CreateNewZone adds a fresh zone entity to the
+  // transaction when no target is given, and links it with the zones on the
+  // other neighbouring tiles in the same transaction. Conquest takes the
+  // defender's player entity in place of the zone index.
   async expandZone(
     gameId: PublicKey,
     playerFaction: Faction,
     expansionType: 'CreateNewZone' | 'InfectionSpread' | 'ImmuneResponse' | 'ConquerZone',
+    sourceZoneEntity: PublicKey,
+    targetZoneEntity?: PublicKey,
     newZoneType?: ZoneType,
-    sourceZoneId?: number,
-    targetZoneId?: number
+    position?: { x: number; y: number },
+    defenderEntity?: PublicKey,
+    neighbourZoneEntities: PublicKey[] = [] // Zones next to the new one, besides the source
   ): Promise<Transaction> {
     const worldPda = FindWorldPda({ worldId: new BN(this.WORLD_INSTANCE_ID) });
-    
+    const payer = this.provider.wallet.publicKey!;
+
+    let transaction = new Transaction();
+    if (!targetZoneEntity) {
+      if (expansionType !== 'CreateNewZone') {
+        throw new Error(`${expansionType} needs the target zone's entity`);
+      }
+      const addEntity = await AddEntity({ payer, world: worldPda, connection: this.connection });
+      const initZoneIx = (await InitializeComponent({
+        payer,
+        entity: addEntity.entityPda,
+        componentId: this.ZONE_COMPONENT,
+      })).instruction;
+      transaction = addEntity.transaction.add(initZoneIx);
+      targetZoneEntity = addEntity.entityPda;
+    }
+    if (expansionType === 'ConquerZone' && !defenderEntity) {
+      throw new Error("ConquerZone needs the defender's player entity");
+    }
+
+    const applySystem = await ApplySystem({
+      authority: payer,
+      // Conquest runs as its own system, which takes the defender instead
+      // of the zone index
+      systemId: expansionType === 'ConquerZone' ? this.CONQUER_ZONE : this.EXPAND_ZONE,
+      world: worldPda,
+      entities: [
+        {
+          entity: gameId,
+          components: [
+            { componentId: this.GAME_COMPONENT },
+            { componentId: this.PLAYER_COMPONENT }
+          ]
+        },
+        {
+          entity: sourceZoneEntity,
+          components: [{ componentId: this.ZONE_COMPONENT }]
+        },
+        {
+          entity: targetZoneEntity,
+          components: [{ componentId: this.ZONE_COMPONENT }]
+        },
+        expansionType === 'ConquerZone'
+          ? { entity: defenderEntity!, components: [{ componentId: this.PLAYER_COMPONENT }] }
+          : { entity: gameId, components: [{ componentId: this.ZONE_INDEX_COMPONENT }] }
+      ],
+      args: {
+        expansion_type: this.expansionTypeToNumber(expansionType),
+        new_zone_type: newZoneType ? this.zoneTypeToNumber(newZoneType) : 0,
+        // Map tile of the new zone, next to the source zone
+        x: position?.x ?? 0,
+        y: position?.y ?? 0
+      }
+    });
+
+    transaction.add(applySystem.transaction);
+    if (expansionType === 'CreateNewZone') {
+      for (const neighbourEntity of neighbourZoneEntities) {
+        transaction.add(await this.connectZones(gameId, targetZoneEntity, neighbourEntity));
+      }
+    }
+    return transaction;
+  }
+
+  // Links two zones on neighbouring tiles of the game's map. Creating a zone
+  // only links it with the zone it grew from.
+  async connectZones(gameId: PublicKey, zoneEntity: PublicKey, neighbourEntity: PublicKey): Promise<Transaction> {
+    const worldPda = FindWorldPda({ worldId: new BN(this.WORLD_INSTANCE_ID) });
+
+    const applySystem = await ApplySystem({
+      authority: this.provider.wallet.publicKey!,
+      systemId: this.CONNECT_ZONES,
+      world: worldPda,
+      entities: [
+        { entity: zoneEntity, components: [{ componentId: this.ZONE_COMPONENT }] },
+        { entity: neighbourEntity, components: [{ componentId: this.ZONE_COMPONENT }] },
+        { entity: gameId, components: [{ componentId: this.ZONE_INDEX_COMPONENT }] }
+      ]
+    });
+
+    return applySystem.transaction;
+  }
+
+  // Moves resources out of or into the pools of a zone. ToZone sends them to
+  // a linked zone; the reserves are reached from the starting zone and the
+  // zones linked to it.
+  async transferResources(
+    gameId: PublicKey,
+    transfer: 'ToZone' | 'ToReserves' | 'FromReserves',
+    amounts: ZoneResources
+  ): Promise<Transaction> {
+    const worldPda = FindWorldPda({ worldId: new BN(this.WORLD_INSTANCE_ID) });
+    const transfers = ['ToZone', 'ToReserves', 'FromReserves'];
+
     const applySystem = await ApplySystem({
       authority: this.provider.wallet.publicKey!,
-      systemId: this.EXPAND_ZONE,
+      systemId: this.TRANSFER_RESOURCES,
       world: worldPda,
       entities: [
         {
@@ -491,15 +606,17 @@ export class BioCommanderSolanaService {
           components: [
             { componentId: this.GAME_COMPONENT },
             { componentId: this.PLAYER_COMPONENT },
+            { componentId: this.ZONE_COMPONENT },
             { componentId: this.ZONE_COMPONENT }
           ]
         }
       ],
       args: {
-        expansion_type: this.expansionTypeToNumber(expansionType),
-        new_zone_type: newZoneType ? this.zoneTypeToNumber(newZoneType) : 0,
-        source_zone_id: sourceZoneId || 0,
-        target_zone_id: targetZoneId || 0
+        transfer: transfers.indexOf(transfer),
+        energy: amounts.energy,
+        antibodies: amounts.antibodies,
+        stem_cells: amounts.stemCells,
+        nutrients: amounts.nutrients
       }
     });
 
@@ -509,11 +626,11 @@ export class BioCommanderSolanaService {
   // Convert expansion type to number for Solana program
   private expansionTypeToNumber(expansionType: string): number {
     switch (expansionType) {
-      case 'CreateNewZone': return 0;
-      case 'InfectionSpread': return 1;
-      case 'ImmuneResponse': return 2;
-      case 'ConquerZone': return 3;
-      default: return 0;
+      // Same order as the expand-zone system's ExpansionType
+      case 'InfectionSpread': return 0;
+      case 'ImmuneResponse': return 1;
+      case 'CreateNewZone': return 2;
+      default: return 2;
     }
   }
 
@@ -1,5 +1,7 @@
 // Bio Commander Types - matching Solana program structures
 
+import * as rules from 'bio-rules';
+
 export enum UnitType {
   // Immune Cells
   TCell = "TCell",
@@ -36,7 +38,8 @@ export enum ActionType {
   MoveUnit = "MoveUnit",
   AttackPosition = "AttackPosition",
   UseSpecialAbility = "UseSpecialAbility",
-  EndTurn = "EndTurn"
+  EndTurn = "EndTurn",
+  Harvest = "Harvest"
 }
 
 export enum SpecialAbility {
@@ -107,6 +110,7 @@ export interface Cell {
 
 export interface Unit {
   unitId: number;
+  gameId: number; // Game.gameId of the game the unit was spawned in
   unitType: UnitType;
   health: number;
   maxHealth: number;
@@ -121,6 +125,7 @@ export interface Unit {
 
 export interface Zone {
   zoneId: number;
+  entity: string; // Entity holding the zone component
   zoneType: ZoneType;
   name: string; // Added name property for display purposes
   x: number;
@@ -146,20 +151,23 @@ export interface Player {
 }
 
 export interface Game {
-  gameId: number;
+  gameId: string; // Address of the Game component
   player1: string;
   player2: string;
   currentTurn: number;
   turnNumber: number;
   mapWidth: number;
   mapHeight: number;
-  totalZones: number;
+  totalZones: number; // Zone tiles of the map, zone control is a share of these
+  nextZoneId: number; // Id of the next created zone
   gameState: GameState;
   winner: string;
   infectionLevel: number;
   immuneResponseLevel: number;
   turnTimeLimit: number;
   lastTurnTimestamp: number;
+  nextUnitId: number;
+  seed: bigint; // Zone interiors are generated from it, see generateInterior in bio-rules
 }
 
 export enum GameState {
@@ -177,6 +185,48 @@ export interface PlayAction {
   abilityIndex: number;
 }
 
+// Tables and formulas come from the bio-rules crate, compiled to WebAssembly,
+// so previews match what the programs charge. Its functions take the same
+// indices as the systems: the declaration order of these enums.
+
+const unitTypeIndex = (unitType: UnitType): number => Object.values(UnitType).indexOf(unitType);
+const zoneTypeIndex = (zoneType: ZoneType): number => Object.values(ZoneType).indexOf(zoneType);
+const factionIndex = (faction: Faction): number => Object.values(Faction).indexOf(faction);
+
+const toResourceCost = (cost: ArrayLike<bigint> | undefined): ResourceCost => {
+  const [energy, antibodies, stemCells, nutrients] = Array.from(cost!, Number);
+  return { energy, antibodies, stemCells, nutrients };
+};
+
+// Stats, abilities and cost a unit spawns with. Zones without a spawn
+// modifier charge the listed cost, see getSpawnCost for the others.
+const unitRules = (unitType: UnitType): Pick<UnitInfo, 'stats' | 'specialAbilities' | 'cost'> => {
+  const [health, attack, defense, movementRange, energyCost] = Array.from(rules.unitBaseStats(unitTypeIndex(unitType))!);
+  const abilities = Object.values(SpecialAbility);
+  return {
+    stats: { health, maxHealth: health, attack, defense, movementRange, energyCost },
+    specialAbilities: Array.from(rules.unitDefaultAbilities(unitTypeIndex(unitType))!)
+      .filter(ability => ability < abilities.length)
+      .map(ability => abilities[ability]),
+    cost: getSpawnCost(unitType, ZoneType.Circulatory)
+  };
+};
+
+export const getSpawnCost = (unitType: UnitType, zoneType: ZoneType): ResourceCost =>
+  toResourceCost(rules.spawnCost(unitTypeIndex(unitType), zoneTypeIndex(zoneType)));
+
+export const getInfectionSpreadCost = (targetZoneType: ZoneType): ResourceCost =>
+  toResourceCost(rules.infectionSpreadCost(zoneTypeIndex(targetZoneType)));
+
+export const getImmuneResponseCost = (targetZoneType: ZoneType): ResourceCost =>
+  toResourceCost(rules.immuneResponseCost(zoneTypeIndex(targetZoneType)));
+
+export const getZoneCreationCost = (zoneType: ZoneType, faction: Faction): ResourceCost =>
+  toResourceCost(rules.zoneCreationCost(zoneTypeIndex(zoneType), factionIndex(faction)));
+
+export const getConquestCost = (targetZoneType: ZoneType): ResourceCost =>
+  toResourceCost(rules.conquestCost(zoneTypeIndex(targetZoneType)));
+
 // Unit data matching Solana program
 export const UNIT_DATA: Record<UnitType, UnitInfo> = {
   [UnitType.TCell]: {
@@ -184,10 +234,8 @@ export const UNIT_DATA: Record<UnitType, UnitInfo> = {
     name: "T-Cell",
     icon: "🛡️",
     description: "Cytotoxic lymphocyte that destroys infected cells",
-    stats: { health: 80, maxHealth: 80, attack: 15, defense: 10, movementRange: 3, energyCost: 20 },
-    specialAbilities: [SpecialAbility.CytokineRelease, SpecialAbility.MemoryResponse],
+    ...unitRules(UnitType.TCell),
     faction: Faction.ImmuneSystem,
-    cost: { energy: 20, antibodies: 5, stemCells: 1, nutrients: 8 }
   },
   
   [UnitType.BCell]: {
@@ -195,10 +243,8 @@ export const UNIT_DATA: Record<UnitType, UnitInfo> = {
     name: "B-Cell",
     icon: "💊",
     description: "Produces antibodies to neutralize threats",
-    stats: { health: 60, maxHealth: 60, attack: 8, defense: 8, movementRange: 2, energyCost: 25 },
-    specialAbilities: [SpecialAbility.AntibodyProduction, SpecialAbility.MemoryResponse],
+    ...unitRules(UnitType.BCell),
     faction: Faction.ImmuneSystem,
-    cost: { energy: 25, antibodies: 2, stemCells: 2, nutrients: 6 }
   },
   
   [UnitType.Macrophage]: {
@@ -206,10 +252,8 @@ export const UNIT_DATA: Record<UnitType, UnitInfo> = {
     name: "Macrophage",
     icon: "🍽️",
     description: "Large phagocyte that engulfs and destroys pathogens",
-    stats: { health: 120, maxHealth: 120, attack: 20, defense: 15, movementRange: 2, energyCost: 30 },
-    specialAbilities: [SpecialAbility.Phagocytosis, SpecialAbility.CytokineRelease],
+    ...unitRules(UnitType.Macrophage),
     faction: Faction.ImmuneSystem,
-    cost: { energy: 30, antibodies: 8, stemCells: 2, nutrients: 12 }
   },
   
   [UnitType.NeutrophilCell]: {
@@ -217,10 +261,8 @@ export const UNIT_DATA: Record<UnitType, UnitInfo> = {
     name: "Neutrophil",
     icon: "⚡",
     description: "Fast-response immune cell, first line of defense",
-    stats: { health: 70, maxHealth: 70, attack: 18, defense: 8, movementRange: 4, energyCost: 15 },
-    specialAbilities: [SpecialAbility.Phagocytosis, SpecialAbility.Infiltration],
+    ...unitRules(UnitType.NeutrophilCell),
     faction: Faction.ImmuneSystem,
-    cost: { energy: 15, antibodies: 3, stemCells: 1, nutrients: 5 }
   },
   
   [UnitType.DendriticCell]: {
@@ -228,10 +270,8 @@ export const UNIT_DATA: Record<UnitType, UnitInfo> = {
     name: "Dendritic Cell",
     icon: "📡",
     description: "Antigen-presenting cell that activates other immune cells",
-    stats: { health: 50, maxHealth: 50, attack: 5, defense: 12, movementRange: 3, energyCost: 35 },
-    specialAbilities: [SpecialAbility.CytokineRelease, SpecialAbility.Infiltration],
+    ...unitRules(UnitType.DendriticCell),
     faction: Faction.ImmuneSystem,
-    cost: { energy: 35, antibodies: 12, stemCells: 3, nutrients: 8 }
   },
   
   [UnitType.NaturalKillerCell]: {
@@ -239,10 +279,8 @@ export const UNIT_DATA: Record<UnitType, UnitInfo> = {
     name: "NK Cell",
     icon: "🗡️",
     description: "Elite killer that destroys compromised cells",
-    stats: { health: 90, maxHealth: 90, attack: 25, defense: 10, movementRange: 3, energyCost: 40 },
-    specialAbilities: [SpecialAbility.CytokineRelease, SpecialAbility.ZoneHealing],
+    ...unitRules(UnitType.NaturalKillerCell),
     faction: Faction.ImmuneSystem,
-    cost: { energy: 40, antibodies: 10, stemCells: 3, nutrients: 15 }
   },
   
   [UnitType.Virus]: {
@@ -250,10 +288,8 @@ export const UNIT_DATA: Record<UnitType, UnitInfo> = {
     name: "Virus",
     icon: "🦠",
     description: "Infectious agent that hijacks cellular machinery",
-    stats: { health: 40, maxHealth: 40, attack: 12, defense: 5, movementRange: 4, energyCost: 10 },
-    specialAbilities: [SpecialAbility.Replication, SpecialAbility.ImmuneEvasion],
+    ...unitRules(UnitType.Virus),
     faction: Faction.Pathogen,
-    cost: { energy: 10, antibodies: 0, stemCells: 0, nutrients: 4 }
   },
   
   [UnitType.Bacteria]: {
@@ -261,10 +297,8 @@ export const UNIT_DATA: Record<UnitType, UnitInfo> = {
     name: "Bacteria",
     icon: "🧫",
     description: "Bacterial pathogen that multiplies rapidly",
-    stats: { health: 60, maxHealth: 60, attack: 15, defense: 8, movementRange: 2, energyCost: 15 },
-    specialAbilities: [SpecialAbility.Replication, SpecialAbility.ToxinRelease],
+    ...unitRules(UnitType.Bacteria),
     faction: Faction.Pathogen,
-    cost: { energy: 15, antibodies: 0, stemCells: 0, nutrients: 6 }
   },
   
   [UnitType.Fungus]: {
@@ -272,10 +306,8 @@ export const UNIT_DATA: Record<UnitType, UnitInfo> = {
     name: "Fungus",
     icon: "🍄",
     description: "Fungal infection that spreads through spores",
-    stats: { health: 80, maxHealth: 80, attack: 10, defense: 12, movementRange: 1, energyCost: 20 },
-    specialAbilities: [SpecialAbility.Replication, SpecialAbility.ResourceDrain],
+    ...unitRules(UnitType.Fungus),
     faction: Faction.Pathogen,
-    cost: { energy: 20, antibodies: 0, stemCells: 0, nutrients: 8 }
   },
   
   [UnitType.Parasite]: {
@@ -283,10 +315,8 @@ export const UNIT_DATA: Record<UnitType, UnitInfo> = {
     name: "Parasite",
     icon: "🪱",
     description: "Parasitic organism that drains host resources",
-    stats: { health: 70, maxHealth: 70, attack: 18, defense: 6, movementRange: 3, energyCost: 25 },
-    specialAbilities: [SpecialAbility.ImmuneEvasion, SpecialAbility.ResourceDrain],
+    ...unitRules(UnitType.Parasite),
     faction: Faction.Pathogen,
-    cost: { energy: 25, antibodies: 0, stemCells: 0, nutrients: 10 }
   },
   
   [UnitType.CancerCell]: {
@@ -294,10 +324,8 @@ export const UNIT_DATA: Record<UnitType, UnitInfo> = {
     name: "Cancer Cell",
     icon: "⚫",
     description: "Malignant cell that grows uncontrollably",
-    stats: { health: 100, maxHealth: 100, attack: 20, defense: 10, movementRange: 2, energyCost: 30 },
-    specialAbilities: [SpecialAbility.Replication, SpecialAbility.Metastasis],
+    ...unitRules(UnitType.CancerCell),
     faction: Faction.Pathogen,
-    cost: { energy: 30, antibodies: 0, stemCells: 0, nutrients: 12 }
   },
   
   [UnitType.Toxin]: {
@@ -305,54 +333,18 @@ export const UNIT_DATA: Record<UnitType, UnitInfo> = {
     name: "Toxin",
     icon: "☠️",
     description: "Poisonous substance that damages tissue",
-    stats: { health: 30, maxHealth: 30, attack: 30, defense: 2, movementRange: 5, energyCost: 5 },
-    specialAbilities: [SpecialAbility.ToxinRelease, SpecialAbility.ResourceDrain],
+    ...unitRules(UnitType.Toxin),
     faction: Faction.Pathogen,
-    cost: { energy: 5, antibodies: 0, stemCells: 0, nutrients: 2 }
   }
 };
 
 export const getZoneResourceGeneration = (zoneType: ZoneType): ZoneResources => {
-  switch (zoneType) {
-    case ZoneType.Circulatory:
-      return { energy: 10, antibodies: 5, stemCells: 2, nutrients: 8 };
-    case ZoneType.Tissue:
-      return { energy: 5, antibodies: 15, stemCells: 1, nutrients: 10 };
-    case ZoneType.Lymphatic:
-      return { energy: 8, antibodies: 20, stemCells: 5, nutrients: 5 };
-    case ZoneType.Barrier:
-      return { energy: 3, antibodies: 25, stemCells: 1, nutrients: 3 };
-    case ZoneType.Organ:
-      return { energy: 15, antibodies: 10, stemCells: 3, nutrients: 15 };
-  }
+  const [energy, antibodies, stemCells, nutrients] = Array.from(rules.zoneResourceGeneration(zoneTypeIndex(zoneType))!);
+  return { energy, antibodies, stemCells, nutrients };
 };
 
-export const getZoneDefenseBonus = (zoneType: ZoneType): number => {
-  switch (zoneType) {
-    case ZoneType.Circulatory:
-      return 0;
-    case ZoneType.Tissue:
-      return 2;
-    case ZoneType.Lymphatic:
-      return 3;
-    case ZoneType.Barrier:
-      return 5;
-    case ZoneType.Organ:
-      return 1;
-  }
-};
+export const getZoneDefenseBonus = (zoneType: ZoneType): number =>
+  rules.zoneDefenseBonus(zoneTypeIndex(zoneType))!;
 
-export const getZoneMovementCost = (zoneType: ZoneType): number => {
-  switch (zoneType) {
-    case ZoneType.Circulatory:
-      return 1;
-    case ZoneType.Tissue:
-      return 3;
-    case ZoneType.Lymphatic:
-      return 2;
-    case ZoneType.Barrier:
-      return 4;
-    case ZoneType.Organ:
-      return 2;
-  }
-};
+export const getZoneMovementCost = (zoneType: ZoneType): number =>
+  rules.zoneMovementCost(zoneTypeIndex(zoneType))!;
@@ -41,6 +41,7 @@ export const createEmptyGrid = (): Cell[][] => {
 
 export const createInitialZone = (zoneId: number, zoneType: ZoneType): Zone => ({
     zoneId,
+    entity: "11111111111111111111111111111111", // Local zone, not on chain
     zoneType,
     name: `${zoneType} Zone ${zoneId}`, // Generate name from zone type and ID
     x: 0,
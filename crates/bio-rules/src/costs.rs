@@ -0,0 +1,260 @@
+use crate::{Faction, UnitType, ZoneType};
+
+// Multipliers are in basis points, so 10_000 leaves a cost unchanged
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// (energy, antibodies, stem_cells, nutrients); every cost function returns
+// None if the arithmetic overflows
+pub type Cost = (u64, u64, u64, u64);
+
+pub fn apply_bps(amount: u64, bps: u64) -> Option<u64> {
+    amount.checked_mul(bps).map(|scaled| scaled / BPS_DENOMINATOR)
+}
+
+pub fn spawn_cost(unit_type: UnitType, zone_type: ZoneType) -> Option<Cost> {
+    let (_, _, _, _, base_cost) = unit_type.get_base_stats();
+    let zone_multiplier = match zone_type {
+        ZoneType::Lymphatic => 8_000, // Cheaper in lymphatic zones
+        ZoneType::Barrier => 15_000,  // More expensive in barrier zones
+        _ => BPS_DENOMINATOR,
+    };
+
+    let adjusted_cost = apply_bps(base_cost as u64, zone_multiplier)?;
+
+    match unit_type.is_immune_cell() {
+        true => Some((adjusted_cost, adjusted_cost / 2, adjusted_cost / 10, adjusted_cost / 3)),
+        false => Some((adjusted_cost.checked_mul(2)?, 0, 0, adjusted_cost)),
+    }
+}
+
+pub fn infection_spread_cost(target: ZoneType) -> Option<Cost> {
+    let base_cost = 100u64;
+    let zone_resistance = match target {
+        ZoneType::Barrier => 30_000,
+        ZoneType::Lymphatic => 20_000,
+        _ => BPS_DENOMINATOR,
+    };
+
+    let adjusted_cost = apply_bps(base_cost, zone_resistance)?;
+    Some((adjusted_cost.checked_mul(2)?, 0, 0, adjusted_cost)) // High energy and nutrient cost
+}
+
+pub fn immune_response_cost(target: ZoneType) -> Option<Cost> {
+    let base_cost = 80u64;
+    let zone_difficulty = match target {
+        ZoneType::Tissue => 20_000, // Harder to establish in infected tissue
+        ZoneType::Organ => 30_000,  // Very difficult in organs
+        _ => BPS_DENOMINATOR,
+    };
+
+    let adjusted_cost = apply_bps(base_cost, zone_difficulty)?;
+    Some((adjusted_cost, adjusted_cost.checked_mul(2)?, adjusted_cost / 4, adjusted_cost / 2))
+}
+
+pub fn zone_creation_cost(zone_type: ZoneType, faction: Faction) -> Option<Cost> {
+    let base_cost = match zone_type {
+        ZoneType::Circulatory => 200,
+        ZoneType::Tissue => 150,
+        ZoneType::Lymphatic => 300,
+        ZoneType::Barrier => 400,
+        ZoneType::Organ => 500,
+    };
+
+    let faction_modifier = match faction {
+        Faction::ImmuneSystem => BPS_DENOMINATOR,
+        Faction::Pathogen => 12_000, // Slightly more expensive for pathogens
+    };
+
+    let adjusted_cost = apply_bps(base_cost, faction_modifier)?;
+    Some((adjusted_cost, adjusted_cost / 2, adjusted_cost / 10, adjusted_cost / 3))
+}
+
+pub fn conquest_cost(target: ZoneType) -> Option<Cost> {
+    let base_cost = 250u64;
+    let defense_multiplier = match target {
+        ZoneType::Barrier => 20_000,
+        ZoneType::Organ => 30_000,
+        ZoneType::Lymphatic => 20_000,
+        _ => BPS_DENOMINATOR,
+    };
+
+    let adjusted_cost = apply_bps(base_cost, defense_multiplier)?;
+    Some((
+        adjusted_cost.checked_mul(3)?,
+        adjusted_cost,
+        adjusted_cost / 5,
+        adjusted_cost.checked_mul(2)?,
+    ))
+}
+
+// Energy a unit pays per movement point of the zone it crosses into
+pub const CROSSING_ENERGY_PER_MOVEMENT_POINT: u64 = 10;
+
+pub fn crossing_cost(destination: ZoneType) -> Option<Cost> {
+    let energy = (destination.get_movement_cost() as u64).checked_mul(CROSSING_ENERGY_PER_MOVEMENT_POINT)?;
+    Some((energy, 0, 0, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Expected values are the ones the previous f64 formulas produced
+
+    #[test]
+    fn spawn_costs_match_table() {
+        let expected = [
+            (UnitType::TCell, ZoneType::Circulatory, (20, 10, 2, 6)),
+            (UnitType::TCell, ZoneType::Tissue, (20, 10, 2, 6)),
+            (UnitType::TCell, ZoneType::Lymphatic, (16, 8, 1, 5)),
+            (UnitType::TCell, ZoneType::Barrier, (30, 15, 3, 10)),
+            (UnitType::TCell, ZoneType::Organ, (20, 10, 2, 6)),
+            (UnitType::BCell, ZoneType::Circulatory, (25, 12, 2, 8)),
+            (UnitType::BCell, ZoneType::Tissue, (25, 12, 2, 8)),
+            (UnitType::BCell, ZoneType::Lymphatic, (20, 10, 2, 6)),
+            (UnitType::BCell, ZoneType::Barrier, (37, 18, 3, 12)),
+            (UnitType::BCell, ZoneType::Organ, (25, 12, 2, 8)),
+            (UnitType::Macrophage, ZoneType::Circulatory, (30, 15, 3, 10)),
+            (UnitType::Macrophage, ZoneType::Tissue, (30, 15, 3, 10)),
+            (UnitType::Macrophage, ZoneType::Lymphatic, (24, 12, 2, 8)),
+            (UnitType::Macrophage, ZoneType::Barrier, (45, 22, 4, 15)),
+            (UnitType::Macrophage, ZoneType::Organ, (30, 15, 3, 10)),
+            (UnitType::NeutrophilCell, ZoneType::Circulatory, (15, 7, 1, 5)),
+            (UnitType::NeutrophilCell, ZoneType::Tissue, (15, 7, 1, 5)),
+            (UnitType::NeutrophilCell, ZoneType::Lymphatic, (12, 6, 1, 4)),
+            (UnitType::NeutrophilCell, ZoneType::Barrier, (22, 11, 2, 7)),
+            (UnitType::NeutrophilCell, ZoneType::Organ, (15, 7, 1, 5)),
+            (UnitType::DendriticCell, ZoneType::Circulatory, (35, 17, 3, 11)),
+            (UnitType::DendriticCell, ZoneType::Tissue, (35, 17, 3, 11)),
+            (UnitType::DendriticCell, ZoneType::Lymphatic, (28, 14, 2, 9)),
+            (UnitType::DendriticCell, ZoneType::Barrier, (52, 26, 5, 17)),
+            (UnitType::DendriticCell, ZoneType::Organ, (35, 17, 3, 11)),
+            (UnitType::NaturalKillerCell, ZoneType::Circulatory, (40, 20, 4, 13)),
+            (UnitType::NaturalKillerCell, ZoneType::Tissue, (40, 20, 4, 13)),
+            (UnitType::NaturalKillerCell, ZoneType::Lymphatic, (32, 16, 3, 10)),
+            (UnitType::NaturalKillerCell, ZoneType::Barrier, (60, 30, 6, 20)),
+            (UnitType::NaturalKillerCell, ZoneType::Organ, (40, 20, 4, 13)),
+            (UnitType::Virus, ZoneType::Circulatory, (20, 0, 0, 10)),
+            (UnitType::Virus, ZoneType::Tissue, (20, 0, 0, 10)),
+            (UnitType::Virus, ZoneType::Lymphatic, (16, 0, 0, 8)),
+            (UnitType::Virus, ZoneType::Barrier, (30, 0, 0, 15)),
+            (UnitType::Virus, ZoneType::Organ, (20, 0, 0, 10)),
+            (UnitType::Bacteria, ZoneType::Circulatory, (30, 0, 0, 15)),
+            (UnitType::Bacteria, ZoneType::Tissue, (30, 0, 0, 15)),
+            (UnitType::Bacteria, ZoneType::Lymphatic, (24, 0, 0, 12)),
+            (UnitType::Bacteria, ZoneType::Barrier, (44, 0, 0, 22)),
+            (UnitType::Bacteria, ZoneType::Organ, (30, 0, 0, 15)),
+            (UnitType::Fungus, ZoneType::Circulatory, (40, 0, 0, 20)),
+            (UnitType::Fungus, ZoneType::Tissue, (40, 0, 0, 20)),
+            (UnitType::Fungus, ZoneType::Lymphatic, (32, 0, 0, 16)),
+            (UnitType::Fungus, ZoneType::Barrier, (60, 0, 0, 30)),
+            (UnitType::Fungus, ZoneType::Organ, (40, 0, 0, 20)),
+            (UnitType::Parasite, ZoneType::Circulatory, (50, 0, 0, 25)),
+            (UnitType::Parasite, ZoneType::Tissue, (50, 0, 0, 25)),
+            (UnitType::Parasite, ZoneType::Lymphatic, (40, 0, 0, 20)),
+            (UnitType::Parasite, ZoneType::Barrier, (74, 0, 0, 37)),
+            (UnitType::Parasite, ZoneType::Organ, (50, 0, 0, 25)),
+            (UnitType::CancerCell, ZoneType::Circulatory, (60, 0, 0, 30)),
+            (UnitType::CancerCell, ZoneType::Tissue, (60, 0, 0, 30)),
+            (UnitType::CancerCell, ZoneType::Lymphatic, (48, 0, 0, 24)),
+            (UnitType::CancerCell, ZoneType::Barrier, (90, 0, 0, 45)),
+            (UnitType::CancerCell, ZoneType::Organ, (60, 0, 0, 30)),
+            (UnitType::Toxin, ZoneType::Circulatory, (10, 0, 0, 5)),
+            (UnitType::Toxin, ZoneType::Tissue, (10, 0, 0, 5)),
+            (UnitType::Toxin, ZoneType::Lymphatic, (8, 0, 0, 4)),
+            (UnitType::Toxin, ZoneType::Barrier, (14, 0, 0, 7)),
+            (UnitType::Toxin, ZoneType::Organ, (10, 0, 0, 5)),
+        ];
+
+        for (unit_type, zone_type, cost) in expected {
+            assert_eq!(spawn_cost(unit_type, zone_type), Some(cost));
+        }
+    }
+
+    #[test]
+    fn infection_spread_costs_match_table() {
+        let expected = [
+            (ZoneType::Circulatory, (200, 0, 0, 100)),
+            (ZoneType::Tissue, (200, 0, 0, 100)),
+            (ZoneType::Lymphatic, (400, 0, 0, 200)),
+            (ZoneType::Barrier, (600, 0, 0, 300)),
+            (ZoneType::Organ, (200, 0, 0, 100)),
+        ];
+
+        for (zone_type, cost) in expected {
+            assert_eq!(infection_spread_cost(zone_type), Some(cost));
+        }
+    }
+
+    #[test]
+    fn immune_response_costs_match_table() {
+        let expected = [
+            (ZoneType::Circulatory, (80, 160, 20, 40)),
+            (ZoneType::Tissue, (160, 320, 40, 80)),
+            (ZoneType::Lymphatic, (80, 160, 20, 40)),
+            (ZoneType::Barrier, (80, 160, 20, 40)),
+            (ZoneType::Organ, (240, 480, 60, 120)),
+        ];
+
+        for (zone_type, cost) in expected {
+            assert_eq!(immune_response_cost(zone_type), Some(cost));
+        }
+    }
+
+    #[test]
+    fn zone_creation_costs_match_table() {
+        let expected = [
+            (ZoneType::Circulatory, Faction::ImmuneSystem, (200, 100, 20, 66)),
+            (ZoneType::Tissue, Faction::ImmuneSystem, (150, 75, 15, 50)),
+            (ZoneType::Lymphatic, Faction::ImmuneSystem, (300, 150, 30, 100)),
+            (ZoneType::Barrier, Faction::ImmuneSystem, (400, 200, 40, 133)),
+            (ZoneType::Organ, Faction::ImmuneSystem, (500, 250, 50, 166)),
+            (ZoneType::Circulatory, Faction::Pathogen, (240, 120, 24, 80)),
+            (ZoneType::Tissue, Faction::Pathogen, (180, 90, 18, 60)),
+            (ZoneType::Lymphatic, Faction::Pathogen, (360, 180, 36, 120)),
+            (ZoneType::Barrier, Faction::Pathogen, (480, 240, 48, 160)),
+            (ZoneType::Organ, Faction::Pathogen, (600, 300, 60, 200)),
+        ];
+
+        for (zone_type, faction, cost) in expected {
+            assert_eq!(zone_creation_cost(zone_type, faction), Some(cost));
+        }
+    }
+
+    #[test]
+    fn conquest_costs_match_table() {
+        let expected = [
+            (ZoneType::Circulatory, (750, 250, 50, 500)),
+            (ZoneType::Tissue, (750, 250, 50, 500)),
+            (ZoneType::Lymphatic, (1500, 500, 100, 1000)),
+            (ZoneType::Barrier, (1500, 500, 100, 1000)),
+            (ZoneType::Organ, (2250, 750, 150, 1500)),
+        ];
+
+        for (zone_type, cost) in expected {
+            assert_eq!(conquest_cost(zone_type), Some(cost));
+        }
+    }
+
+    #[test]
+    fn apply_bps_rounds_down_and_checks_overflow() {
+        assert_eq!(apply_bps(25, 15_000), Some(37));
+        assert_eq!(apply_bps(15, 8_000), Some(12));
+        assert_eq!(apply_bps(u64::MAX, 20_000), None);
+    }
+
+    #[test]
+    fn crossing_costs_match_table() {
+        let expected = [
+            (ZoneType::Circulatory, (10, 0, 0, 0)),
+            (ZoneType::Tissue, (30, 0, 0, 0)),
+            (ZoneType::Lymphatic, (20, 0, 0, 0)),
+            (ZoneType::Barrier, (40, 0, 0, 0)),
+            (ZoneType::Organ, (20, 0, 0, 0)),
+        ];
+
+        for (zone_type, cost) in expected {
+            assert_eq!(crossing_cost(zone_type), Some(cost));
+        }
+    }
+}
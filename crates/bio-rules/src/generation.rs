@@ -0,0 +1,205 @@
+use crate::{ResourceType, ZoneType};
+
+// Interiors of created zones, generated from the game seed and the zone's map
+// tile. The expand-zone system fills a new zone with `generate_interior`, and
+// clients run the same code to check a zone's grid against its game's seed.
+
+pub const GRID_SIZE: u8 = 16; // Same as grid::GRID_SIZE
+const LAST: u8 = GRID_SIZE - 1;
+
+// Resource nodes start with an amount in this range
+pub const MIN_RESOURCE_AMOUNT: u16 = 100;
+pub const MAX_RESOURCE_AMOUNT: u16 = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Feature {
+    Obstacle,
+    Resource { resource_type: ResourceType, amount: u16 },
+}
+
+// What a random stream is drawn for, so that one zone's gates do not depend
+// on what its interior holds
+const INTERIOR: u64 = 1;
+const VERTICAL_BORDER: u64 = 2; // Between a tile and the one east of it
+const HORIZONTAL_BORDER: u64 = 3; // Between a tile and the one south of it
+
+/// Every feature of a `zone_type` zone on map tile (x, y), as (x, y, feature)
+/// cells; all other cells are empty. The edges are walls except for the
+/// zone's gates. Obstacles and resource nodes stay off the ring of cells just
+/// inside the walls, so every gate can reach every other.
+pub fn generate_interior(game_seed: u64, zone_type: ZoneType, x: u8, y: u8) -> Vec<(u8, u8, Feature)> {
+    let (obstacles, resource_nodes, _) = zone_type.get_interior_layout();
+    let mut features = Vec::new();
+
+    for corner in [(0, 0), (LAST, 0), (0, LAST), (LAST, LAST)] {
+        features.push((corner.0, corner.1, Feature::Obstacle));
+    }
+    for (side, gates) in gates(game_seed, zone_type, x, y).iter().enumerate() {
+        for position in (1..LAST).filter(|position| !gates.contains(position)) {
+            let (x, y) = edge_cell(side, position);
+            features.push((x, y, Feature::Obstacle));
+        }
+    }
+
+    let mut rng = Rng::new(game_seed, INTERIOR, x as i16, y as i16);
+    let mut cells: Vec<(u8, u8)> = (2..LAST - 1).flat_map(|x| (2..LAST - 1).map(move |y| (x, y))).collect();
+    let placed = (obstacles + resource_nodes) as usize;
+    rng.shuffle_prefix(&mut cells, placed);
+    for (index, &(x, y)) in cells[..placed].iter().enumerate() {
+        let feature = match index < obstacles as usize {
+            true => Feature::Obstacle,
+            false => rng.resource_node(zone_type),
+        };
+        features.push((x, y, feature));
+    }
+
+    features
+}
+
+/// Positions along each side of the zone, North, East, South, West, of its
+/// gates: x on the North and South sides, y on the others. Both zones along a
+/// border draw its gates from the same shuffle and open as many as their type
+/// has, so all gates of the side with fewer face gates of the other.
+pub fn gates(game_seed: u64, zone_type: ZoneType, x: u8, y: u8) -> [Vec<u8>; 4] {
+    let (_, _, count) = zone_type.get_interior_layout();
+    let (x, y) = (x as i16, y as i16);
+    [
+        border_gates(game_seed, HORIZONTAL_BORDER, x, y - 1, count),
+        border_gates(game_seed, VERTICAL_BORDER, x, y, count),
+        border_gates(game_seed, HORIZONTAL_BORDER, x, y, count),
+        border_gates(game_seed, VERTICAL_BORDER, x - 1, y, count),
+    ]
+}
+
+// The border is identified by the tile west or north of it, which may lie
+// off the map
+fn border_gates(game_seed: u64, border: u64, x: i16, y: i16, count: u8) -> Vec<u8> {
+    let mut positions: Vec<u8> = (1..LAST).collect();
+    let count = (count as usize).min(positions.len());
+    Rng::new(game_seed, border, x, y).shuffle_prefix(&mut positions, count);
+    positions.truncate(count);
+    positions
+}
+
+fn edge_cell(side: usize, position: u8) -> (u8, u8) {
+    match side {
+        0 => (position, 0),
+        1 => (LAST, position),
+        2 => (position, LAST),
+        _ => (0, position),
+    }
+}
+
+// SplitMix64, small and easy to port to clients
+struct Rng(u64);
+
+impl Rng {
+    const GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+    fn new(game_seed: u64, stream: u64, x: i16, y: i16) -> Self {
+        let key = (stream << 32) | ((x as u16 as u64) << 16) | y as u16 as u64;
+        Rng(game_seed ^ key.wrapping_mul(Self::GAMMA))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(Self::GAMMA);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    // Moves `count` randomly chosen items to the front, in random order
+    fn shuffle_prefix<T>(&mut self, items: &mut [T], count: usize) {
+        for index in 0..count.min(items.len()) {
+            let other = index + self.below((items.len() - index) as u64) as usize;
+            items.swap(index, other);
+        }
+    }
+
+    // Resource types are as likely as the zone's generation of them
+    fn resource_node(&mut self, zone_type: ZoneType) -> Feature {
+        let (energy, antibodies, stem_cells, nutrients) = zone_type.get_resource_generation();
+        let weights = [energy, antibodies, stem_cells, nutrients];
+        let mut roll = self.below(weights.iter().sum::<u32>() as u64) as u32;
+        let mut resource_type = ResourceType::Nutrients;
+        for (index, weight) in weights.into_iter().enumerate() {
+            if roll < weight {
+                resource_type = ResourceType::ALL[index];
+                break;
+            }
+            roll -= weight;
+        }
+
+        let spread = (MAX_RESOURCE_AMOUNT - MIN_RESOURCE_AMOUNT + 1) as u64;
+        let amount = MIN_RESOURCE_AMOUNT + self.below(spread) as u16;
+        Feature::Resource { resource_type, amount }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: u64 = 0x5EED;
+
+    fn feature_at(features: &[(u8, u8, Feature)], x: u8, y: u8) -> Option<Feature> {
+        features.iter().find(|feature| (feature.0, feature.1) == (x, y)).map(|feature| feature.2)
+    }
+
+    #[test]
+    fn interiors_follow_the_zone_type() {
+        for zone_type in ZoneType::ALL {
+            let (obstacles, resource_nodes, gates_per_side) = zone_type.get_interior_layout();
+            let features = generate_interior(SEED, zone_type, 2, 3);
+
+            let walls = 4 * (GRID_SIZE as usize - 1) - 4 * gates_per_side as usize;
+            let nodes = features.iter().filter(|feature| matches!(feature.2, Feature::Resource { .. })).count();
+            assert_eq!(features.len(), walls + obstacles as usize + resource_nodes as usize);
+            assert_eq!(nodes, resource_nodes as usize);
+
+            // Nothing on the ring just inside the walls, and one feature per cell
+            let inside = |position: u8| (1..LAST).contains(&position);
+            let ring = |position: u8| [1, LAST - 1].contains(&position);
+            assert!(features.iter().all(|&(x, y, _)| !(inside(x) && inside(y) && (ring(x) || ring(y)))));
+            let mut cells: Vec<_> = features.iter().map(|&(x, y, _)| (x, y)).collect();
+            cells.sort();
+            cells.dedup();
+            assert_eq!(cells.len(), features.len());
+        }
+    }
+
+    #[test]
+    fn interiors_depend_only_on_the_seed_and_tile() {
+        let interior = generate_interior(SEED, ZoneType::Organ, 1, 0);
+
+        assert_eq!(generate_interior(SEED, ZoneType::Organ, 1, 0), interior);
+        assert_ne!(generate_interior(SEED + 1, ZoneType::Organ, 1, 0), interior);
+        assert_ne!(generate_interior(SEED, ZoneType::Organ, 0, 1), interior);
+    }
+
+    #[test]
+    fn gates_face_each_other_across_a_border() {
+        // A Barrier zone has one gate per side, a Circulatory zone four
+        let west = generate_interior(SEED, ZoneType::Barrier, 1, 1);
+        let east = generate_interior(SEED, ZoneType::Circulatory, 2, 1);
+        let north = generate_interior(SEED, ZoneType::Circulatory, 1, 0);
+
+        let east_gates = gates(SEED, ZoneType::Barrier, 1, 1)[1].clone();
+        assert_eq!(east_gates.len(), 1);
+        assert!(gates(SEED, ZoneType::Circulatory, 2, 1)[3].starts_with(&east_gates));
+        for y in east_gates {
+            assert_eq!(feature_at(&west, LAST, y), None);
+            assert_eq!(feature_at(&east, 0, y), None);
+        }
+
+        for x in gates(SEED, ZoneType::Barrier, 1, 1)[0].clone() {
+            assert_eq!(feature_at(&west, x, 0), None);
+            assert_eq!(feature_at(&north, x, LAST), None);
+        }
+    }
+}
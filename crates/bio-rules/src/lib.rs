@@ -0,0 +1,328 @@
+// Game rules with no Solana dependencies. The components and systems read
+// their tables and formulas from here, and clients compile the same code to
+// WASM so their previews match what the programs charge.
+
+pub mod costs;
+pub mod generation;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+// Resources a unit extracts from a resource node with one Harvest
+pub const HARVEST_AMOUNT: u16 = 25;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnitType {
+    // Immune Cells
+    TCell,
+    BCell,
+    Macrophage,
+    NeutrophilCell,
+    DendriticCell,
+    NaturalKillerCell,
+
+    // Pathogens
+    Virus,
+    Bacteria,
+    Fungus,
+    Parasite,
+    CancerCell,
+    Toxin,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecialAbility {
+    // Immune Cell Abilities
+    AntibodyProduction,
+    Phagocytosis,
+    CytokineRelease,
+    MemoryResponse,
+    Infiltration,
+    ZoneHealing,
+
+    // Pathogen Abilities
+    Replication,
+    Mutation,
+    ToxinRelease,
+    ImmuneEvasion,
+    Metastasis,
+    ResourceDrain,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZoneType {
+    Circulatory,
+    Tissue,
+    Lymphatic,
+    Barrier,
+    Organ,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResourceType {
+    Energy,
+    Antibodies,
+    StemCells,
+    Nutrients,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Faction {
+    ImmuneSystem,
+    Pathogen,
+}
+
+impl UnitType {
+    pub const ALL: [UnitType; 12] = [
+        UnitType::TCell,
+        UnitType::BCell,
+        UnitType::Macrophage,
+        UnitType::NeutrophilCell,
+        UnitType::DendriticCell,
+        UnitType::NaturalKillerCell,
+        UnitType::Virus,
+        UnitType::Bacteria,
+        UnitType::Fungus,
+        UnitType::Parasite,
+        UnitType::CancerCell,
+        UnitType::Toxin,
+    ];
+
+    // Same numbering as the play system's unit_type argument
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    pub fn get_base_stats(&self) -> (u16, u16, u16, u8, u16) {
+        // Returns (health, attack, defense, movement_range, energy_cost)
+        match self {
+            // Immune Cells
+            UnitType::TCell => (80, 15, 10, 3, 20),
+            UnitType::BCell => (60, 8, 8, 2, 25),
+            UnitType::Macrophage => (120, 20, 15, 2, 30),
+            UnitType::NeutrophilCell => (70, 18, 8, 4, 15),
+            UnitType::DendriticCell => (50, 5, 12, 3, 35),
+            UnitType::NaturalKillerCell => (90, 25, 10, 3, 40),
+
+            // Pathogens
+            UnitType::Virus => (40, 12, 5, 4, 10),
+            UnitType::Bacteria => (60, 15, 8, 2, 15),
+            UnitType::Fungus => (80, 10, 12, 1, 20),
+            UnitType::Parasite => (70, 18, 6, 3, 25),
+            UnitType::CancerCell => (100, 20, 10, 2, 30),
+            UnitType::Toxin => (30, 30, 2, 5, 5),
+        }
+    }
+
+    pub fn get_attack_range(&self) -> u8 {
+        // Manhattan distance in cells
+        match self {
+            UnitType::BCell => 3,         // Antibodies act at a distance
+            UnitType::DendriticCell => 2,
+            UnitType::Fungus => 2,        // Spores
+            UnitType::Toxin => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn get_default_abilities(&self) -> [Option<SpecialAbility>; 3] {
+        // The abilities a unit of this type spawns with, in ability slot order
+        match self {
+            // Immune Cells
+            UnitType::TCell => [Some(SpecialAbility::CytokineRelease), Some(SpecialAbility::MemoryResponse), None],
+            UnitType::BCell => [Some(SpecialAbility::AntibodyProduction), Some(SpecialAbility::MemoryResponse), None],
+            UnitType::Macrophage => [Some(SpecialAbility::Phagocytosis), Some(SpecialAbility::CytokineRelease), None],
+            UnitType::NeutrophilCell => [Some(SpecialAbility::Phagocytosis), Some(SpecialAbility::Infiltration), None],
+            UnitType::DendriticCell => [Some(SpecialAbility::CytokineRelease), Some(SpecialAbility::Infiltration), None],
+            UnitType::NaturalKillerCell => [Some(SpecialAbility::CytokineRelease), Some(SpecialAbility::ZoneHealing), None],
+
+            // Pathogens
+            UnitType::Virus => [Some(SpecialAbility::Replication), Some(SpecialAbility::ImmuneEvasion), Some(SpecialAbility::Mutation)],
+            UnitType::Bacteria => [Some(SpecialAbility::Replication), Some(SpecialAbility::ToxinRelease), None],
+            UnitType::Fungus => [Some(SpecialAbility::Replication), Some(SpecialAbility::ResourceDrain), None],
+            UnitType::Parasite => [Some(SpecialAbility::ImmuneEvasion), Some(SpecialAbility::ResourceDrain), None],
+            UnitType::CancerCell => [Some(SpecialAbility::Replication), Some(SpecialAbility::Metastasis), None],
+            UnitType::Toxin => [Some(SpecialAbility::ToxinRelease), Some(SpecialAbility::ResourceDrain), None],
+        }
+    }
+
+    pub fn is_immune_cell(&self) -> bool {
+        matches!(self,
+            UnitType::TCell | UnitType::BCell | UnitType::Macrophage |
+            UnitType::NeutrophilCell | UnitType::DendriticCell | UnitType::NaturalKillerCell
+        )
+    }
+}
+
+impl SpecialAbility {
+    pub const ALL: [SpecialAbility; 12] = [
+        SpecialAbility::AntibodyProduction,
+        SpecialAbility::Phagocytosis,
+        SpecialAbility::CytokineRelease,
+        SpecialAbility::MemoryResponse,
+        SpecialAbility::Infiltration,
+        SpecialAbility::ZoneHealing,
+        SpecialAbility::Replication,
+        SpecialAbility::Mutation,
+        SpecialAbility::ToxinRelease,
+        SpecialAbility::ImmuneEvasion,
+        SpecialAbility::Metastasis,
+        SpecialAbility::ResourceDrain,
+    ];
+
+    // Same numbering as the unit component's SpecialAbility
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    pub fn get_cost(&self) -> costs::Cost {
+        // Returns (energy, antibodies, stem_cells, nutrients)
+        match self {
+            // Immune Cell Abilities
+            SpecialAbility::AntibodyProduction => (20, 0, 0, 10),
+            SpecialAbility::Phagocytosis => (5, 0, 0, 0),
+            SpecialAbility::CytokineRelease => (30, 10, 0, 0),
+            SpecialAbility::MemoryResponse => (20, 20, 5, 0),
+            SpecialAbility::Infiltration => (25, 0, 0, 10),
+            SpecialAbility::ZoneHealing => (10, 10, 0, 0),
+
+            // Pathogen Abilities
+            SpecialAbility::Replication => (30, 0, 0, 20),
+            SpecialAbility::Mutation => (25, 0, 0, 15),
+            SpecialAbility::ToxinRelease => (30, 0, 0, 10),
+            SpecialAbility::ImmuneEvasion => (20, 0, 0, 5),
+            SpecialAbility::Metastasis => (40, 0, 0, 30),
+            SpecialAbility::ResourceDrain => (10, 0, 0, 0),
+        }
+    }
+
+    pub fn get_cooldown(&self) -> u8 {
+        // Own turns before the ability can be used again
+        match self {
+            // Immune Cell Abilities
+            SpecialAbility::AntibodyProduction => 2,
+            SpecialAbility::Phagocytosis => 1,
+            SpecialAbility::CytokineRelease => 2,
+            SpecialAbility::MemoryResponse => 3,
+            SpecialAbility::Infiltration => 2,
+            SpecialAbility::ZoneHealing => 3,
+
+            // Pathogen Abilities
+            SpecialAbility::Replication => 2,
+            SpecialAbility::Mutation => 3,
+            SpecialAbility::ToxinRelease => 2,
+            SpecialAbility::ImmuneEvasion => 3,
+            SpecialAbility::Metastasis => 4,
+            SpecialAbility::ResourceDrain => 2,
+        }
+    }
+}
+
+impl ZoneType {
+    pub const ALL: [ZoneType; 5] = [
+        ZoneType::Circulatory,
+        ZoneType::Tissue,
+        ZoneType::Lymphatic,
+        ZoneType::Barrier,
+        ZoneType::Organ,
+    ];
+
+    // Same numbering as the expand-zone system's new_zone_type argument
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    pub fn get_movement_cost(&self) -> u8 {
+        match self {
+            ZoneType::Circulatory => 1,
+            ZoneType::Tissue => 3,
+            ZoneType::Lymphatic => 2,
+            ZoneType::Barrier => 4,
+            ZoneType::Organ => 2,
+        }
+    }
+
+    pub fn get_resource_generation(&self) -> (u32, u32, u32, u32) {
+        // Returns (energy, antibodies, stem_cells, nutrients) per turn
+        match self {
+            ZoneType::Circulatory => (10, 5, 2, 8),
+            ZoneType::Tissue => (5, 15, 1, 10),
+            ZoneType::Lymphatic => (8, 20, 5, 5),
+            ZoneType::Barrier => (3, 25, 1, 3),
+            ZoneType::Organ => (15, 10, 3, 15),
+        }
+    }
+
+    pub fn get_defense_bonus(&self) -> u16 {
+        match self {
+            ZoneType::Circulatory => 0,
+            ZoneType::Tissue => 2,
+            ZoneType::Lymphatic => 3,
+            ZoneType::Barrier => 5,
+            ZoneType::Organ => 1,
+        }
+    }
+
+    pub fn get_interior_layout(&self) -> (u8, u8, u8) {
+        // Returns (obstacles, resource_nodes, gates_per_side) of a generated zone
+        match self {
+            ZoneType::Circulatory => (6, 4, 4),
+            ZoneType::Tissue => (12, 4, 3),
+            ZoneType::Lymphatic => (8, 3, 2),
+            ZoneType::Barrier => (20, 2, 1),
+            ZoneType::Organ => (10, 6, 2),
+        }
+    }
+}
+
+impl ResourceType {
+    pub const ALL: [ResourceType; 4] = [
+        ResourceType::Energy,
+        ResourceType::Antibodies,
+        ResourceType::StemCells,
+        ResourceType::Nutrients,
+    ];
+
+    // Same numbering as the grid's ResourceType
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+}
+
+impl Faction {
+    // Same numbering as the join-game system's faction argument
+    pub fn from_index(index: u8) -> Option<Self> {
+        match index {
+            0 => Some(Faction::ImmuneSystem),
+            1 => Some(Faction::Pathogen),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indices_follow_declaration_order() {
+        // The WASM bindings hand out `as u8` casts, the systems take indices
+        for (index, unit_type) in UnitType::ALL.into_iter().enumerate() {
+            assert_eq!(unit_type as usize, index);
+        }
+        for (index, ability) in SpecialAbility::ALL.into_iter().enumerate() {
+            assert_eq!(ability as usize, index);
+        }
+    }
+
+    #[test]
+    fn units_spawn_with_abilities_of_their_side() {
+        // The first six abilities are the immune cells', the rest the pathogens'
+        for unit_type in UnitType::ALL {
+            let abilities = unit_type.get_default_abilities();
+            assert!(abilities[0].is_some(), "{unit_type:?} spawns without abilities");
+            for ability in abilities.into_iter().flatten() {
+                assert_eq!((ability as usize) < 6, unit_type.is_immune_cell(), "{unit_type:?} has {ability:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::generation::{self, Feature};
+use crate::{costs, Faction, SpecialAbility, UnitType, ZoneType};
+use wasm_bindgen::prelude::*;
+
+// Enum arguments are the same indices the systems take. Costs come back as
+// [energy, antibodies, stem_cells, nutrients], or undefined for an unknown
+// index.
+
+fn to_vec(cost: costs::Cost) -> Vec<u64> {
+    vec![cost.0, cost.1, cost.2, cost.3]
+}
+
+#[wasm_bindgen(js_name = unitBaseStats)]
+pub fn unit_base_stats(unit_type: u8) -> Option<Vec<u16>> {
+    let (health, attack, defense, movement_range, energy_cost) =
+        UnitType::from_index(unit_type)?.get_base_stats();
+    Some(vec![health, attack, defense, movement_range as u16, energy_cost])
+}
+
+// Ability indices of the slots a unit spawns with, 255 for an empty slot
+#[wasm_bindgen(js_name = unitDefaultAbilities)]
+pub fn unit_default_abilities(unit_type: u8) -> Option<Vec<u8>> {
+    let abilities = UnitType::from_index(unit_type)?.get_default_abilities();
+    Some(abilities.iter().map(|ability| ability.map_or(u8::MAX, |ability| ability as u8)).collect())
+}
+
+#[wasm_bindgen(js_name = zoneResourceGeneration)]
+pub fn zone_resource_generation(zone_type: u8) -> Option<Vec<u32>> {
+    let (energy, antibodies, stem_cells, nutrients) =
+        ZoneType::from_index(zone_type)?.get_resource_generation();
+    Some(vec![energy, antibodies, stem_cells, nutrients])
+}
+
+#[wasm_bindgen(js_name = zoneDefenseBonus)]
+pub fn zone_defense_bonus(zone_type: u8) -> Option<u16> {
+    Some(ZoneType::from_index(zone_type)?.get_defense_bonus())
+}
+
+#[wasm_bindgen(js_name = zoneMovementCost)]
+pub fn zone_movement_cost(zone_type: u8) -> Option<u8> {
+    Some(ZoneType::from_index(zone_type)?.get_movement_cost())
+}
+
+#[wasm_bindgen(js_name = abilityCost)]
+pub fn ability_cost(ability: u8) -> Option<Vec<u64>> {
+    Some(to_vec(SpecialAbility::from_index(ability)?.get_cost()))
+}
+
+#[wasm_bindgen(js_name = abilityCooldown)]
+pub fn ability_cooldown(ability: u8) -> Option<u8> {
+    Some(SpecialAbility::from_index(ability)?.get_cooldown())
+}
+
+#[wasm_bindgen(js_name = spawnCost)]
+pub fn spawn_cost(unit_type: u8, zone_type: u8) -> Option<Vec<u64>> {
+    costs::spawn_cost(UnitType::from_index(unit_type)?, ZoneType::from_index(zone_type)?).map(to_vec)
+}
+
+#[wasm_bindgen(js_name = infectionSpreadCost)]
+pub fn infection_spread_cost(target_zone_type: u8) -> Option<Vec<u64>> {
+    costs::infection_spread_cost(ZoneType::from_index(target_zone_type)?).map(to_vec)
+}
+
+#[wasm_bindgen(js_name = immuneResponseCost)]
+pub fn immune_response_cost(target_zone_type: u8) -> Option<Vec<u64>> {
+    costs::immune_response_cost(ZoneType::from_index(target_zone_type)?).map(to_vec)
+}
+
+#[wasm_bindgen(js_name = zoneCreationCost)]
+pub fn zone_creation_cost(zone_type: u8, faction: u8) -> Option<Vec<u64>> {
+    costs::zone_creation_cost(ZoneType::from_index(zone_type)?, Faction::from_index(faction)?).map(to_vec)
+}
+
+#[wasm_bindgen(js_name = conquestCost)]
+pub fn conquest_cost(target_zone_type: u8) -> Option<Vec<u64>> {
+    costs::conquest_cost(ZoneType::from_index(target_zone_type)?).map(to_vec)
+}
+
+#[wasm_bindgen(js_name = crossingCost)]
+pub fn crossing_cost(destination_zone_type: u8) -> Option<Vec<u64>> {
+    costs::crossing_cost(ZoneType::from_index(destination_zone_type)?).map(to_vec)
+}
+
+// Four numbers per cell, [x, y, kind, amount], with the cell kinds of the
+// packed zone grid: 3 for an obstacle, 4 plus the ResourceType index for a
+// resource node. Cells not listed are empty.
+#[wasm_bindgen(js_name = generateInterior)]
+pub fn generate_interior(game_seed: u64, zone_type: u8, x: u8, y: u8) -> Option<Vec<u16>> {
+    let features = generation::generate_interior(game_seed, ZoneType::from_index(zone_type)?, x, y);
+    let cells = features.into_iter().flat_map(|(x, y, feature)| {
+        let (kind, amount) = match feature {
+            Feature::Obstacle => (3, 0),
+            Feature::Resource { resource_type, amount } => (4 + resource_type as u16, amount),
+        };
+        [x as u16, y as u16, kind, amount]
+    });
+    Some(cells.collect())
+}
@@ -0,0 +1,52 @@
+// Compute and account size limits the benchmarks hold every system to
+
+// Compute units an instruction gets when the transaction requests no budget
+pub const DEFAULT_INSTRUCTION_UNITS: u64 = 200_000;
+
+// A benchmark consuming more than this fails. The measurements are whole
+// transactions through the world program's CPI, so they already include the
+// apply overhead; the 40k left under the default limit is for what they do
+// not cover: grids fuller than a benchmark's, and features added before the
+// next measurement. The report prints how far the heaviest action is below
+// this, so the margin can be checked against each run's numbers.
+pub const UNIT_THRESHOLD: u64 = 160_000;
+
+// Largest account a program can create through CPI, which is how the world
+// program initializes components (MAX_PERMITTED_DATA_INCREASE)
+pub const MAX_COMPONENT_SIZE: usize = 10_240;
+
+// Collects measurements, prints them as a table, and reports every one over
+// its threshold at once rather than stopping at the first
+#[derive(Default)]
+pub struct Report {
+    over: Vec<String>,
+    heaviest: Option<(String, u64)>,
+}
+
+impl Report {
+    pub fn units(&mut self, name: &str, units: u64) {
+        let share = units * 100 / DEFAULT_INSTRUCTION_UNITS;
+        println!("{name:<36} {units:>8} CU  {share:>3}% of the default limit");
+        if units > UNIT_THRESHOLD {
+            self.over.push(format!("{name}: {units} CU, threshold {UNIT_THRESHOLD}"));
+        }
+        if self.heaviest.as_ref().map_or(true, |(_, heaviest)| units > *heaviest) {
+            self.heaviest = Some((name.to_string(), units));
+        }
+    }
+
+    pub fn size(&mut self, name: &str, bytes: usize) {
+        println!("{name:<36} {bytes:>8} bytes");
+        if bytes > MAX_COMPONENT_SIZE {
+            self.over.push(format!("{name}: {bytes} bytes, limit {MAX_COMPONENT_SIZE}"));
+        }
+    }
+
+    pub fn assert_within_thresholds(&self) {
+        if let Some((name, units)) = &self.heaviest {
+            let margin = UNIT_THRESHOLD as i64 - *units as i64;
+            println!("heaviest: {name}, {units} CU, {margin} CU under the threshold");
+        }
+        assert!(self.over.is_empty(), "over threshold:\n{}", self.over.join("\n"));
+    }
+}
@@ -0,0 +1,274 @@
+use game::Game;
+use grid::Zone;
+use players::Player;
+use serde_json::json;
+use solana_program_test::BanksClientError;
+use solana_sdk::signature::Keypair;
+use unit::Unit;
+use zone_index::ZoneIndex;
+
+use crate::{ComponentRef, Harness};
+
+pub const IMMUNE: u8 = 0;
+pub const PATHOGEN: u8 = 1;
+
+// A match between two fresh keypairs, player 1 immune and player 2 pathogen.
+// Indices are into `signers` and `players`, and into `zones`, whose first two
+// entries are the players' starting zones. Every action returns the compute
+// units its transaction consumed.
+pub struct Match {
+    pub harness: Harness,
+    pub signers: [Keypair; 2],
+    pub game: ComponentRef,
+    pub zone_index: ComponentRef, // On the game's entity
+    pub players: [ComponentRef; 2],
+    pub zones: Vec<ComponentRef>,
+}
+
+impl Match {
+    // Creates the game, player and starting zone components, without joining
+    pub async fn new() -> Self {
+        let mut harness = Harness::start().await;
+        let game = harness.spawn_component(game::ID, None).await.unwrap();
+        let zone_index = harness.initialize_component(game.entity, zone_index::ID).await.unwrap();
+        let game_id = game.pda();
+        let mut players = Vec::new();
+        let mut zones = Vec::new();
+        for zone_id in 0..2 {
+            players.push(harness.spawn_component(players::ID, None).await.unwrap());
+            let seed = Zone::entity_seed(&game_id, zone_id);
+            zones.push(harness.spawn_component(grid::ID, Some(seed.to_vec())).await.unwrap());
+        }
+
+        Self {
+            harness,
+            signers: [Keypair::new(), Keypair::new()],
+            game,
+            zone_index,
+            players: players.try_into().unwrap(),
+            zones,
+        }
+    }
+
+    // Both players joined, so the match is running
+    pub async fn start() -> Self {
+        let mut game_match = Self::new().await;
+        game_match.join(0).await.unwrap();
+        game_match.join(1).await.unwrap();
+        game_match
+    }
+
+    pub async fn join(&mut self, player: usize) -> Result<u64, BanksClientError> {
+        let components = [self.game, self.players[player], self.zones[player], self.zone_index];
+        let faction = [IMMUNE, PATHOGEN][player];
+        self.harness
+            .apply(&self.signers[player], join_game::ID, &components, json!({ "faction": faction }))
+            .await
+    }
+
+    pub async fn game(&mut self) -> Game {
+        self.harness.component(self.game).await.unwrap()
+    }
+
+    pub async fn zone(&mut self, zone: usize) -> Zone {
+        self.harness.component(self.zones[zone]).await.unwrap()
+    }
+
+    pub async fn zone_index(&mut self) -> ZoneIndex {
+        self.harness.component(self.zone_index).await.unwrap()
+    }
+
+    // Unlocks every unit type for `player` and adds 1000 of each resource to
+    // their reserves, so a benchmark can field any unit. No sequence of
+    // actions unlocks unit types yet.
+    pub async fn supply(&mut self, player: usize) {
+        let mut state: Player = self.harness.component(self.players[player]).await.unwrap();
+        for unit_type in 0..state.unlocked_units.len() {
+            state.unlock_unit(unit_type);
+        }
+        state.add_resources(1_000, 1_000, 1_000, 1_000);
+        self.harness.set_component(self.players[player], &state).await.unwrap();
+    }
+
+    // Moves a zone on the map, where no sequence of actions would put it.
+    // Links are left as they are.
+    pub async fn place_zone(&mut self, zone: usize, x: u8, y: u8) {
+        let mut state = self.zone(zone).await;
+        state.x = x;
+        state.y = y;
+        self.harness.set_component(self.zones[zone], &state).await.unwrap();
+
+        let mut zone_index = self.zone_index().await;
+        if let Some(entry) = zone_index.zones.iter_mut().find(|entry| entry.zone_id == state.zone_id) {
+            (entry.x, entry.y) = (x, y);
+        }
+        self.harness.set_component(self.zone_index, &zone_index).await.unwrap();
+    }
+
+    // Creates a zone on (x, y) next to `source`. Returns its index and the
+    // units the creation consumed.
+    pub async fn create_zone(
+        &mut self,
+        player: usize,
+        source: usize,
+        zone_type: u8,
+        x: u8,
+        y: u8,
+    ) -> Result<(usize, u64), BanksClientError> {
+        let game = self.game().await;
+        let seed = Zone::entity_seed(&game.game_id, game.next_zone_id);
+        let component = self.harness.spawn_component(grid::ID, Some(seed.to_vec())).await.unwrap();
+        self.zones.push(component);
+        let target = self.zones.len() - 1;
+        let args = json!({ "new_zone_type": zone_type, "x": x, "y": y });
+        let units = self.expand_with(player, source, target, "CreateNewZone", args).await?;
+        Ok((target, units))
+    }
+
+    // Links two zones on neighbouring tiles, which anyone may do
+    pub async fn connect_zones(&mut self, zone: usize, neighbour: usize) -> Result<u64, BanksClientError> {
+        let components = [self.zones[zone], self.zones[neighbour], self.zone_index];
+        self.harness.apply(&self.signers[0], connect_zones::ID, &components, json!({})).await
+    }
+
+    // A fresh Unit component, at the entity a spawn of the next unit expects
+    pub async fn next_unit(&mut self) -> ComponentRef {
+        let game = self.game().await;
+        let seed = Unit::entity_seed(&game.game_id, game.next_unit_id);
+        self.harness.spawn_component(unit::ID, Some(seed.to_vec())).await.unwrap()
+    }
+
+    // A fresh Unit component that no unit will ever be bound to
+    pub async fn unused_unit(&mut self) -> ComponentRef {
+        self.harness.spawn_component(unit::ID, None).await.unwrap()
+    }
+
+    // Runs a play action for `player` in `zone`
+    pub async fn play(
+        &mut self,
+        player: usize,
+        zone: usize,
+        units: [ComponentRef; 2],
+        args: serde_json::Value,
+    ) -> Result<u64, BanksClientError> {
+        let components = [self.game, self.players[player], self.zones[zone], units[0], units[1]];
+        self.harness.apply(&self.signers[player], play::ID, &components, args).await
+    }
+
+    // Returns the new unit's component and the units the spawn consumed
+    pub async fn spawn(&mut self, player: usize, zone: usize, unit_type: u8, x: u8, y: u8) -> (ComponentRef, u64) {
+        let unit = self.next_unit().await;
+        let target = self.unused_unit().await;
+        let args = json!({ "action": "SpawnUnit", "x": x, "y": y, "unit_type": unit_type, "ability_index": 0 });
+        let units = self.play(player, zone, [unit, target], args).await.unwrap();
+        (unit, units)
+    }
+
+    pub async fn end_turn(&mut self, player: usize) -> u64 {
+        let units = [self.unused_unit().await, self.unused_unit().await];
+        let args = json!({ "action": "EndTurn", "x": 0, "y": 0, "unit_type": 0, "ability_index": 0 });
+        self.play(player, player, units, args).await.unwrap()
+    }
+
+    pub async fn expand(
+        &mut self,
+        player: usize,
+        source: usize,
+        target: usize,
+        expansion_type: &str,
+    ) -> Result<u64, BanksClientError> {
+        let args = json!({ "new_zone_type": 0, "x": 0, "y": 0 });
+        self.expand_with(player, source, target, expansion_type, args).await
+    }
+
+    // `args` holds the CreateNewZone arguments
+    async fn expand_with(
+        &mut self,
+        player: usize,
+        source: usize,
+        target: usize,
+        expansion_type: &str,
+        mut args: serde_json::Value,
+    ) -> Result<u64, BanksClientError> {
+        let components = [self.game, self.players[player], self.zones[source], self.zones[target], self.zone_index];
+        args["expansion_type"] = json!(expansion_type);
+        self.harness.apply(&self.signers[player], expand_zone::ID, &components, args).await
+    }
+
+    // The opponent of `player` stands in as the defender
+    pub async fn conquer(&mut self, player: usize, source: usize, target: usize) -> Result<u64, BanksClientError> {
+        let components = [
+            self.game,
+            self.players[player],
+            self.zones[source],
+            self.zones[target],
+            self.players[1 - player],
+        ];
+        self.harness.apply(&self.signers[player], conquer_zone::ID, &components, json!({})).await
+    }
+
+    // Moves [energy, antibodies, stem_cells, nutrients] out of or into the
+    // pools of `zone`; `transfer` names a play::Transfer
+    pub async fn transfer(
+        &mut self,
+        player: usize,
+        zone: usize,
+        other_zone: usize,
+        transfer: &str,
+        [energy, antibodies, stem_cells, nutrients]: [u32; 4],
+    ) -> Result<u64, BanksClientError> {
+        let components = [self.game, self.players[player], self.zones[zone], self.zones[other_zone]];
+        let args = json!({
+            "transfer": transfer,
+            "energy": energy,
+            "antibodies": antibodies,
+            "stem_cells": stem_cells,
+            "nutrients": nutrients,
+        });
+        self.harness.apply(&self.signers[player], transfer_resources::ID, &components, args).await
+    }
+
+    // `direction` is the side of `source` that `destination` lies on
+    pub async fn move_across_zones(
+        &mut self,
+        player: usize,
+        source: usize,
+        destination: usize,
+        unit: ComponentRef,
+        direction: &str,
+    ) -> Result<u64, BanksClientError> {
+        self.cross(player, source, destination, unit, direction, false).await
+    }
+
+    // Crosses with the unit's Infiltration ability instead of its move
+    pub async fn infiltrate(
+        &mut self,
+        player: usize,
+        source: usize,
+        destination: usize,
+        unit: ComponentRef,
+        direction: &str,
+    ) -> Result<u64, BanksClientError> {
+        self.cross(player, source, destination, unit, direction, true).await
+    }
+
+    async fn cross(
+        &mut self,
+        player: usize,
+        source: usize,
+        destination: usize,
+        unit: ComponentRef,
+        direction: &str,
+        infiltrate: bool,
+    ) -> Result<u64, BanksClientError> {
+        let components = [
+            self.game,
+            self.players[player],
+            self.zones[source],
+            self.zones[destination],
+            unit,
+        ];
+        let args = json!({ "direction": direction, "infiltrate": infiltrate });
+        self.harness.apply(&self.signers[player], move_across_zones::ID, &components, args).await
+    }
+}
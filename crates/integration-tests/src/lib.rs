@@ -0,0 +1,298 @@
+// Loads the SBF builds of the components and systems, plus the world program
+// from tests/fixtures, into a solana-program-test bank, and drives them through
+// the world program the same way the client does.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    bpf_loader,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_program,
+    sysvar,
+    transaction::Transaction,
+};
+use std::path::PathBuf;
+
+pub mod budget;
+pub mod game_match;
+
+pub use game_match::Match;
+
+// Enough for every system, so a test fails on logic rather than on budget
+pub const COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+// Library name and program id of every program a match needs
+pub fn programs() -> [(&'static str, Pubkey); 12] {
+    [
+        ("game", game::ID),
+        ("grid", grid::ID),
+        ("players", players::ID),
+        ("unit", unit::ID),
+        ("zone_index", zone_index::ID),
+        ("join_game", join_game::ID),
+        ("play", play::ID),
+        ("expand_zone", expand_zone::ID),
+        ("move_across_zones", move_across_zones::ID),
+        ("connect_zones", connect_zones::ID),
+        ("conquer_zone", conquer_zone::ID),
+        ("transfer_resources", transfer_resources::ID),
+    ]
+}
+
+pub struct Harness {
+    pub context: ProgramTestContext,
+    pub world: Pubkey,
+}
+
+// A component account, identified by the entity it belongs to and its program
+#[derive(Clone, Copy)]
+pub struct ComponentRef {
+    pub entity: Pubkey,
+    pub program_id: Pubkey,
+}
+
+impl ComponentRef {
+    pub fn pda(&self) -> Pubkey {
+        // Our components use an empty component id as their seed
+        Pubkey::find_program_address(&[b"", self.entity.as_ref()], &self.program_id).0
+    }
+}
+
+impl Harness {
+    // Starts a bank with every program loaded and a fresh world
+    pub async fn start() -> Self {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let mut program_test = ProgramTest::default();
+
+        add_program(&mut program_test, world::ID, root.join("tests/fixtures/world.so"));
+        for (name, program_id) in programs() {
+            add_program(&mut program_test, program_id, root.join("target/deploy").join(format!("{name}.so")));
+        }
+
+        let context = program_test.start_with_context().await;
+        let mut harness = Self { context, world: Pubkey::default() };
+        harness.world = harness.initialize_world().await.expect("world initialization failed");
+        harness
+    }
+
+    async fn initialize_world(&mut self) -> Result<Pubkey, BanksClientError> {
+        let payer = self.context.payer.pubkey();
+        let registry = Pubkey::find_program_address(&[b"registry"], &world::ID).0;
+        let world = Pubkey::find_program_address(&[b"world", &0u64.to_be_bytes()], &world::ID).0;
+
+        let initialize_registry = Instruction {
+            program_id: world::ID,
+            accounts: world::accounts::InitializeRegistry {
+                registry,
+                payer,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: world::instruction::InitializeRegistry {}.data(),
+        };
+        let initialize_world = Instruction {
+            program_id: world::ID,
+            accounts: world::accounts::InitializeNewWorld {
+                payer,
+                world,
+                registry,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: world::instruction::InitializeNewWorld {}.data(),
+        };
+
+        self.send(&[initialize_registry, initialize_world], &[]).await?;
+        Ok(world)
+    }
+
+    // Adds an entity, at a PDA derived from `extra_seed` when one is given
+    pub async fn add_entity(&mut self, extra_seed: Option<Vec<u8>>) -> Result<Pubkey, BanksClientError> {
+        let world_state: world::World = self.account(self.world).await?;
+        let counter = match extra_seed {
+            Some(_) => [0; 8],
+            None => world_state.entities.to_be_bytes(),
+        };
+        let entity = Pubkey::find_program_address(
+            &[b"entity", &world_state.id.to_be_bytes(), &counter, extra_seed.as_deref().unwrap_or_default()],
+            &world::ID,
+        )
+        .0;
+
+        let instruction = Instruction {
+            program_id: world::ID,
+            accounts: world::accounts::AddEntity {
+                payer: self.context.payer.pubkey(),
+                entity,
+                world: self.world,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: world::instruction::AddEntity { extra_seed }.data(),
+        };
+        self.send(&[instruction], &[]).await?;
+        Ok(entity)
+    }
+
+    // Initializes a component with its default value; anyone may update it
+    // through the world program
+    pub async fn initialize_component(&mut self, entity: Pubkey, program_id: Pubkey) -> Result<ComponentRef, BanksClientError> {
+        let component = ComponentRef { entity, program_id };
+        let instruction = Instruction {
+            program_id: world::ID,
+            accounts: world::accounts::InitializeComponent {
+                payer: self.context.payer.pubkey(),
+                data: component.pda(),
+                entity,
+                component_program: program_id,
+                authority: world::ID,
+                instruction_sysvar_account: sysvar::instructions::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: world::instruction::InitializeComponent {}.data(),
+        };
+        self.send(&[instruction], &[]).await?;
+        Ok(component)
+    }
+
+    // Adds an entity holding a single freshly initialized component
+    pub async fn spawn_component(&mut self, program_id: Pubkey, extra_seed: Option<Vec<u8>>) -> Result<ComponentRef, BanksClientError> {
+        let entity = self.add_entity(extra_seed).await?;
+        self.initialize_component(entity, program_id).await
+    }
+
+    pub fn apply_instruction(&self, authority: &Pubkey, system: Pubkey, components: &[ComponentRef], args: &serde_json::Value) -> Instruction {
+        let args = args.to_string().into_bytes();
+
+        // apply takes its accounts in a different order from apply2..apply5
+        let (mut accounts, data) = match components {
+            [component] => (
+                vec![
+                    AccountMeta::new_readonly(component.program_id, false),
+                    AccountMeta::new_readonly(system, false),
+                    AccountMeta::new(component.pda(), false),
+                ],
+                world::instruction::Apply { args }.data(),
+            ),
+            _ => {
+                let mut accounts = vec![AccountMeta::new_readonly(system, false)];
+                for component in components {
+                    accounts.push(AccountMeta::new_readonly(component.program_id, false));
+                    accounts.push(AccountMeta::new(component.pda(), false));
+                }
+                let data = match components.len() {
+                    2 => world::instruction::Apply2 { args }.data(),
+                    3 => world::instruction::Apply3 { args }.data(),
+                    4 => world::instruction::Apply4 { args }.data(),
+                    5 => world::instruction::Apply5 { args }.data(),
+                    count => panic!("the world program takes 1 to 5 components, not {count}"),
+                };
+                (accounts, data)
+            }
+        };
+        accounts.extend([
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            AccountMeta::new_readonly(self.world, false),
+        ]);
+
+        Instruction { program_id: world::ID, accounts, data }
+    }
+
+    // Runs a system over the components, signed by `authority`, and returns
+    // the compute units the transaction consumed
+    pub async fn apply(
+        &mut self,
+        authority: &Keypair,
+        system: Pubkey,
+        components: &[ComponentRef],
+        args: serde_json::Value,
+    ) -> Result<u64, BanksClientError> {
+        let instruction = self.apply_instruction(&authority.pubkey(), system, components, &args);
+        self.send(&[instruction], &[authority]).await
+    }
+
+    pub async fn component<T: AccountDeserialize>(&mut self, component: ComponentRef) -> Result<T, BanksClientError> {
+        self.account(component.pda()).await
+    }
+
+    // Overwrites a component in place, for setups no sequence of actions can
+    // reach yet
+    pub async fn set_component<T: AccountSerialize>(&mut self, component: ComponentRef, value: &T) -> Result<(), BanksClientError> {
+        let address = component.pda();
+        let mut account = self
+            .context
+            .banks_client
+            .get_account(address)
+            .await?
+            .unwrap_or_else(|| panic!("account {address} does not exist"));
+
+        let mut data = Vec::with_capacity(account.data.len());
+        value
+            .try_serialize(&mut data)
+            .map_err(|_| BanksClientError::ClientError("component does not serialize"))?;
+        data.resize(account.data.len(), 0);
+        account.data = data;
+
+        self.context.set_account(&address, &account.into());
+        Ok(())
+    }
+
+    pub async fn account<T: AccountDeserialize>(&mut self, address: Pubkey) -> Result<T, BanksClientError> {
+        let account = self
+            .context
+            .banks_client
+            .get_account(address)
+            .await?
+            .unwrap_or_else(|| panic!("account {address} does not exist"));
+        T::try_deserialize(&mut account.data.as_slice())
+            .map_err(|_| BanksClientError::ClientError("account data does not match the requested type"))
+    }
+
+    pub fn transaction(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Transaction {
+        let mut all_instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT)];
+        all_instructions.extend_from_slice(instructions);
+
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend(signers.iter().filter(|signer| signer.pubkey() != self.context.payer.pubkey()));
+
+        Transaction::new_signed_with_payer(
+            &all_instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            self.context.last_blockhash,
+        )
+    }
+
+    // Returns the compute units consumed by the whole transaction
+    pub async fn send(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<u64, BanksClientError> {
+        // A fresh blockhash keeps repeated identical actions, like two
+        // EndTurns, from being dropped as duplicates
+        self.context.last_blockhash = self.context.get_new_latest_blockhash().await?;
+        let transaction = self.transaction(instructions, signers);
+        let outcome = self.context.banks_client.process_transaction_with_metadata(transaction).await?;
+        outcome.result.map_err(BanksClientError::TransactionError)?;
+        Ok(outcome.metadata.map_or(0, |metadata| metadata.compute_units_consumed))
+    }
+}
+
+fn add_program(program_test: &mut ProgramTest, program_id: Pubkey, path: PathBuf) {
+    let data = std::fs::read(&path)
+        .unwrap_or_else(|error| panic!("cannot read {}: {error}; run `anchor build` first", path.display()));
+    program_test.add_account(
+        program_id,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()).max(1),
+            data,
+            owner: bpf_loader::ID,
+            executable: true,
+            rent_epoch: 0,
+        },
+    );
+}
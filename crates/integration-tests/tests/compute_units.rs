@@ -0,0 +1,220 @@
+// Measures the compute units of every play action, expansion type and ability,
+// and the size of every component account. Run with `-- --nocapture` for the table.
+
+use anchor_lang::Space;
+use grid::{CellContent, Zone};
+use integration_tests::budget::Report;
+use integration_tests::{ComponentRef, Match};
+use serde_json::json;
+
+const T_CELL: u8 = 0;
+const B_CELL: u8 = 1;
+const MACROPHAGE: u8 = 2;
+const DENDRITIC_CELL: u8 = 4;
+const NATURAL_KILLER_CELL: u8 = 5;
+const VIRUS: u8 = 6;
+const BACTERIA: u8 = 7;
+const FUNGUS: u8 = 8;
+const CANCER_CELL: u8 = 10;
+
+// One match that passes through every action: the starting zones are moved
+// next to each other, so conquest, and combat after it, can happen
+#[tokio::test]
+async fn systems_stay_within_their_compute_budget() {
+    let mut report = Report::default();
+    let mut game_match = Match::new().await;
+
+    report.units("join_game", game_match.join(0).await.unwrap());
+    game_match.join(1).await.unwrap();
+    game_match.place_zone(1, 1, 0).await;
+    report.units("connect_zones", game_match.connect_zones(0, 1).await.unwrap());
+
+    // Turn 1, immune player
+    let (created, units) = game_match.create_zone(0, 0, 0, 0, 1).await.unwrap();
+    report.units("expand_zone CreateNewZone", units);
+    let units = game_match.expand(0, 0, 1, "ImmuneResponse").await.unwrap();
+    report.units("expand_zone ImmuneResponse", units);
+    let (t_cell, units) = game_match.spawn(0, 0, T_CELL, 4, 4).await;
+    report.units("play SpawnUnit", units);
+    let (traveller, _) = game_match.spawn(0, 0, T_CELL, 15, 2).await;
+    let zone = game_match.zone(created).await;
+    let (node_x, node_y) = zone
+        .occupied_cells()
+        .find_map(|(x, y, content)| match content {
+            CellContent::Resource { .. } if zone.is_empty_cell(x - 1, y) => Some((x, y)),
+            _ => None,
+        })
+        .unwrap();
+    let (harvester, _) = game_match.spawn(0, created, T_CELL, node_x - 1, node_y).await;
+    report.units("play EndTurn", game_match.end_turn(0).await);
+
+    // Turn 2, pathogen player
+    let units = game_match.expand(1, 1, 0, "InfectionSpread").await.unwrap();
+    report.units("expand_zone InfectionSpread", units);
+    game_match.spawn(1, 1, VIRUS, 5, 5).await;
+    let (virus, _) = game_match.spawn(1, 1, VIRUS, 5, 7).await;
+    game_match.end_turn(1).await;
+
+    // Turn 3: the conquest removes the virus at (5, 5) and keeps the one at (5, 7)
+    let units = game_match.conquer(0, 0, 1).await.unwrap();
+    report.units("conquer_zone", units);
+    let (attacker, _) = game_match.spawn(0, 1, T_CELL, 5, 6).await;
+    let target = game_match.unused_unit().await;
+    let args = json!({ "action": "MoveUnit", "x": 4, "y": 5, "unit_type": 0, "ability_index": 0 });
+    let units = game_match.play(0, 0, [t_cell, target], args).await.unwrap();
+    report.units("play MoveUnit", units);
+    let units = game_match.move_across_zones(0, 0, 1, traveller, "East").await.unwrap();
+    report.units("move_across_zones", units);
+    let target = game_match.unused_unit().await;
+    let args = json!({ "action": "Harvest", "x": node_x, "y": node_y, "unit_type": 0, "ability_index": 0 });
+    let units = game_match.play(0, created, [harvester, target], args).await.unwrap();
+    report.units("play Harvest", units);
+    let units = game_match.transfer(0, 0, created, "ToZone", [10, 5, 1, 5]).await.unwrap();
+    report.units("transfer_resources", units);
+    game_match.end_turn(0).await;
+
+    // Turn 4: Replication binds a whole new unit, the heaviest ability
+    let child = game_match.next_unit().await;
+    let args = json!({ "action": "UseSpecialAbility", "x": 0, "y": 0, "unit_type": 0, "ability_index": 0 });
+    let units = game_match.play(1, 1, [virus, child], args).await.unwrap();
+    report.units("play UseSpecialAbility Replication", units);
+    game_match.end_turn(1).await;
+
+    // Turn 5
+    let args = json!({ "action": "AttackPosition", "x": 5, "y": 7, "unit_type": 0, "ability_index": 0 });
+    let units = game_match.play(0, 1, [attacker, virus], args).await.unwrap();
+    report.units("play AttackPosition", units);
+
+    report.assert_within_thresholds();
+}
+
+// Every ability some unit type starts with, each used by a unit spawned for
+// it. Mutation is left out: no unit type starts with it, so play never runs it.
+#[tokio::test]
+async fn abilities_stay_within_their_compute_budget() {
+    let mut report = Report::default();
+    let mut game_match = Match::start().await;
+    game_match.place_zone(1, 1, 0).await;
+    game_match.connect_zones(0, 1).await.unwrap();
+    game_match.supply(0).await;
+    game_match.supply(1).await;
+
+    // Rows where a unit can cross the border between the two zones
+    let (west, east) = (game_match.zone(0).await, game_match.zone(1).await);
+    let rows: Vec<u8> = (0..16).filter(|&y| west.is_empty_cell(15, y) && east.is_empty_cell(0, y)).collect();
+
+    // Turn 1, immune player
+    for (unit_type, ability_index, name) in [
+        (B_CELL, 0, "AntibodyProduction"),
+        (MACROPHAGE, 0, "Phagocytosis"),
+        (T_CELL, 1, "MemoryResponse"),
+        (NATURAL_KILLER_CELL, 1, "ZoneHealing"),
+    ] {
+        let (x, y) = empty_cell(&mut game_match, 0, |_, x, _| x < 15).await;
+        let (unit, _) = game_match.spawn(0, 0, unit_type, x, y).await;
+        let units = use_ability(&mut game_match, 0, 0, unit, ability_index, (0, 0)).await;
+        report.units(&format!("play UseSpecialAbility {name}"), units);
+    }
+    let (scout, _) = game_match.spawn(0, 0, DENDRITIC_CELL, 15, rows[0]).await;
+    let units = game_match.infiltrate(0, 0, 1, scout, "East").await.unwrap();
+    report.units("move_across_zones Infiltration", units);
+    game_match.end_turn(0).await;
+
+    // Turn 2, pathogen player: ToxinRelease hits the scout at (0, rows[0])
+    let scout_row = rows[0];
+    let (x, y) = empty_cell(&mut game_match, 1, |_, x, y| x == 1 && y.abs_diff(scout_row) <= 1).await;
+    let (bacteria, _) = game_match.spawn(1, 1, BACTERIA, x, y).await;
+    let units = use_ability(&mut game_match, 1, 1, bacteria, 1, (0, 0)).await;
+    report.units("play UseSpecialAbility ToxinRelease", units);
+
+    let (x, y) = empty_cell(&mut game_match, 1, |zone, x, y| x > 1 && y > 0 && zone.is_empty_cell(x, y - 1)).await;
+    let (virus, _) = game_match.spawn(1, 1, VIRUS, x, y).await;
+    let child = game_match.next_unit().await;
+    let args = json!({ "action": "UseSpecialAbility", "x": 0, "y": 0, "unit_type": 0, "ability_index": 0 });
+    let units = game_match.play(1, 1, [virus, child], args).await.unwrap();
+    report.units("play UseSpecialAbility Replication", units);
+    let units = use_ability(&mut game_match, 1, 1, virus, 1, (0, 0)).await;
+    report.units("play UseSpecialAbility ImmuneEvasion", units);
+
+    let (x, y) = empty_cell(&mut game_match, 1, |_, x, _| x > 1).await;
+    let (cancer_cell, _) = game_match.spawn(1, 1, CANCER_CELL, x, y).await;
+    let destination = empty_cell(&mut game_match, 1, |_, x, _| x > 1).await;
+    let units = use_ability(&mut game_match, 1, 1, cancer_cell, 1, destination).await;
+    report.units("play UseSpecialAbility Metastasis", units);
+
+    // The fungus drains the immune player's zone from inside it
+    let (fungus, _) = game_match.spawn(1, 1, FUNGUS, 0, rows[1]).await;
+    game_match.move_across_zones(1, 1, 0, fungus, "West").await.unwrap();
+    let units = use_ability(&mut game_match, 1, 0, fungus, 1, (0, 0)).await;
+    report.units("play UseSpecialAbility ResourceDrain", units);
+    game_match.end_turn(1).await;
+
+    // Turn 3: CytokineRelease hits the bacteria next to the scout
+    let units = use_ability(&mut game_match, 0, 1, scout, 0, (0, 0)).await;
+    report.units("play UseSpecialAbility CytokineRelease", units);
+
+    report.assert_within_thresholds();
+}
+
+// Uses the ability in slot `ability_index` of `unit`, with (x, y) as its target
+// cell, and returns the units it consumed
+async fn use_ability(
+    game_match: &mut Match,
+    player: usize,
+    zone: usize,
+    unit: ComponentRef,
+    ability_index: u8,
+    (x, y): (u8, u8),
+) -> u64 {
+    let target = game_match.unused_unit().await;
+    let args = json!({ "action": "UseSpecialAbility", "x": x, "y": y, "unit_type": 0, "ability_index": ability_index });
+    game_match.play(player, zone, [unit, target], args).await.unwrap()
+}
+
+// First empty cell (x, y) of `zone` that `wanted` accepts
+async fn empty_cell(game_match: &mut Match, zone: usize, wanted: impl Fn(&Zone, u8, u8) -> bool) -> (u8, u8) {
+    let state = game_match.zone(zone).await;
+    (0..16)
+        .flat_map(|y| (0..16).map(move |x| (x, y)))
+        .find(|&(x, y)| state.is_empty_cell(x, y) && wanted(&state, x, y))
+        .unwrap_or_else(|| panic!("zone {zone} has no cell for the benchmark"))
+}
+
+#[tokio::test]
+async fn components_match_their_declared_size() {
+    let mut game_match = Match::start().await;
+    let components = [
+        ("game", game_match.game),
+        ("players", game_match.players[0]),
+        ("grid", game_match.zones[0]),
+        ("unit", game_match.unused_unit().await),
+        ("zone_index", game_match.zone_index),
+    ];
+
+    for (name, component) in components {
+        let account = game_match.harness.context.banks_client.get_account(component.pda()).await.unwrap().unwrap();
+        assert_eq!(account.data.len(), declared_size(name), "size of the {name} account");
+    }
+}
+
+#[test]
+fn component_accounts_fit_a_cpi_allocation() {
+    let mut report = Report::default();
+    for name in ["game", "players", "grid", "unit", "zone_index"] {
+        report.size(name, declared_size(name));
+    }
+    report.size("grid, legacy layout", grid::legacy::LEGACY_ZONE_SIZE);
+    report.assert_within_thresholds();
+}
+
+// Discriminator plus the space each component declares
+fn declared_size(name: &str) -> usize {
+    8 + match name {
+        "game" => game::Game::INIT_SPACE,
+        "players" => players::Player::INIT_SPACE,
+        "grid" => grid::Zone::INIT_SPACE,
+        "unit" => unit::Unit::INIT_SPACE,
+        "zone_index" => zone_index::ZoneIndex::INIT_SPACE,
+        _ => unreachable!("unknown component {name}"),
+    }
+}
@@ -0,0 +1,139 @@
+use game::{GameState, GameWinner};
+use grid::CellContent;
+use integration_tests::Match;
+use players::Player;
+use serde_json::json;
+use solana_sdk::signature::Signer;
+use unit::Unit;
+
+const T_CELL: u8 = 0;
+const VIRUS: u8 = 6;
+
+#[tokio::test]
+async fn joining_starts_the_match() {
+    let mut game_match = Match::start().await;
+    let game = game_match.game().await;
+
+    assert!(matches!(game.game_state, GameState::Active));
+    assert_eq!(game.player1, game_match.signers[0].pubkey());
+    assert_eq!(game.player2, game_match.signers[1].pubkey());
+    for index in 0..2 {
+        let player: Player = game_match.harness.component(game_match.players[index]).await.unwrap();
+        let zone = game_match.zone(index).await;
+        assert_eq!(player.controlled_zones, 1);
+        assert_eq!(zone.owner, game_match.signers[index].pubkey());
+    }
+}
+
+#[tokio::test]
+async fn spawned_units_move_on_their_owners_next_turn() {
+    let mut game_match = Match::start().await;
+    let (t_cell, _) = game_match.spawn(0, 0, T_CELL, 4, 4).await;
+
+    let unit: Unit = game_match.harness.component(t_cell).await.unwrap();
+    assert_eq!(unit.owner, game_match.signers[0].pubkey());
+    assert!(matches!(game_match.zone(0).await.cell(4, 4), Some(CellContent::ImmuneCell { .. })));
+
+    game_match.end_turn(0).await;
+    game_match.end_turn(1).await;
+
+    let target = game_match.unused_unit().await;
+    let args = json!({ "action": "MoveUnit", "x": 4, "y": 5, "unit_type": 0, "ability_index": 0 });
+    game_match.play(0, 0, [t_cell, target], args).await.unwrap();
+
+    let unit: Unit = game_match.harness.component(t_cell).await.unwrap();
+    assert_eq!((unit.x, unit.y), (4, 5));
+    let zone = game_match.zone(0).await;
+    assert!(zone.is_empty_cell(4, 4));
+    assert!(matches!(zone.cell(4, 5), Some(CellContent::ImmuneCell { .. })));
+}
+
+#[tokio::test]
+async fn players_only_act_on_their_own_turn() {
+    let mut game_match = Match::start().await;
+    let units = [game_match.next_unit().await, game_match.unused_unit().await];
+    let args = json!({ "action": "SpawnUnit", "x": 4, "y": 4, "unit_type": VIRUS, "ability_index": 0 });
+
+    assert!(game_match.play(1, 1, units, args).await.is_err());
+    assert_eq!(game_match.game().await.next_unit_id, 1);
+}
+
+#[tokio::test]
+async fn players_create_new_zones() {
+    let mut game_match = Match::start().await;
+
+    let (new_zone, _) = game_match.create_zone(0, 0, 0, 0, 1).await.unwrap();
+
+    let zone = game_match.zone(new_zone).await;
+    let player: Player = game_match.harness.component(game_match.players[0]).await.unwrap();
+    assert_eq!(zone.owner, game_match.signers[0].pubkey());
+    assert_eq!((zone.x, zone.y), (0, 1));
+    assert_eq!(zone.connected_zones, [Some(0), None, None, None]);
+    assert_eq!(game_match.zone(0).await.connected_zones, [None, None, Some(zone.zone_id), None]);
+    assert_eq!(player.controlled_zones, 2);
+    assert_eq!(game_match.zone_index().await.zone_at(0, 1), Some(zone.zone_id));
+
+    // Anyone can regenerate the interior from the game's seed
+    let mut expected = zone.clone();
+    expected.generate_interior(game_match.game().await.seed).unwrap();
+    assert!(zone.cells == expected.cells && zone.occupants == expected.occupants);
+
+    // The tile is taken now
+    assert!(game_match.create_zone(0, 0, 0, 0, 1).await.is_err());
+}
+
+#[tokio::test]
+async fn units_harvest_resource_nodes() {
+    let mut game_match = Match::start().await;
+    let (zone, _) = game_match.create_zone(0, 0, 0, 0, 1).await.unwrap();
+
+    // A resource node with an empty cell to its west
+    let state = game_match.zone(zone).await;
+    let (x, y, amount) = state
+        .occupied_cells()
+        .find_map(|(x, y, content)| match content {
+            CellContent::Resource { amount, .. } if state.is_empty_cell(x - 1, y) => Some((x, y, amount)),
+            _ => None,
+        })
+        .unwrap();
+    let (t_cell, _) = game_match.spawn(0, zone, T_CELL, x - 1, y).await;
+    game_match.end_turn(0).await;
+    game_match.end_turn(1).await;
+
+    let target = game_match.unused_unit().await;
+    let args = json!({ "action": "Harvest", "x": x, "y": y, "unit_type": 0, "ability_index": 0 });
+    game_match.play(0, zone, [t_cell, target], args).await.unwrap();
+
+    assert!(matches!(
+        game_match.zone(zone).await.cell(x, y),
+        Some(CellContent::Resource { amount: left, .. }) if left == amount - 25
+    ));
+}
+
+#[tokio::test]
+async fn resources_move_between_pools_and_reserves() {
+    let mut game_match = Match::start().await;
+    let (zone, _) = game_match.create_zone(0, 0, 0, 0, 1).await.unwrap();
+    let home_energy = game_match.zone(0).await.energy;
+    let zone_energy = game_match.zone(zone).await.energy;
+
+    game_match.transfer(0, 0, zone, "ToZone", [40, 0, 0, 0]).await.unwrap();
+    game_match.transfer(0, zone, 0, "ToReserves", [10, 0, 0, 0]).await.unwrap();
+
+    assert_eq!(game_match.zone(0).await.energy, home_energy - 40);
+    assert_eq!(game_match.zone(zone).await.energy, zone_energy + 30);
+    assert!(game_match.transfer(0, 0, 1, "ToZone", [1, 0, 0, 0]).await.is_err());
+}
+
+#[tokio::test]
+async fn matches_end_in_a_draw_at_the_turn_limit() {
+    let mut game_match = Match::start().await;
+    let max_turns = game_match.game().await.max_turns;
+
+    for turn in 0..max_turns {
+        game_match.end_turn(turn as usize % 2).await;
+    }
+
+    let game = game_match.game().await;
+    assert!(matches!(game.game_state, GameState::Finished { winner: GameWinner::Draw }));
+}
@@ -0,0 +1,126 @@
+use bolt_lang::*;
+
+use crate::Simulation;
+
+// Actions a bot tries before it gives up and ends its turn
+pub const ATTEMPTS_PER_TURN: usize = 12;
+
+// Hard stop for matches whose max_turns is disabled
+pub const MAX_SIMULATED_TURNS: u32 = 1_000;
+
+// SplitMix64, so a seed replays the same match on every platform
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound.max(1)
+    }
+
+    fn cell(&mut self) -> u8 {
+        self.below(16) as u8
+    }
+}
+
+// Plays a match between two bots that pick random actions, and returns it
+// once the game is over or MAX_SIMULATED_TURNS have passed. Rejected actions
+// are simply skipped, as a failed transaction would be.
+pub fn play_random_match(seed: u64) -> Result<Simulation> {
+    let mut rng = Rng::new(seed);
+    let first = rng.below(2) as u8;
+    let factions = [first, 1 - first];
+    let mut simulation = Simulation::new(factions)?;
+
+    while simulation.game.is_game_active() && simulation.game.turn_number < MAX_SIMULATED_TURNS {
+        for _ in 0..ATTEMPTS_PER_TURN {
+            let _ = random_action(&mut simulation, &mut rng);
+            if !simulation.game.is_game_active() {
+                return Ok(simulation);
+            }
+        }
+
+        let home = home_zone(&simulation);
+        simulation.end_turn(home)?;
+    }
+
+    Ok(simulation)
+}
+
+fn random_action(simulation: &mut Simulation, rng: &mut Rng) -> Result<()> {
+    let player_key = Simulation::player_key(simulation.current_player());
+    let own_units: Vec<u32> = simulation
+        .units
+        .iter()
+        .filter(|unit| unit.owner == player_key && unit.is_active)
+        .map(|unit| unit.unit_id)
+        .collect();
+    let unit_id = own_units
+        .get(rng.below(own_units.len() as u64) as usize)
+        .copied()
+        .unwrap_or_default();
+
+    match rng.below(6) {
+        0 => {
+            let zone = home_zone(simulation);
+            simulation.spawn(zone, rng.below(12) as u8, rng.cell(), rng.cell()).map(|_| ())
+        }
+        1 => {
+            let (x, y) = near(simulation, unit_id, rng);
+            simulation.move_unit(unit_id, x, y)
+        }
+        2 => {
+            let (x, y) = near(simulation, unit_id, rng);
+            simulation.attack(unit_id, x, y)
+        }
+        3 => {
+            let (x, y) = near(simulation, unit_id, rng);
+            simulation.use_ability(unit_id, rng.below(2) as u8, x, y).map(|_| ())
+        }
+        4 => {
+            // Next to a random zone, on a tile that may be taken or off the map
+            let source = rng.below(simulation.zones.len() as u64) as usize;
+            let (x, y) = (simulation.zones[source].x, simulation.zones[source].y);
+            let (x, y) = match rng.below(4) {
+                0 => (x, y.wrapping_sub(1)),
+                1 => (x + 1, y),
+                2 => (x, y + 1),
+                _ => (x.wrapping_sub(1), y),
+            };
+            simulation.create_zone(source, rng.below(5) as u8, x, y).map(|_| ())
+        }
+        _ => {
+            // Conquest of a random zone, which only lands on adjacent enemy zones
+            let source = home_zone(simulation);
+            let target = rng.below(simulation.zones.len() as u64) as usize;
+            simulation.conquer(source, target)
+        }
+    }
+}
+
+// First zone owned by the current player, or the first zone if they own none
+fn home_zone(simulation: &Simulation) -> usize {
+    let player_key = Simulation::player_key(simulation.current_player());
+    simulation
+        .zones
+        .iter()
+        .position(|zone| zone.owner == player_key)
+        .unwrap_or_default()
+}
+
+// A random cell within three cells of the unit
+fn near(simulation: &Simulation, unit_id: u32, rng: &mut Rng) -> (u8, u8) {
+    let (x, y) = simulation.unit(unit_id).map_or((0, 0), |unit| (unit.x, unit.y));
+    let offset = |rng: &mut Rng, coordinate: u8| (coordinate as i64 + rng.below(7) as i64 - 3).clamp(0, 15) as u8;
+    (offset(rng, x), offset(rng, y))
+}
@@ -0,0 +1,375 @@
+// Runs whole Bio Commander matches natively. Every action goes through the
+// same functions the systems call, over in-memory components, so matches can
+// be scripted and replayed in plain `cargo test` without a validator.
+
+use bolt_lang::*;
+use expand_zone::{ExpansionType, NewZone};
+use game::Game;
+use game_control::ControlAction;
+use grid::{CellContent, Zone, GRID_SIZE};
+use play::{ActionType, Command, Direction, Transfer};
+use players::Player;
+use unit::Unit;
+use zone_index::ZoneIndex;
+
+pub mod bot;
+
+// Seconds added to the clock by each action, well inside the turn time limit
+pub const ACTION_SECONDS: i64 = 5;
+
+// Like a transaction, an action that fails leaves the simulation unchanged
+pub struct Simulation {
+    pub game: Game,
+    pub players: [Player; 2], // By player_id - 1
+    pub zones: Vec<Zone>,
+    pub zone_index: ZoneIndex,
+    pub units: Vec<Unit>, // Every unit ever bound, including dead ones
+    pub now: i64,
+}
+
+impl Simulation {
+    // Seats both players with the given faction indices and starts the game
+    pub fn new(factions: [u8; 2]) -> Result<Self> {
+        let mut simulation = Self {
+            game: Game::default(),
+            players: [Player::default(), Player::default()],
+            zones: Vec::new(),
+            zone_index: ZoneIndex::default(),
+            units: Vec::new(),
+            now: 0,
+        };
+        // As the first join would, from a stand-in component address
+        simulation.game.game_id = Pubkey::new_from_array([0xee; 32]);
+
+        for (index, faction) in factions.into_iter().enumerate() {
+            let mut zone = Zone::default();
+            join_game::seat_player(
+                &mut simulation.game,
+                &mut simulation.players[index],
+                &mut zone,
+                &mut simulation.zone_index,
+                Self::player_key(index),
+                faction,
+                simulation.now,
+            )?;
+            simulation.zones.push(zone);
+        }
+
+        Ok(simulation)
+    }
+
+    pub fn player_key(index: usize) -> Pubkey {
+        Pubkey::new_from_array([index as u8 + 1; 32])
+    }
+
+    // Index into `players` of the player whose turn it is
+    pub fn current_player(&self) -> usize {
+        self.game.current_turn.saturating_sub(1) as usize
+    }
+
+    pub fn unit(&self, unit_id: u32) -> Option<&Unit> {
+        self.units.iter().find(|unit| unit.unit_id == unit_id)
+    }
+
+    pub fn zone_index(&self, zone_id: u32) -> Option<usize> {
+        self.zones.iter().position(|zone| zone.zone_id == zone_id)
+    }
+
+    // Adds a freshly initialized zone component, as a client would before
+    // CreateNewZone
+    pub fn add_zone(&mut self) -> usize {
+        self.zones.push(Zone::default());
+        self.zones.len() - 1
+    }
+
+    // Moves a zone to another map tile, for scenarios that need zones where
+    // no sequence of actions would put them. Links are left as they are.
+    pub fn place_zone(&mut self, zone: usize, x: u8, y: u8) {
+        let zone = &mut self.zones[zone];
+        zone.x = x;
+        zone.y = y;
+        if let Some(entry) = self.zone_index.zones.iter_mut().find(|entry| entry.zone_id == zone.zone_id) {
+            (entry.x, entry.y) = (x, y);
+        }
+    }
+
+    pub fn advance_time(&mut self, seconds: i64) {
+        self.now += seconds;
+    }
+
+    pub fn spawn(&mut self, zone: usize, unit_type: u8, x: u8, y: u8) -> Result<u32> {
+        let command = Command { action: ActionType::SpawnUnit, x, y, unit_type, ability_index: 0 };
+        let (unit, _) = self.play(zone, Unit::default(), Unit::default(), command)?;
+        let unit_id = unit.unit_id;
+        self.store_unit(unit);
+        Ok(unit_id)
+    }
+
+    pub fn move_unit(&mut self, unit_id: u32, x: u8, y: u8) -> Result<()> {
+        let command = Command { action: ActionType::MoveUnit, x, y, unit_type: 0, ability_index: 0 };
+        self.act(unit_id, command).map(|_| ())
+    }
+
+    pub fn attack(&mut self, unit_id: u32, x: u8, y: u8) -> Result<()> {
+        let command = Command { action: ActionType::AttackPosition, x, y, unit_type: 0, ability_index: 0 };
+        self.act(unit_id, command).map(|_| ())
+    }
+
+    // Harvests the resource node on (x, y), next to the unit
+    pub fn harvest(&mut self, unit_id: u32, x: u8, y: u8) -> Result<()> {
+        let command = Command { action: ActionType::Harvest, x, y, unit_type: 0, ability_index: 0 };
+        self.act(unit_id, command).map(|_| ())
+    }
+
+    // Returns the id of the child unit when the ability placed one
+    pub fn use_ability(&mut self, unit_id: u32, ability_index: u8, x: u8, y: u8) -> Result<Option<u32>> {
+        let command = Command { action: ActionType::UseSpecialAbility, x, y, unit_type: 0, ability_index };
+        self.act(unit_id, command)
+    }
+
+    // Moves a unit into the zone its own zone is connected to on `direction`
+    pub fn move_across_zones(&mut self, unit_id: u32, direction: Direction) -> Result<()> {
+        self.cross(unit_id, direction, play::move_unit_across_zones)
+    }
+
+    // Crosses like move_across_zones, with the unit's Infiltration ability
+    pub fn infiltrate(&mut self, unit_id: u32, direction: Direction) -> Result<()> {
+        self.cross(unit_id, direction, play::infiltrate_across_zones)
+    }
+
+    fn cross(
+        &mut self,
+        unit_id: u32,
+        direction: Direction,
+        cross: fn(&mut Game, &mut Player, &mut Zone, &mut Zone, &mut Unit, Direction) -> Result<()>,
+    ) -> Result<()> {
+        let index = self.current_player();
+        let mut unit = self.unit(unit_id).cloned().unwrap_or_default();
+        let source = self.zone_index(unit.zone_id).unwrap_or_default();
+        let destination = self.zones[source].connected_zones[direction.index()]
+            .and_then(|zone_id| self.zone_index(zone_id))
+            .unwrap_or(source);
+
+        let mut game = self.game.clone();
+        let mut player = self.players[index].clone();
+        let mut source_zone = self.zones[source].clone();
+        let mut destination_zone = self.zones[destination].clone();
+
+        cross(
+            &mut game,
+            &mut player,
+            &mut source_zone,
+            &mut destination_zone,
+            &mut unit,
+            direction,
+        )?;
+
+        self.game = game;
+        self.players[index] = player;
+        self.zones[source] = source_zone;
+        self.zones[destination] = destination_zone;
+        self.store_unit(unit);
+        self.now += ACTION_SECONDS;
+        Ok(())
+    }
+
+    // Moves [energy, antibodies, stem_cells, nutrients] out of or into the
+    // pools of `zone`, see play::transfer_resources
+    pub fn transfer(&mut self, zone: usize, other_zone: usize, transfer: Transfer, amounts: [u32; 4]) -> Result<()> {
+        let index = self.current_player();
+        let mut game = self.game.clone();
+        let mut player = self.players[index].clone();
+        let mut zone_state = self.zones[zone].clone();
+        let mut other_state = self.zones[other_zone].clone();
+
+        play::transfer_resources(&mut game, &mut player, &mut zone_state, &mut other_state, transfer, amounts)?;
+
+        self.game = game;
+        self.players[index] = player;
+        self.zones[zone] = zone_state;
+        self.zones[other_zone] = other_state;
+        self.now += ACTION_SECONDS;
+        Ok(())
+    }
+
+    pub fn end_turn(&mut self, zone: usize) -> Result<()> {
+        self.end_turn_with(zone, [0, 0])
+    }
+
+    // Ends the turn passing the components of up to two units, whose action
+    // budgets are reset for their owner's next turn. 0 passes a fresh one.
+    pub fn end_turn_with(&mut self, zone: usize, unit_ids: [u32; 2]) -> Result<()> {
+        let command = Command { action: ActionType::EndTurn, x: 0, y: 0, unit_type: 0, ability_index: 0 };
+        let [unit, other_unit] = unit_ids.map(|unit_id| self.unit(unit_id).cloned().unwrap_or_default());
+        let (unit, other_unit) = self.play(zone, unit, other_unit, command)?;
+        for unit in [unit, other_unit] {
+            if unit.owner != Pubkey::default() {
+                self.store_unit(unit);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn expand(&mut self, source: usize, target: usize, expansion_type: ExpansionType, new_zone: NewZone) -> Result<()> {
+        let index = self.current_player();
+        let mut game = self.game.clone();
+        let mut player = self.players[index].clone();
+        let mut source_zone = self.zones[source].clone();
+        let mut target_zone = self.zones[target].clone();
+        let mut zone_index = self.zone_index.clone();
+
+        expand_zone::apply_expansion(
+            &mut game,
+            &mut player,
+            &mut source_zone,
+            &mut target_zone,
+            &mut zone_index,
+            expansion_type,
+            new_zone,
+        )?;
+
+        self.game = game;
+        self.players[index] = player;
+        self.zones[source] = source_zone;
+        self.zones[target] = target_zone;
+        self.zone_index = zone_index;
+        self.now += ACTION_SECONDS;
+        Ok(())
+    }
+
+    // The opponent of the current player stands in as the defender
+    pub fn conquer(&mut self, source: usize, target: usize) -> Result<()> {
+        let index = self.current_player();
+        let mut game = self.game.clone();
+        let mut player = self.players[index].clone();
+        let mut defender = self.players[1 - index].clone();
+        let mut target_zone = self.zones[target].clone();
+
+        conquer_zone::apply_conquest(&mut game, &mut player, &mut defender, &self.zones[source], &mut target_zone)?;
+
+        self.game = game;
+        self.players[index] = player;
+        self.players[1 - index] = defender;
+        self.zones[target] = target_zone;
+        self.now += ACTION_SECONDS;
+        Ok(())
+    }
+
+    // Creates a zone on (x, y) next to `source`, then links it with every
+    // other zone already on a neighbouring tile, as a client would in one
+    // transaction. Returns the index of the new zone.
+    pub fn create_zone(&mut self, source: usize, zone_type: u8, x: u8, y: u8) -> Result<usize> {
+        let target = self.add_zone();
+        let new_zone = NewZone { zone_type, x, y };
+        if let Err(error) = self.expand(source, target, ExpansionType::CreateNewZone, new_zone) {
+            self.zones.pop();
+            return Err(error);
+        }
+
+        for zone_id in self.zone_index.neighbours(x, y).into_iter().flatten() {
+            match self.zone_index(zone_id) {
+                Some(neighbour) if neighbour != source => self.connect_zones(target, neighbour)?,
+                _ => {}
+            }
+        }
+        Ok(target)
+    }
+
+    pub fn connect_zones(&mut self, zone: usize, neighbour: usize) -> Result<()> {
+        let mut zone_state = self.zones[zone].clone();
+        let mut neighbour_state = self.zones[neighbour].clone();
+        connect_zones::link_zones(&mut zone_state, &mut neighbour_state, &self.zone_index)?;
+        self.zones[zone] = zone_state;
+        self.zones[neighbour] = neighbour_state;
+        Ok(())
+    }
+
+    // Runs a game-control action for the player at `index`, whoever's turn it is
+    pub fn control(&mut self, index: usize, action: ControlAction) -> Result<()> {
+        let mut game = self.game.clone();
+        game_control::apply_control(&mut game, &Self::player_key(index), action, self.now)?;
+        self.game = game;
+        Ok(())
+    }
+
+    pub fn claim_timeout(&mut self) -> Result<()> {
+        let mut game = self.game.clone();
+        claim_timeout::force_end_turn(&mut game, [&self.players[0], &self.players[1]], self.now)?;
+        self.game = game;
+        Ok(())
+    }
+
+    // Runs a play action with the acting unit's component passed as
+    // target_unit as well. The world program writes target_unit back last.
+    pub fn act_on_itself(&mut self, unit_id: u32, command: Command) -> Result<()> {
+        let unit = self.unit(unit_id).cloned().unwrap_or_default();
+        let zone = self.zone_index(unit.zone_id).unwrap_or_default();
+        let (_, target_unit) = self.play(zone, unit.clone(), unit, command)?;
+        self.store_unit(target_unit);
+        Ok(())
+    }
+
+    // Runs a unit action in the unit's own zone, against whatever unit stands
+    // on the target cell
+    fn act(&mut self, unit_id: u32, command: Command) -> Result<Option<u32>> {
+        let unit = self.unit(unit_id).cloned().unwrap_or_default();
+        let zone = self.zone_index(unit.zone_id).unwrap_or_default();
+        let target_unit = match command.action {
+            ActionType::AttackPosition => self.unit_at(zone, command.x, command.y).cloned().unwrap_or_default(),
+            _ => Unit::default(),
+        };
+
+        let (unit, target_unit) = self.play(zone, unit, target_unit, command)?;
+        self.store_unit(unit);
+
+        // A bound target_unit is either the attacked unit or a new child
+        let child_id = match command.action {
+            ActionType::UseSpecialAbility if target_unit.owner != Pubkey::default() => Some(target_unit.unit_id),
+            _ => None,
+        };
+        if target_unit.owner != Pubkey::default() {
+            self.store_unit(target_unit);
+        }
+        Ok(child_id)
+    }
+
+    fn play(&mut self, zone: usize, mut unit: Unit, mut target_unit: Unit, command: Command) -> Result<(Unit, Unit)> {
+        let index = self.current_player();
+        let mut game = self.game.clone();
+        let mut player = self.players[index].clone();
+        let mut zone_state = self.zones[zone].clone();
+
+        play::apply_action(
+            &mut game,
+            &mut player,
+            &mut zone_state,
+            &mut unit,
+            &mut target_unit,
+            command,
+            self.now,
+        )?;
+
+        self.game = game;
+        self.players[index] = player;
+        self.zones[zone] = zone_state;
+        self.now += ACTION_SECONDS;
+        Ok((unit, target_unit))
+    }
+
+    fn unit_at(&self, zone: usize, x: u8, y: u8) -> Option<&Unit> {
+        if x as usize >= GRID_SIZE || y as usize >= GRID_SIZE {
+            return None;
+        }
+        match self.zones[zone].cell(x, y)? {
+            CellContent::ImmuneCell { unit_id, .. } | CellContent::Pathogen { unit_id, .. } => self.unit(unit_id),
+            _ => None,
+        }
+    }
+
+    fn store_unit(&mut self, unit: Unit) {
+        match self.units.iter_mut().find(|stored| stored.unit_id == unit.unit_id) {
+            Some(stored) => *stored = unit,
+            None => self.units.push(unit),
+        }
+    }
+}
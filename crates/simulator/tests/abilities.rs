@@ -0,0 +1,300 @@
+use bio_simulator::Simulation;
+use grid::CellContent;
+use play::{BioCommanderError, Direction};
+
+const IMMUNE: u8 = 0;
+const PATHOGEN: u8 = 1;
+const T_CELL: u8 = 0;
+const MACROPHAGE: u8 = 2;
+const NATURAL_KILLER: u8 = 5;
+const VIRUS: u8 = 6;
+const BACTERIA: u8 = 7;
+const FUNGUS: u8 = 8;
+const CANCER: u8 = 10;
+
+// Player 1's T cell on (14, 4) of zone 0, next to a unit of player 2 of type
+// `pathogen` that crossed in on (15, 4). Player 1 is to play.
+fn skirmish(pathogen: u8) -> (Simulation, u32, u32) {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    simulation.place_zone(1, 1, 0);
+    simulation.connect_zones(0, 1).unwrap();
+    let t_cell = simulation.spawn(0, T_CELL, 14, 4).unwrap();
+    simulation.end_turn(0).unwrap();
+    let enemy = simulation.spawn(1, pathogen, 0, 4).unwrap();
+    simulation.end_turn(1).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.move_across_zones(enemy, Direction::West).unwrap();
+    simulation.end_turn(1).unwrap();
+    (simulation, t_cell, enemy)
+}
+
+// Health the grid holds for the unit on (x, y) of `zone`
+fn cell_health(simulation: &Simulation, zone: usize, x: u8, y: u8) -> Option<u16> {
+    match simulation.zones[zone].cell(x, y)? {
+        CellContent::ImmuneCell { health, .. } | CellContent::Pathogen { health, .. } => Some(health),
+        _ => None,
+    }
+}
+
+// Energy and nutrients the player holds in their reserves and zone pools
+fn holdings(simulation: &Simulation, player: usize, zone: usize) -> (u64, u64) {
+    let (reserves, pools) = (&simulation.players[player], &simulation.zones[zone]);
+    (
+        reserves.energy_reserves + pools.energy as u64,
+        reserves.nutrient_reserves + pools.nutrients as u64,
+    )
+}
+
+#[test]
+fn cytokine_release_damages_enemies_around() {
+    let (mut simulation, t_cell, virus) = skirmish(VIRUS);
+    let level = simulation.game.immune_response_level;
+
+    simulation.use_ability(t_cell, 0, 0, 0).unwrap();
+
+    assert_eq!(cell_health(&simulation, 0, 15, 4), Some(30));
+    assert_eq!(cell_health(&simulation, 0, 14, 4), Some(80));
+    assert_eq!(simulation.game.immune_response_level, level + 1);
+
+    // The virus picks up the damage from the grid when it next acts
+    simulation.end_turn(0).unwrap();
+    simulation.move_unit(virus, 15, 6).unwrap();
+    assert_eq!(simulation.unit(virus).unwrap().health, 30);
+}
+
+#[test]
+fn toxin_release_damages_enemies_around() {
+    let (mut simulation, _, bacteria) = skirmish(BACTERIA);
+    simulation.end_turn(0).unwrap();
+    let level = simulation.game.infection_level;
+
+    simulation.use_ability(bacteria, 1, 0, 0).unwrap();
+
+    assert_eq!(cell_health(&simulation, 0, 14, 4), Some(65));
+    assert_eq!(cell_health(&simulation, 0, 15, 4), Some(60));
+    assert_eq!(simulation.game.infection_level, level + 1);
+}
+
+#[test]
+fn units_killed_by_area_effects_are_settled_lazily() {
+    let (mut simulation, t_cell, virus) = skirmish(VIRUS);
+    simulation.zones[0].set_unit_health(15, 4, 10);
+    let unit_count = simulation.zones[0].unit_count;
+
+    simulation.use_ability(t_cell, 0, 0, 0).unwrap();
+
+    assert!(simulation.zones[0].is_empty_cell(15, 4));
+    assert_eq!(simulation.zones[0].unit_count, unit_count - 1);
+
+    // The virus's component is out of reach of the ability and stays active,
+    // but the unit is gone from the grid and can no longer act
+    assert!(simulation.unit(virus).unwrap().is_active);
+    assert_eq!(simulation.players[1].total_units, 1);
+    simulation.end_turn(0).unwrap();
+    assert!(simulation.move_unit(virus, 15, 6).is_err());
+
+    // Its owner's count is settled as soon as they act
+    simulation.end_turn(1).unwrap();
+    assert_eq!(simulation.players[1].total_units, 0);
+}
+
+#[test]
+fn memory_response_raises_attack_and_defense_up_to_the_cap() {
+    let (mut simulation, t_cell, _) = skirmish(VIRUS);
+
+    simulation.use_ability(t_cell, 1, 0, 0).unwrap();
+    let unit = simulation.unit(t_cell).unwrap();
+    assert_eq!((unit.attack, unit.defense), (17, 12));
+
+    // 15 attack and 10 defense at base, at most MAX_STAT_BONUS (10) above
+    for _ in 0..3 {
+        simulation.end_turn(0).unwrap();
+        simulation.end_turn(1).unwrap();
+    }
+    let unit = simulation.units.iter_mut().find(|unit| unit.unit_id == t_cell).unwrap();
+    (unit.attack, unit.defense) = (24, 19);
+    simulation.use_ability(t_cell, 1, 0, 0).unwrap();
+    let unit = simulation.unit(t_cell).unwrap();
+    assert_eq!((unit.attack, unit.defense), (25, 20));
+}
+
+#[test]
+fn mutation_raises_attack_and_health_up_to_the_cap() {
+    let (mut simulation, _, virus) = skirmish(VIRUS);
+    simulation.end_turn(0).unwrap();
+
+    simulation.use_ability(virus, 2, 0, 0).unwrap();
+    let unit = simulation.unit(virus).unwrap();
+    assert_eq!((unit.attack, unit.max_health, unit.health), (15, 45, 45));
+    assert_eq!(cell_health(&simulation, 0, 15, 4), Some(45));
+
+    // 40 health at base, at most MAX_STAT_BONUS (10) above
+    for _ in 0..3 {
+        simulation.end_turn(1).unwrap();
+        simulation.end_turn(0).unwrap();
+    }
+    simulation.units.iter_mut().find(|unit| unit.unit_id == virus).unwrap().max_health = 48;
+    simulation.use_ability(virus, 2, 0, 0).unwrap();
+    let unit = simulation.unit(virus).unwrap();
+    assert_eq!((unit.attack, unit.max_health, unit.health), (18, 50, 47));
+}
+
+#[test]
+fn immune_evasion_hides_the_unit_through_the_opponents_turn() {
+    let (mut simulation, t_cell, virus) = skirmish(VIRUS);
+    simulation.end_turn(0).unwrap();
+
+    // Moving keeps it hidden
+    simulation.use_ability(virus, 1, 0, 0).unwrap();
+    simulation.move_unit(virus, 14, 5).unwrap();
+    simulation.end_turn(1).unwrap();
+    assert!(simulation.zones[0].is_hidden(14, 5, simulation.game.turn_number));
+
+    // Neither attacks nor area effects reach it
+    assert!(simulation.attack(t_cell, 14, 5).is_err());
+    simulation.use_ability(t_cell, 0, 0, 0).unwrap();
+    assert_eq!(cell_health(&simulation, 0, 14, 5), Some(40));
+
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+    simulation.attack(t_cell, 14, 5).unwrap();
+}
+
+#[test]
+fn metastasis_moves_the_unit_within_range() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    simulation.players[1].unlock_unit(CANCER as usize);
+    simulation.end_turn(0).unwrap();
+    let cancer = simulation.spawn(1, CANCER, 5, 5).unwrap();
+    simulation.end_turn(1).unwrap();
+    simulation.end_turn(0).unwrap();
+    let level = simulation.game.infection_level;
+
+    // METASTASIS_RANGE (6) cells at most
+    assert!(simulation.use_ability(cancer, 1, 5, 12).is_err());
+    simulation.use_ability(cancer, 1, 9, 7).unwrap();
+
+    let unit = simulation.unit(cancer).unwrap();
+    assert_eq!((unit.x, unit.y), (9, 7));
+    assert!(simulation.zones[1].is_empty_cell(5, 5));
+    assert!(matches!(simulation.zones[1].cell(9, 7), Some(CellContent::Pathogen { unit_id, .. }) if unit_id == cancer));
+    assert_eq!(simulation.game.infection_level, level + 2);
+}
+
+#[test]
+fn resource_drain_steals_from_enemy_zones_only() {
+    let (mut simulation, _, fungus) = skirmish(FUNGUS);
+    simulation.end_turn(0).unwrap();
+    let pools = (simulation.zones[0].energy, simulation.zones[0].nutrients);
+    let (energy, nutrients) = (simulation.players[1].energy_reserves, simulation.players[1].nutrient_reserves);
+
+    simulation.use_ability(fungus, 1, 0, 0).unwrap();
+
+    // The enemy zone's pools do not pay for the ability (10 energy)
+    let drained = (pools.0.min(30), pools.1.min(20));
+    assert_eq!((simulation.zones[0].energy, simulation.zones[0].nutrients), (pools.0 - drained.0, pools.1 - drained.1));
+    assert_eq!(simulation.players[1].energy_reserves, energy + drained.0 as u64 - 10);
+    assert_eq!(simulation.players[1].nutrient_reserves, nutrients + drained.1 as u64);
+
+    // Crossed back into its own zone, it has nothing to drain
+    for _ in 0..2 {
+        simulation.end_turn(1).unwrap();
+        simulation.end_turn(0).unwrap();
+    }
+    simulation.move_across_zones(fungus, Direction::East).unwrap();
+    assert!(simulation.use_ability(fungus, 1, 0, 0).is_err());
+}
+
+#[test]
+fn phagocytosis_heals_and_digests() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    let macrophage = simulation.spawn(0, MACROPHAGE, 4, 4).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+    simulation.zones[0].set_unit_health(4, 4, 110);
+    let (energy, nutrients) = holdings(&simulation, 0, 0);
+
+    simulation.use_ability(macrophage, 0, 0, 0).unwrap();
+
+    // Healed by 20, up to its 120 max health
+    assert_eq!(simulation.unit(macrophage).unwrap().health, 120);
+    assert_eq!(cell_health(&simulation, 0, 4, 4), Some(120));
+    // 10 energy and 5 nutrients, less the 5 energy it costs
+    assert_eq!(holdings(&simulation, 0, 0), (energy + 5, nutrients + 5));
+}
+
+#[test]
+fn zone_healing_restores_the_zone_pools() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    simulation.players[0].unlock_unit(NATURAL_KILLER as usize);
+    let killer = simulation.spawn(0, NATURAL_KILLER, 4, 4).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+    let (energy, nutrients) = holdings(&simulation, 0, 0);
+
+    simulation.use_ability(killer, 1, 0, 0).unwrap();
+
+    // 50 energy and 30 nutrients, less the 10 energy it costs
+    assert_eq!(holdings(&simulation, 0, 0), (energy + 40, nutrients + 30));
+}
+
+#[test]
+fn abilities_cool_down_for_their_owners_turns() {
+    let (mut simulation, t_cell, _) = skirmish(VIRUS);
+
+    // CytokineRelease cools down for 2 of its owner's turns
+    simulation.use_ability(t_cell, 0, 0, 0).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+    assert!(simulation.use_ability(t_cell, 0, 0, 0).is_err());
+
+    // Other abilities are not held up
+    simulation.use_ability(t_cell, 1, 0, 0).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+    simulation.use_ability(t_cell, 0, 0, 0).unwrap();
+}
+
+// Player 2's virus on (5, 5) of zone 1, ready to act
+fn lone_virus() -> (Simulation, u32) {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    simulation.end_turn(0).unwrap();
+    let virus = simulation.spawn(1, VIRUS, 5, 5).unwrap();
+    simulation.end_turn(1).unwrap();
+    simulation.end_turn(0).unwrap();
+    (simulation, virus)
+}
+
+#[test]
+fn replicas_take_the_first_free_cell_at_half_health() {
+    let (mut simulation, virus) = lone_virus();
+    simulation.zones[1].set_cell(5, 4, Some(CellContent::Obstacle)).unwrap();
+    let next_unit_id = simulation.game.next_unit_id;
+
+    // North is blocked, so the replica goes east
+    let child = simulation.use_ability(virus, 0, 0, 0).unwrap().unwrap();
+
+    assert_eq!(child, next_unit_id);
+    assert_eq!(simulation.game.next_unit_id, next_unit_id + 1);
+    let unit = simulation.unit(child).unwrap();
+    assert_eq!((unit.zone_id, unit.x, unit.y), (1, 6, 5));
+    assert_eq!((unit.health, unit.max_health), (20, 40));
+    assert_eq!(unit.owner, Simulation::player_key(1));
+    assert!(matches!(simulation.zones[1].cell(6, 5), Some(CellContent::Pathogen { unit_id, health: 20, seat: 2 }) if unit_id == child));
+    assert_eq!(simulation.players[1].total_units, 2);
+}
+
+#[test]
+fn surrounded_units_cannot_replicate() {
+    let (mut simulation, virus) = lone_virus();
+    for (x, y) in [(4, 4), (5, 4), (6, 4), (4, 5), (6, 5), (4, 6), (5, 6), (6, 6)] {
+        simulation.zones[1].set_cell(x, y, Some(CellContent::Obstacle)).unwrap();
+    }
+    let next_unit_id = simulation.game.next_unit_id;
+
+    let error = simulation.use_ability(virus, 0, 0, 0).unwrap_err();
+
+    assert_eq!(error, BioCommanderError::NoFreeCell.into());
+    assert_eq!(simulation.game.next_unit_id, next_unit_id);
+    assert_eq!(simulation.players[1].total_units, 1);
+}
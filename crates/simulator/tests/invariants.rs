@@ -0,0 +1,228 @@
+use bio_simulator::Simulation;
+use expand_zone::{ExpansionType, NewZone};
+use grid::{CellContent, POOL_CAPS};
+use play::Transfer;
+use proptest::prelude::*;
+
+#[derive(Clone, Debug)]
+enum Action {
+    Spawn { unit_type: u8, x: u8, y: u8 },
+    Move { unit: usize, dx: i8, dy: i8 },
+    Attack { unit: usize, dx: i8, dy: i8 },
+    Ability { unit: usize, ability_index: u8, dx: i8, dy: i8 },
+    Harvest { unit: usize, dx: i8, dy: i8 },
+    CreateZone { source: usize, zone_type: u8, x: u8, y: u8 },
+    Expand { source: usize, target: usize, expansion_type: u8 }, // InfectionSpread or ImmuneResponse
+    Transfer { zone: usize, other_zone: usize, transfer: u8, amounts: [u32; 4] },
+    Conquer { zone: usize },
+    EndTurn,
+    ClaimTimeout,
+}
+
+fn offset() -> impl Strategy<Value = i8> {
+    -3i8..=3
+}
+
+fn action() -> impl Strategy<Value = Action> {
+    prop_oneof![
+        3 => (0u8..12, 0u8..16, 0u8..16).prop_map(|(unit_type, x, y)| Action::Spawn { unit_type, x, y }),
+        3 => (any::<usize>(), offset(), offset()).prop_map(|(unit, dx, dy)| Action::Move { unit, dx, dy }),
+        3 => (any::<usize>(), offset(), offset()).prop_map(|(unit, dx, dy)| Action::Attack { unit, dx, dy }),
+        2 => (any::<usize>(), 0u8..3, offset(), offset())
+            .prop_map(|(unit, ability_index, dx, dy)| Action::Ability { unit, ability_index, dx, dy }),
+        1 => (any::<usize>(), -1i8..=1, -1i8..=1).prop_map(|(unit, dx, dy)| Action::Harvest { unit, dx, dy }),
+        // Tiles up to one past the edge of the 4x4 map
+        1 => (any::<usize>(), 0u8..5, 0u8..5, 0u8..5)
+            .prop_map(|(source, zone_type, x, y)| Action::CreateZone { source, zone_type, x, y }),
+        1 => (any::<usize>(), any::<usize>(), 0u8..2)
+            .prop_map(|(source, target, expansion_type)| Action::Expand { source, target, expansion_type }),
+        1 => (any::<usize>(), any::<usize>(), 0u8..3, prop::array::uniform4(0u32..120))
+            .prop_map(|(zone, other_zone, transfer, amounts)| Action::Transfer { zone, other_zone, transfer, amounts }),
+        1 => any::<usize>().prop_map(|zone| Action::Conquer { zone }),
+        2 => Just(Action::EndTurn),
+        1 => Just(Action::ClaimTimeout),
+    ]
+}
+
+fn shifted(coordinate: u8, delta: i8) -> u8 {
+    (coordinate as i16 + delta as i16).clamp(0, 15) as u8
+}
+
+// Zone owned by the current player, or the first zone when they own none
+fn home_zone(simulation: &Simulation) -> usize {
+    let player_key = Simulation::player_key(simulation.current_player());
+    simulation
+        .zones
+        .iter()
+        .position(|zone| zone.owner == player_key)
+        .unwrap_or_default()
+}
+
+// Picks one of the current player's units that is still on the board
+fn pick_unit(simulation: &Simulation, choice: usize) -> Option<(u32, u8, u8)> {
+    let player_key = Simulation::player_key(simulation.current_player());
+    let units: Vec<_> = simulation
+        .units
+        .iter()
+        .filter(|unit| unit.owner == player_key && unit.is_active)
+        .collect();
+    let unit = units.get(choice % units.len().max(1))?;
+    Some((unit.unit_id, unit.x, unit.y))
+}
+
+// Applies an action, ignoring rejections as the chain would
+fn apply(simulation: &mut Simulation, action: &Action) {
+    let _ = match *action {
+        Action::Spawn { unit_type, x, y } => {
+            let zone = home_zone(simulation);
+            simulation.spawn(zone, unit_type, x, y).map(|_| ())
+        }
+        Action::Move { unit, dx, dy } => match pick_unit(simulation, unit) {
+            Some((unit_id, x, y)) => simulation.move_unit(unit_id, shifted(x, dx), shifted(y, dy)),
+            None => Ok(()),
+        },
+        Action::Attack { unit, dx, dy } => match pick_unit(simulation, unit) {
+            Some((unit_id, x, y)) => simulation.attack(unit_id, shifted(x, dx), shifted(y, dy)),
+            None => Ok(()),
+        },
+        Action::Ability { unit, ability_index, dx, dy } => match pick_unit(simulation, unit) {
+            Some((unit_id, x, y)) => simulation
+                .use_ability(unit_id, ability_index, shifted(x, dx), shifted(y, dy))
+                .map(|_| ()),
+            None => Ok(()),
+        },
+        Action::Harvest { unit, dx, dy } => match pick_unit(simulation, unit) {
+            Some((unit_id, x, y)) => simulation.harvest(unit_id, shifted(x, dx), shifted(y, dy)),
+            None => Ok(()),
+        },
+        Action::CreateZone { source, zone_type, x, y } => {
+            let source = source % simulation.zones.len();
+            simulation.create_zone(source, zone_type, x, y).map(|_| ())
+        }
+        Action::Expand { source, target, expansion_type } => {
+            let expansion_type = [ExpansionType::InfectionSpread, ExpansionType::ImmuneResponse][expansion_type as usize];
+            let count = simulation.zones.len();
+            simulation.expand(source % count, target % count, expansion_type, NewZone::default())
+        }
+        Action::Transfer { zone, other_zone, transfer, amounts } => {
+            let transfer = [Transfer::ToZone, Transfer::ToReserves, Transfer::FromReserves][transfer as usize];
+            let count = simulation.zones.len();
+            simulation.transfer(zone % count, other_zone % count, transfer, amounts)
+        }
+        Action::Conquer { zone } => {
+            let source = home_zone(simulation);
+            let target = zone % simulation.zones.len();
+            simulation.conquer(source, target)
+        }
+        Action::EndTurn => {
+            let zone = home_zone(simulation);
+            simulation.end_turn(zone)
+        }
+        Action::ClaimTimeout => {
+            simulation.advance_time(simulation.game.turn_time_limit as i64);
+            simulation.claim_timeout()
+        }
+    };
+}
+
+fn check_invariants(simulation: &Simulation) -> Result<(), TestCaseError> {
+    let game = &simulation.game;
+    prop_assert!(game.current_turn == 1 || game.current_turn == 2);
+
+    for zone in &simulation.zones {
+        let unit_cells = zone
+            .occupied_cells()
+            .filter(|(_, _, content)| matches!(content, CellContent::ImmuneCell { .. } | CellContent::Pathogen { .. }))
+            .count();
+        prop_assert_eq!(zone.unit_count as usize, unit_cells, "unit_count of zone {}", zone.zone_id);
+
+        for (pool, cap) in zone.pools().into_iter().zip(POOL_CAPS) {
+            prop_assert!(pool <= cap, "pool of zone {} over its cap", zone.zone_id);
+        }
+
+        // Harvested nodes are cleared rather than left empty
+        let empty_nodes = zone
+            .occupied_cells()
+            .filter(|(_, _, content)| matches!(content, CellContent::Resource { amount: 0, .. }))
+            .count();
+        prop_assert_eq!(empty_nodes, 0, "empty resource nodes in zone {}", zone.zone_id);
+
+        // One indexed zone per tile, and links only between neighbours, both ways
+        prop_assert!(game.is_on_map(zone.x, zone.y));
+        prop_assert!(simulation.zone_index.contains(zone.zone_id, zone.x, zone.y));
+        let same_tile = simulation.zones.iter().filter(|other| (other.x, other.y) == (zone.x, zone.y));
+        prop_assert_eq!(same_tile.count(), 1, "zones on the tile of zone {}", zone.zone_id);
+        for (side, link) in zone.connected_zones.iter().enumerate() {
+            let Some(neighbour) = link.and_then(|zone_id| simulation.zone_index(zone_id)) else {
+                prop_assert!(link.is_none(), "zone {} links to a missing zone", zone.zone_id);
+                continue;
+            };
+            let neighbour = &simulation.zones[neighbour];
+            prop_assert_eq!(zone.side_towards(neighbour.x, neighbour.y), Some(side));
+            prop_assert_eq!(neighbour.connected_zones[(side + 2) % 4], Some(zone.zone_id));
+        }
+    }
+
+    prop_assert_eq!(simulation.zone_index.zones.len(), simulation.zones.len());
+
+    for (index, player) in simulation.players.iter().enumerate() {
+        let player_key = Simulation::player_key(index);
+
+        // Every unit cell on the board belongs to a bound unit of the seat it records
+        let mut live_units = 0;
+        for (_, _, content) in simulation.zones.iter().flat_map(|zone| zone.occupied_cells()) {
+            let (CellContent::ImmuneCell { unit_id, seat, .. } | CellContent::Pathogen { unit_id, seat, .. }) = content else {
+                continue;
+            };
+            if seat as usize == index + 1 {
+                let owner = simulation.unit(unit_id).map(|unit| unit.owner);
+                prop_assert_eq!(owner, Some(player_key), "owner of unit {}", unit_id);
+                live_units += 1;
+            }
+        }
+        let unsettled = player.total_units as usize - game.unit_losses(&player_key) as usize;
+        prop_assert_eq!(unsettled, live_units, "live units of player {}", index + 1);
+
+        let owned = simulation.zones.iter().filter(|zone| zone.owner == player_key);
+        prop_assert_eq!(player.controlled_zones as usize, owned.clone().count());
+        prop_assert_eq!(
+            player.organ_zones as usize,
+            owned.clone().filter(|zone| zone.zone_type == grid::ZoneType::Organ).count()
+        );
+
+        // Reserves only wrap around by underflowing
+        for reserve in [
+            player.energy_reserves,
+            player.antibody_reserves,
+            player.stem_cell_reserves,
+            player.nutrient_reserves,
+        ] {
+            prop_assert!(reserve < u64::MAX / 2);
+        }
+    }
+
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn bookkeeping_matches_the_board(
+        first_faction in 0u8..2,
+        adjacent in any::<bool>(),
+        actions in prop::collection::vec(action(), 1..300),
+    ) {
+        let mut simulation = Simulation::new([first_faction, 1 - first_faction]).unwrap();
+        if adjacent {
+            // Lets the players conquer each other's starting zones
+            simulation.place_zone(1, 1, 0);
+        }
+        check_invariants(&simulation)?;
+
+        for action in &actions {
+            apply(&mut simulation, action);
+            check_invariants(&simulation)?;
+        }
+    }
+}
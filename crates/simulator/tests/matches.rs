@@ -0,0 +1,667 @@
+use bio_simulator::{bot, Simulation};
+use bolt_lang::Pubkey;
+use expand_zone::{ExpansionType, NewZone};
+use game::{GameState, GameWinner};
+use game_control::ControlAction;
+use grid::{CellContent, ResourceType, POOL_CAPS};
+use play::{ActionType, Command, Direction, Transfer};
+use players::SpecialBonus;
+
+const IMMUNE: u8 = 0;
+const PATHOGEN: u8 = 1;
+const T_CELL: u8 = 0;
+const NEUTROPHIL: u8 = 3;
+const VIRUS: u8 = 6;
+const PARASITE: u8 = 9;
+const CIRCULATORY: u8 = 0;
+
+// Player 1 plays the immune system from zone 0, player 2 the pathogen from
+// zone 1, with the two starting zones moved next to each other
+fn neighbouring_match() -> Simulation {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    simulation.place_zone(1, 1, 0);
+    simulation
+}
+
+#[test]
+fn both_players_start_with_a_zone() {
+    let simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+
+    assert!(simulation.game.is_game_active());
+    assert_eq!(simulation.game.current_turn, 1);
+    for (index, player) in simulation.players.iter().enumerate() {
+        assert_eq!(player.controlled_zones, 1);
+        assert_eq!(simulation.zones[index].owner, Simulation::player_key(index));
+    }
+}
+
+#[test]
+fn players_pick_opposite_factions() {
+    assert!(Simulation::new([IMMUNE, IMMUNE]).is_err());
+    assert!(Simulation::new([PATHOGEN, PATHOGEN]).is_err());
+    assert!(Simulation::new([PATHOGEN, IMMUNE]).is_ok());
+}
+
+#[test]
+fn spawned_units_act_from_their_owners_next_turn() {
+    let mut simulation = neighbouring_match();
+    let t_cell = simulation.spawn(0, T_CELL, 4, 4).unwrap();
+
+    assert!(simulation.move_unit(t_cell, 4, 5).is_err());
+
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+    simulation.move_unit(t_cell, 4, 5).unwrap();
+
+    let unit = simulation.unit(t_cell).unwrap();
+    assert_eq!((unit.x, unit.y), (4, 5));
+    assert!(simulation.zones[0].is_empty_cell(4, 4));
+}
+
+#[test]
+fn ending_a_turn_resets_the_budgets_of_the_units_passed_in() {
+    let mut simulation = neighbouring_match();
+    let t_cell = simulation.spawn(0, T_CELL, 4, 4).unwrap();
+    let other = simulation.spawn(0, T_CELL, 8, 8).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+    simulation.move_unit(t_cell, 4, 5).unwrap();
+    simulation.use_ability(t_cell, 1, 0, 0).unwrap();
+    simulation.move_unit(other, 8, 9).unwrap();
+
+    simulation.end_turn_with(0, [t_cell, 0]).unwrap();
+
+    // Set up for the owner's next turn, while the unit left out keeps its
+    // spent budget until it next acts
+    let next_turn = simulation.game.turn_number + 1;
+    let unit = simulation.unit(t_cell).unwrap();
+    assert_eq!((unit.action_turn, unit.action_points, unit.has_moved), (next_turn, 2, false));
+    let unit = simulation.unit(other).unwrap();
+    assert_eq!((unit.action_points, unit.has_moved), (1, true));
+
+    // A unit of the other player is left as it is
+    let virus = simulation.spawn(1, VIRUS, 5, 5).unwrap();
+    simulation.end_turn_with(1, [t_cell, virus]).unwrap();
+    assert_eq!(simulation.unit(t_cell).unwrap().action_turn, next_turn);
+
+    simulation.move_unit(t_cell, 4, 6).unwrap();
+}
+
+#[test]
+fn rejected_actions_leave_the_state_unchanged() {
+    let mut simulation = neighbouring_match();
+    simulation.spawn(0, T_CELL, 4, 4).unwrap();
+    let energy = simulation.players[0].energy_reserves;
+    let next_unit_id = simulation.game.next_unit_id;
+
+    // Occupied cell
+    assert!(simulation.spawn(0, T_CELL, 4, 4).is_err());
+    // Locked unit type
+    assert!(simulation.spawn(0, PARASITE, 6, 6).is_err());
+
+    assert_eq!(simulation.players[0].energy_reserves, energy);
+    assert_eq!(simulation.game.next_unit_id, next_unit_id);
+    assert_eq!(simulation.players[0].total_units, 1);
+}
+
+#[test]
+fn units_of_another_game_are_rejected() {
+    let mut simulation = neighbouring_match();
+    simulation.connect_zones(0, 1).unwrap();
+    let t_cell = simulation.spawn(0, T_CELL, 14, 4).unwrap();
+    simulation.end_turn(0).unwrap();
+    let virus = simulation.spawn(1, VIRUS, 0, 4).unwrap();
+    simulation.end_turn(1).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.move_across_zones(virus, Direction::West).unwrap();
+    simulation.end_turn(1).unwrap();
+
+    // Unit components with the same ids, bound in another game
+    let forge = |simulation: &mut Simulation, unit_id: u32, game_id: Pubkey| {
+        simulation.units.iter_mut().find(|unit| unit.unit_id == unit_id).unwrap().game_id = game_id;
+    };
+    let game_id = simulation.game.game_id;
+    let other_game = Pubkey::new_unique();
+    forge(&mut simulation, t_cell, other_game);
+    assert!(simulation.move_unit(t_cell, 14, 5).is_err());
+    forge(&mut simulation, t_cell, game_id);
+    forge(&mut simulation, virus, other_game);
+    assert!(simulation.attack(t_cell, 15, 4).is_err());
+
+    forge(&mut simulation, virus, game_id);
+    simulation.attack(t_cell, 15, 4).unwrap();
+    assert!(simulation.unit(virus).unwrap().health < 40);
+}
+
+#[test]
+fn zones_of_another_game_are_rejected() {
+    let mut simulation = neighbouring_match();
+
+    // Copies of both starting zones, placed in another game
+    let other_game = Pubkey::new_unique();
+    let mut forge = |zone: usize| {
+        let mut forged = simulation.zones[zone].clone();
+        forged.game_id = other_game;
+        simulation.zones.push(forged);
+        simulation.zones.len() - 1
+    };
+    let (home, enemy) = (forge(0), forge(1));
+    let reserves = simulation.players[0].energy_reserves;
+
+    assert!(simulation.spawn(home, T_CELL, 4, 4).is_err());
+    assert!(simulation.end_turn(home).is_err());
+    assert!(simulation.transfer(0, home, Transfer::FromReserves, [10, 0, 0, 0]).is_err());
+    let fresh = simulation.add_zone();
+    let new_zone = NewZone { zone_type: CIRCULATORY, x: 0, y: 1 };
+    assert!(simulation.expand(home, fresh, ExpansionType::CreateNewZone, new_zone).is_err());
+    assert!(simulation.conquer(0, enemy).is_err());
+
+    assert_eq!(simulation.players[0].energy_reserves, reserves);
+    assert_eq!(simulation.zones[enemy].owner, Simulation::player_key(1));
+    simulation.conquer(0, 1).unwrap();
+}
+
+#[test]
+fn a_unit_cannot_also_be_the_target_unit() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    let t_cell = simulation.spawn(0, T_CELL, 4, 4).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+
+    // Written back last, the target copy would hand back the spent move
+    let command = Command { action: ActionType::MoveUnit, x: 5, y: 4, unit_type: 0, ability_index: 0 };
+    assert!(simulation.act_on_itself(t_cell, command).is_err());
+    assert!(simulation.zones[0].is_empty_cell(5, 4));
+
+    simulation.move_unit(t_cell, 5, 4).unwrap();
+    assert!(simulation.unit(t_cell).unwrap().has_moved);
+}
+
+#[test]
+fn units_cross_into_connected_zones() {
+    let mut simulation = neighbouring_match();
+    simulation.connect_zones(0, 1).unwrap();
+    let t_cell = simulation.spawn(0, T_CELL, 15, 4).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+    let energy = simulation.players[0].energy_reserves;
+
+    simulation.move_across_zones(t_cell, Direction::East).unwrap();
+
+    let unit = simulation.unit(t_cell).unwrap();
+    assert_eq!((unit.zone_id, unit.x, unit.y), (simulation.zones[1].zone_id, 0, 4));
+    assert!(simulation.zones[0].is_empty_cell(15, 4));
+    assert!(matches!(simulation.zones[1].cell(0, 4), Some(CellContent::ImmuneCell { .. })));
+    assert_eq!((simulation.zones[0].unit_count, simulation.zones[1].unit_count), (0, 1));
+
+    let movement_cost = simulation.zones[1].zone_type.get_movement_cost() as u64;
+    assert_eq!(simulation.players[0].energy_reserves, energy - 10 * movement_cost);
+
+    // The crossing was the unit's move for this turn
+    assert!(simulation.move_unit(t_cell, 1, 4).is_err());
+}
+
+#[test]
+fn crossings_need_a_connected_border() {
+    let mut simulation = neighbouring_match();
+    let t_cell = simulation.spawn(0, T_CELL, 15, 4).unwrap();
+    let inland = simulation.spawn(0, T_CELL, 14, 4).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+
+    assert!(simulation.move_across_zones(t_cell, Direction::East).is_err());
+
+    simulation.connect_zones(0, 1).unwrap();
+    assert!(simulation.move_across_zones(inland, Direction::East).is_err());
+    assert!(simulation.move_across_zones(t_cell, Direction::North).is_err());
+    assert_eq!(simulation.unit(t_cell).unwrap().zone_id, simulation.zones[0].zone_id);
+}
+
+#[test]
+fn infiltration_slips_units_into_connected_zones() {
+    let mut simulation = neighbouring_match();
+    simulation.connect_zones(0, 1).unwrap();
+    simulation.players[0].unlock_unit(NEUTROPHIL as usize);
+    let far = simulation.spawn(0, NEUTROPHIL, 6, 4).unwrap();
+    let near = simulation.spawn(0, NEUTROPHIL, 7, 5).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+
+    // Only through move-across-zones, from within twice the movement range
+    assert!(simulation.use_ability(near, 1, 8, 5).is_err());
+    assert!(simulation.infiltrate(far, Direction::East).is_err());
+    let energy = |simulation: &Simulation| simulation.players[0].energy_reserves + simulation.zones[0].energy as u64;
+    let before = energy(&simulation);
+    simulation.infiltrate(near, Direction::East).unwrap();
+
+    let unit = simulation.unit(near).unwrap();
+    assert_eq!((unit.zone_id, unit.x, unit.y), (simulation.zones[1].zone_id, 0, 5));
+    assert!(simulation.zones[0].is_empty_cell(7, 5));
+    assert!(matches!(simulation.zones[1].cell(0, 5), Some(CellContent::ImmuneCell { .. })));
+    assert_eq!((simulation.zones[0].unit_count, simulation.zones[1].unit_count), (1, 1));
+    // The ability's cost instead of the crossing cost, from the source zone's pools first
+    assert_eq!(energy(&simulation), before - 25);
+
+    // The unit keeps its move, and the ability cools down
+    simulation.move_unit(near, 2, 5).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+    assert!(simulation.infiltrate(near, Direction::West).is_err());
+}
+
+#[test]
+fn created_zones_link_with_their_neighbours() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    let east = simulation.create_zone(0, CIRCULATORY, 1, 0).unwrap();
+    let south = simulation.create_zone(0, CIRCULATORY, 0, 1).unwrap();
+    let inner = simulation.create_zone(east, CIRCULATORY, 1, 1).unwrap();
+
+    let zone_ids: Vec<_> = simulation.zones.iter().map(|zone| zone.zone_id).collect();
+    assert_eq!(zone_ids, [0, 1, 2, 3, 4]);
+    assert_eq!(simulation.zones[0].connected_zones, [None, Some(2), Some(3), None]);
+    assert_eq!(simulation.zones[inner].connected_zones, [Some(2), None, None, Some(3)]);
+    assert_eq!(simulation.zones[east].connected_zones, [None, None, Some(4), Some(0)]);
+    assert_eq!(simulation.zones[south].connected_zones, [Some(0), Some(4), None, None]);
+
+    assert_eq!((simulation.zones[inner].x, simulation.zones[inner].y), (1, 1));
+    assert!(simulation.zones[east].is_border_zone);
+    assert!(!simulation.zones[inner].is_border_zone);
+
+    assert_eq!(simulation.zone_index.zone_at(1, 1), Some(4));
+    assert_eq!(simulation.zone_index.share(simulation.players[0].controlled_zones), 80);
+}
+
+#[test]
+fn zones_only_link_within_their_game() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    let east = simulation.create_zone(0, CIRCULATORY, 1, 0).unwrap();
+
+    // A zone of another game, claiming the starting zone's id and tile
+    let mut forged = simulation.zones[0].clone();
+    forged.game_id = Pubkey::new_unique();
+    forged.connected_zones = [None; 4];
+    simulation.zones.push(forged);
+    let forged = simulation.zones.len() - 1;
+    simulation.zones[east].connected_zones = [None; 4];
+
+    assert!(simulation.connect_zones(east, forged).is_err());
+    assert_eq!(simulation.zones[east].connected_zones, [None; 4]);
+    simulation.connect_zones(east, 0).unwrap();
+}
+
+#[test]
+fn zones_are_created_on_free_tiles_next_to_the_player() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    simulation.create_zone(0, CIRCULATORY, 1, 0).unwrap();
+    let energy = simulation.players[0].energy_reserves;
+
+    // Taken, not next to the source, next to the opponent's zone only
+    assert!(simulation.create_zone(0, CIRCULATORY, 1, 0).is_err());
+    assert!(simulation.create_zone(0, CIRCULATORY, 2, 2).is_err());
+    assert!(simulation.create_zone(1, CIRCULATORY, 3, 2).is_err());
+
+    // Off the map
+    simulation.game.map_height = 1;
+    assert!(simulation.create_zone(0, CIRCULATORY, 0, 1).is_err());
+
+    assert_eq!(simulation.zones.len(), 3);
+    assert_eq!(simulation.players[0].energy_reserves, energy);
+}
+
+#[test]
+fn created_zones_are_walled_with_gates() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    let north = simulation.create_zone(0, CIRCULATORY, 1, 0).unwrap();
+    let south = simulation.create_zone(north, CIRCULATORY, 1, 1).unwrap();
+    let cells: Vec<_> = simulation.zones[north].occupied_cells().map(|(_, _, content)| content).collect();
+    assert!(cells.iter().any(|content| matches!(content, CellContent::Resource { .. })));
+    assert!(matches!(simulation.zones[north].cell(0, 0), Some(CellContent::Obstacle)));
+
+    // Zones of the same type open the same gates on their shared border
+    let gate = (1..15).find(|&x| simulation.zones[north].is_empty_cell(x, 15)).unwrap();
+    assert!(simulation.zones[south].is_empty_cell(gate, 0));
+
+    let t_cell = simulation.spawn(north, T_CELL, gate, 15).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+    simulation.move_across_zones(t_cell, Direction::South).unwrap();
+    let unit = simulation.unit(t_cell).unwrap();
+    assert_eq!((unit.zone_id, unit.x, unit.y), (simulation.zones[south].zone_id, gate, 0));
+}
+
+#[test]
+fn units_cross_both_ways_between_a_created_zone_and_its_other_neighbour() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    let east = simulation.create_zone(0, CIRCULATORY, 1, 0).unwrap();
+    let south = simulation.create_zone(0, CIRCULATORY, 0, 1).unwrap();
+    let inner = simulation.create_zone(east, CIRCULATORY, 1, 1).unwrap();
+
+    // Created from east, the inner zone was linked to south by connect_zones
+    let gate = (1..15).find(|&y| simulation.zones[inner].is_empty_cell(0, y)).unwrap();
+    assert!(simulation.zones[south].is_empty_cell(15, gate));
+    let t_cell = simulation.spawn(inner, T_CELL, 0, gate).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+
+    simulation.move_across_zones(t_cell, Direction::West).unwrap();
+    let unit = simulation.unit(t_cell).unwrap();
+    assert_eq!((unit.zone_id, unit.x, unit.y), (simulation.zones[south].zone_id, 15, gate));
+    assert_eq!((simulation.zones[inner].unit_count, simulation.zones[south].unit_count), (0, 1));
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+
+    simulation.move_across_zones(t_cell, Direction::East).unwrap();
+    let unit = simulation.unit(t_cell).unwrap();
+    assert_eq!((unit.zone_id, unit.x, unit.y), (simulation.zones[inner].zone_id, 0, gate));
+    assert!(simulation.zones[south].is_empty_cell(15, gate));
+    assert!(matches!(simulation.zones[inner].cell(0, gate), Some(CellContent::ImmuneCell { .. })));
+
+    // The starting zone is diagonal to the inner zone, so the two never link
+    assert!(simulation.connect_zones(inner, 0).is_err());
+    assert_eq!(simulation.zones[inner].connected_zones, [Some(2), None, None, Some(3)]);
+}
+
+#[test]
+fn expansions_only_reach_zones_on_the_map() {
+    let mut simulation = neighbouring_match();
+    let pools = simulation.zones[1].pools();
+    let level = simulation.game.immune_response_level;
+
+    // A zone component no expansion placed, moved next to the player's zone
+    let stray = simulation.add_zone();
+    (simulation.zones[stray].x, simulation.zones[stray].y) = (0, 1);
+    assert!(simulation.expand(0, stray, ExpansionType::ImmuneResponse, NewZone::default()).is_err());
+    assert_eq!(simulation.zones[stray].owner, Default::default());
+
+    // Expansions reach out from the player's own zones, not into them
+    let created = simulation.create_zone(0, CIRCULATORY, 0, 1).unwrap();
+    assert!(simulation.expand(0, created, ExpansionType::ImmuneResponse, NewZone::default()).is_err());
+    assert!(simulation.expand(created, 0, ExpansionType::ImmuneResponse, NewZone::default()).is_err());
+
+    simulation.expand(0, 1, ExpansionType::ImmuneResponse, NewZone::default()).unwrap();
+    assert_eq!(simulation.zones[1].energy, pools[0] + 50);
+    assert_eq!(simulation.zones[1].antibodies, pools[1] + 100);
+    assert_eq!(simulation.game.immune_response_level, level + 5);
+}
+
+#[test]
+fn expansions_only_use_the_index_of_their_game() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    assert_eq!(simulation.zone_index.game_id, simulation.game.game_id);
+
+    // The index of another game, with the same zones on it
+    let game_id = simulation.game.game_id;
+    simulation.zone_index.game_id = Pubkey::new_unique();
+    assert!(simulation.create_zone(0, CIRCULATORY, 1, 0).is_err());
+    assert_eq!(simulation.game.next_zone_id, 2);
+
+    simulation.zone_index.game_id = game_id;
+    simulation.create_zone(0, CIRCULATORY, 1, 0).unwrap();
+}
+
+#[test]
+fn units_harvest_nodes_until_they_run_dry() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    let zone = simulation.create_zone(0, CIRCULATORY, 1, 0).unwrap();
+
+    // A resource node with an empty cell next to it
+    let (node, cell, resource_type, amount) = simulation.zones[zone]
+        .occupied_cells()
+        .find_map(|(x, y, content)| {
+            let CellContent::Resource { resource_type, amount } = content else { return None };
+            let cell = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                .into_iter()
+                .find(|&(x, y)| simulation.zones[zone].is_empty_cell(x, y))?;
+            Some(((x, y), cell, resource_type, amount))
+        })
+        .unwrap();
+    let t_cell = simulation.spawn(zone, T_CELL, cell.0, cell.1).unwrap();
+    let reserves = |simulation: &Simulation| {
+        let player = &simulation.players[0];
+        match resource_type {
+            ResourceType::Energy => player.energy_reserves,
+            ResourceType::Antibodies => player.antibody_reserves,
+            ResourceType::StemCells => player.stem_cell_reserves,
+            ResourceType::Nutrients => player.nutrient_reserves,
+        }
+    };
+
+    let mut left = amount;
+    while left > 0 {
+        simulation.end_turn(0).unwrap();
+        simulation.end_turn(1).unwrap();
+        let before = reserves(&simulation);
+        simulation.harvest(t_cell, node.0, node.1).unwrap();
+        assert_eq!(reserves(&simulation) - before, left.min(25) as u64);
+        left -= left.min(25);
+    }
+
+    assert!(simulation.zones[zone].is_empty_cell(node.0, node.1));
+    assert!(simulation.harvest(t_cell, node.0, node.1).is_err());
+    assert!(simulation.harvest(t_cell, cell.0, cell.1).is_err());
+}
+
+#[test]
+fn spawns_draw_from_the_zone_pools_first() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    let reserves = simulation.players[0].energy_reserves;
+    let pool = simulation.zones[0].energy;
+
+    // A T cell costs 16 energy in the immune player's lymphatic starting zone
+    simulation.spawn(0, T_CELL, 4, 4).unwrap();
+    assert_eq!(simulation.zones[0].energy, pool - 16);
+    assert_eq!(simulation.players[0].energy_reserves, reserves);
+
+    simulation.zones[0].energy = 5;
+    simulation.spawn(0, T_CELL, 4, 5).unwrap();
+    assert_eq!(simulation.zones[0].energy, 0);
+    assert_eq!(simulation.players[0].energy_reserves, reserves - 11);
+}
+
+#[test]
+fn ending_a_turn_credits_each_zone_once() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    let created = simulation.create_zone(0, CIRCULATORY, 1, 0).unwrap();
+    let reserves = simulation.players[0].energy_reserves;
+    let pools = [simulation.zones[0].energy, simulation.zones[created].energy];
+
+    // The zone passed in generates into its pools, every other one into the reserves
+    simulation.end_turn(0).unwrap();
+
+    let (home, _, _, _) = simulation.zones[0].zone_type.get_resource_generation();
+    let (other, _, _, _) = simulation.zones[created].zone_type.get_resource_generation();
+    assert_eq!(simulation.zones[0].energy, (pools[0] + home).min(POOL_CAPS[0]));
+    assert_eq!(simulation.zones[created].energy, pools[1]);
+    assert_eq!(simulation.players[0].energy_reserves, reserves + other as u64);
+}
+
+#[test]
+fn production_bonuses_reach_pools_and_reserves() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    let created = simulation.create_zone(0, CIRCULATORY, 1, 0).unwrap();
+    simulation.players[0].special_bonuses[0] = Some(SpecialBonus::IncreasedProduction);
+    simulation.zones[0].energy = 0;
+    let reserves = simulation.players[0].energy_reserves;
+
+    simulation.end_turn(0).unwrap();
+
+    let (home, _, _, _) = simulation.zones[0].zone_type.get_resource_generation();
+    let (other, _, _, _) = simulation.zones[created].zone_type.get_resource_generation();
+    assert_eq!(simulation.zones[0].energy, home * 125 / 100);
+    assert_eq!(simulation.players[0].energy_reserves, reserves + other as u64 * 125 / 100);
+}
+
+#[test]
+fn resources_move_along_linked_zones() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+    let near = simulation.create_zone(0, CIRCULATORY, 1, 0).unwrap();
+    let far = simulation.create_zone(near, CIRCULATORY, 2, 0).unwrap();
+    let pools = |simulation: &Simulation, zone: usize| simulation.zones[zone].pools();
+    let (home_pools, near_pools) = (pools(&simulation, 0), pools(&simulation, near));
+    let reserves = simulation.players[0].energy_reserves;
+
+    simulation.transfer(0, near, Transfer::ToZone, [40, 0, 0, 10]).unwrap();
+    assert_eq!(pools(&simulation, 0), [home_pools[0] - 40, home_pools[1], home_pools[2], home_pools[3] - 10]);
+    assert_eq!(pools(&simulation, near)[0], near_pools[0] + 40);
+
+    // Reserves are reached from the starting zone and the zones linked to it
+    simulation.transfer(near, 0, Transfer::ToReserves, [30, 0, 0, 0]).unwrap();
+    assert_eq!(simulation.players[0].energy_reserves, reserves + 30);
+    simulation.transfer(far, near, Transfer::ToZone, [10, 0, 0, 0]).unwrap();
+    assert!(simulation.transfer(far, near, Transfer::ToReserves, [10, 0, 0, 0]).is_err());
+    assert!(simulation.transfer(far, 0, Transfer::FromReserves, [10, 0, 0, 0]).is_err());
+    simulation.transfer(0, far, Transfer::FromReserves, [0, 0, 5, 0]).unwrap();
+
+    // Pools stay within their caps, and transfers only cross links
+    assert!(simulation.transfer(0, near, Transfer::FromReserves, [0, 0, 90, 0]).is_err());
+    assert!(simulation.transfer(0, far, Transfer::ToZone, [1, 0, 0, 0]).is_err());
+    assert!(simulation.transfer(0, 1, Transfer::ToZone, [1, 0, 0, 0]).is_err());
+    assert!(simulation.transfer(0, 0, Transfer::ToReserves, [1, 0, 0, 0]).is_err());
+
+    simulation.end_turn(0).unwrap();
+    assert!(simulation.transfer(0, near, Transfer::ToZone, [1, 0, 0, 0]).is_err());
+}
+
+#[test]
+fn conquest_brings_units_into_combat() {
+    let mut simulation = neighbouring_match();
+    simulation.end_turn(0).unwrap();
+
+    simulation.spawn(1, VIRUS, 5, 5).unwrap();
+    let survivor = simulation.spawn(1, VIRUS, 5, 7).unwrap();
+    simulation.end_turn(1).unwrap();
+
+    // Half of the defending units fall with the zone
+    simulation.conquer(0, 1).unwrap();
+    assert_eq!(simulation.zones[1].owner, Simulation::player_key(0));
+    assert_eq!(simulation.zones[1].unit_count, 1);
+    assert_eq!(simulation.game.unit_losses(&Simulation::player_key(1)), 1);
+    let standing = simulation.players[1].standing(&simulation.game);
+    assert_eq!((standing.live_units, standing.controlled_zones), (1, 0));
+
+    let t_cell = simulation.spawn(1, T_CELL, 5, 6).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.end_turn(1).unwrap();
+
+    // The loss settled when the defender acted
+    assert_eq!(simulation.players[1].total_units, 1);
+    assert_eq!(simulation.game.unit_losses(&Simulation::player_key(1)), 0);
+
+    // 15 attack + 2 faction bonus against 5 defense + 2 for tissue
+    simulation.attack(t_cell, 5, 7).unwrap();
+    assert_eq!(simulation.unit(survivor).unwrap().health, 30);
+}
+
+#[test]
+fn conquering_the_last_zone_of_a_unitless_player_wins() {
+    let mut simulation = neighbouring_match();
+
+    simulation.conquer(0, 1).unwrap();
+
+    assert!(matches!(simulation.game.game_state, GameState::Finished { winner: GameWinner::Player1 }));
+    assert_eq!(simulation.game.winner, Simulation::player_key(0));
+}
+
+#[test]
+fn controlling_most_of_the_map_wins_after_creating_zones() {
+    let mut simulation = Simulation::new([IMMUNE, PATHOGEN]).unwrap();
+
+    // Row by row, each tile is next to one created before it. The starting
+    // zone and 11 created zones are 75% of the 16 tiles.
+    let tiles = (0..4u8).flat_map(|y| (0..4u8).map(move |x| (x, y))).skip(1).take(11);
+    for (count, (x, y)) in tiles.enumerate() {
+        assert!(simulation.game.is_game_active());
+        let neighbour = match x {
+            0 => (x, y - 1),
+            _ => (x - 1, y),
+        };
+        let source = simulation.zone_index.zone_at(neighbour.0, neighbour.1).unwrap();
+        let source = simulation.zone_index(source).unwrap();
+        simulation.players[0].add_resources(1_000, 1_000, 1_000, 1_000);
+        simulation.create_zone(source, CIRCULATORY, x, y).unwrap();
+        assert_eq!(simulation.players[0].controlled_zones as usize, count + 2);
+    }
+
+    assert_eq!(simulation.game.total_zones, 16);
+    assert!(matches!(simulation.game.game_state, GameState::Finished { winner: GameWinner::Player1 }));
+}
+
+#[test]
+fn reaching_the_turn_limit_is_a_draw() {
+    let mut simulation = neighbouring_match();
+    simulation.game.max_turns = 4;
+
+    for turn in 0..4 {
+        simulation.end_turn(turn % 2).unwrap();
+    }
+
+    assert!(matches!(simulation.game.game_state, GameState::Finished { winner: GameWinner::Draw }));
+    assert!(simulation.end_turn(0).is_err());
+}
+
+#[test]
+fn idle_players_lose_their_turn_to_a_timeout_claim() {
+    let mut simulation = neighbouring_match();
+
+    assert!(simulation.claim_timeout().is_err());
+
+    simulation.advance_time(simulation.game.turn_time_limit as i64);
+    simulation.claim_timeout().unwrap();
+
+    assert_eq!(simulation.game.current_turn, 2);
+    assert_eq!(simulation.game.timeout_strikes, [1, 0]);
+}
+
+#[test]
+fn faction_wins_go_to_the_seat_playing_the_faction() {
+    let mut simulation = neighbouring_match();
+    simulation.game.infection_level = 100;
+
+    // The immune player acts, so only their standing is checked
+    simulation.end_turn(0).unwrap();
+
+    assert!(matches!(simulation.game.game_state, GameState::Finished { winner: GameWinner::Infection }));
+    assert_eq!(simulation.game.winner, Simulation::player_key(1));
+}
+
+#[test]
+fn pausing_needs_both_players_and_either_can_resume() {
+    let mut simulation = neighbouring_match();
+
+    // A pause request lapses when the turn changes
+    simulation.control(0, ControlAction::RequestPause).unwrap();
+    simulation.end_turn(0).unwrap();
+    simulation.control(1, ControlAction::RequestPause).unwrap();
+    assert!(simulation.game.is_game_active());
+    simulation.control(0, ControlAction::RequestPause).unwrap();
+    assert!(simulation.game.game_state == GameState::Paused);
+
+    // The player not on turn ends the pause alone
+    simulation.control(0, ControlAction::Resume).unwrap();
+    assert!(simulation.game.is_game_active());
+    assert!(simulation.control(1, ControlAction::Resume).is_err());
+}
+
+#[test]
+fn random_matches_always_finish() {
+    for seed in 0..200 {
+        let simulation = bot::play_random_match(seed).unwrap();
+
+        assert!(
+            matches!(simulation.game.game_state, GameState::Finished { .. }),
+            "match {seed} did not finish"
+        );
+        assert!(simulation.game.turn_number <= simulation.game.max_turns);
+    }
+}
+
+#[test]
+fn random_matches_replay_from_their_seed() {
+    let first = bot::play_random_match(7).unwrap();
+    let second = bot::play_random_match(7).unwrap();
+
+    assert_eq!(first.game.turn_number, second.game.turn_number);
+    assert_eq!(first.game.next_unit_id, second.game.next_unit_id);
+    assert_eq!(first.players[0].energy_reserves, second.players[0].energy_reserves);
+    assert_eq!(first.zones.len(), second.zones.len());
+}
@@ -1,23 +1,39 @@
 use bolt_lang::*;
 
+pub mod victory;
+
+pub use victory::Standing;
+
 declare_id!("GAMe111111111111111111111111111111111111111");
 
+// Maps are at most 8x8 zones
+pub const MAX_MAP_SIZE: u8 = 8;
+
 #[component]
 pub struct Game {
-    pub game_id: u32,
+    pub game_id: Pubkey, // Address of this Game component, set by the first join
     pub player1: Pubkey,
     pub player2: Pubkey,
     pub current_turn: u8, // 1 or 2
     pub turn_number: u32,
     pub map_width: u8,
     pub map_height: u8,
-    pub total_zones: u32,
+    pub total_zones: u32, // Zone tiles of the map, zone control is a share of these
     pub game_state: GameState,
     pub winner: Pubkey,
     pub infection_level: u8, // 0-100, affects pathogen spawn rates
     pub immune_response_level: u8, // 0-100, affects immune cell effectiveness
     pub turn_time_limit: u64, // seconds
     pub last_turn_timestamp: i64,
+    pub next_unit_id: u32, // Shared by all zones, never reused
+    pub pending_unit_losses: [u16; 2], // Units lost since each player last acted, by player_id - 1
+    pub timeout_strikes: [u8; 2], // Consecutive turns each player let run out, by player_id - 1
+    pub pause_requested_by: Pubkey, // Default when no pause request is pending
+    pub draw_offered_by: Pubkey, // Default when no draw offer is pending
+    pub max_turns: u32, // Turn number at which the game ends in a draw, 0 for no limit
+    pub pathogen_seat: u8, // player_id of the pathogen player, 0 until they join
+    pub seed: u64, // Drawn when the game starts, zone interiors are generated from it
+    pub next_zone_id: u32, // Id of the next created zone, never reused
 }
 
 #[component_deserialize]
@@ -61,6 +77,8 @@ impl Game {
     pub fn switch_turn(&mut self) {
         self.current_turn = if self.current_turn == 1 { 2 } else { 1 };
         self.turn_number += 1;
+        // A pause request only stands for the turn it was made in
+        self.pause_requested_by = Pubkey::default();
     }
 
     pub fn get_current_player(&self) -> Pubkey {
@@ -71,6 +89,28 @@ impl Game {
         }
     }
 
+    pub fn is_turn_expired(&self, now: i64) -> bool {
+        // A limit of 0 disables the turn timer
+        self.turn_time_limit > 0
+            && now.saturating_sub(self.last_turn_timestamp) >= self.turn_time_limit as i64
+    }
+
+    pub fn add_timeout_strike(&mut self, player: &Pubkey) -> u8 {
+        match self.player_index(player) {
+            Some(index) => {
+                self.timeout_strikes[index] = self.timeout_strikes[index].saturating_add(1);
+                self.timeout_strikes[index]
+            }
+            None => 0,
+        }
+    }
+
+    pub fn clear_timeout_strikes(&mut self, player: &Pubkey) {
+        if let Some(index) = self.player_index(player) {
+            self.timeout_strikes[index] = 0;
+        }
+    }
+
     pub fn is_game_active(&self) -> bool {
         matches!(self.game_state, GameState::Active)
     }
@@ -85,6 +125,54 @@ impl Game {
         self.winner = winner_pubkey;
     }
 
+    pub fn allocate_unit_id(&mut self) -> Option<u32> {
+        let unit_id = self.next_unit_id;
+        self.next_unit_id = unit_id.checked_add(1)?;
+        Some(unit_id)
+    }
+
+    pub fn allocate_zone_id(&mut self) -> Option<u32> {
+        let zone_id = self.next_zone_id;
+        self.next_zone_id = zone_id.checked_add(1)?;
+        Some(zone_id)
+    }
+
+    pub fn record_unit_loss(&mut self, owner: &Pubkey) {
+        if let Some(index) = self.player_index(owner) {
+            self.pending_unit_losses[index] = self.pending_unit_losses[index].saturating_add(1);
+        }
+    }
+
+    pub fn unit_losses(&self, player: &Pubkey) -> u16 {
+        self.player_index(player).map_or(0, |index| self.pending_unit_losses[index])
+    }
+
+    pub fn take_unit_losses(&mut self, player: &Pubkey) -> u16 {
+        match self.player_index(player) {
+            Some(index) => std::mem::take(&mut self.pending_unit_losses[index]),
+            None => 0,
+        }
+    }
+
+    fn player_index(&self, player: &Pubkey) -> Option<usize> {
+        if &self.player1 == player {
+            Some(0)
+        } else if &self.player2 == player {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_on_map(&self, x: u8, y: u8) -> bool {
+        x < self.map_width.min(MAX_MAP_SIZE) && y < self.map_height.min(MAX_MAP_SIZE)
+    }
+
+    // Tiles on the edge of the map hold border zones
+    pub fn is_border_tile(&self, x: u8, y: u8) -> bool {
+        x == 0 || y == 0 || x + 1 >= self.map_width || y + 1 >= self.map_height
+    }
+
     pub fn update_infection_level(&mut self, delta: i8) {
         let new_level = (self.infection_level as i16) + (delta as i16);
         self.infection_level = new_level.clamp(0, 100) as u8;
@@ -99,7 +187,7 @@ impl Game {
 impl Default for Game {
     fn default() -> Self {
         Self::new(GameInit {
-            game_id: 0,
+            game_id: Pubkey::default(),
             player1: Pubkey::default(),
             player2: Pubkey::default(),
             current_turn: 1,
@@ -113,6 +201,42 @@ impl Default for Game {
             immune_response_level: 30, // Start with moderate immune response
             turn_time_limit: 300, // 5 minutes per turn
             last_turn_timestamp: 0,
+            next_unit_id: 1, // 0 is left for unbound unit components
+            pending_unit_losses: [0; 2],
+            timeout_strikes: [0; 2],
+            pause_requested_by: Pubkey::default(),
+            draw_offered_by: Pubkey::default(),
+            max_turns: 200, // 100 turns each
+            pathogen_seat: 0,
+            seed: 0,
+            next_zone_id: 2, // 0 and 1 are the starting zones
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_ids_start_at_one_and_are_never_reused() {
+        let mut game = Game::default();
+        assert_eq!(game.allocate_unit_id(), Some(1));
+        assert_eq!(game.allocate_unit_id(), Some(2));
+        assert_eq!(game.next_unit_id, 3);
+    }
+
+    #[test]
+    fn unit_ids_run_out_instead_of_wrapping() {
+        let mut game = Game { next_unit_id: u32::MAX, ..Game::default() };
+        assert_eq!(game.allocate_unit_id(), None);
+        assert_eq!(game.next_unit_id, u32::MAX);
+    }
+
+    #[test]
+    fn zone_ids_follow_the_starting_zones() {
+        let mut game = Game::default();
+        assert_eq!(game.allocate_zone_id(), Some(2));
+        assert_eq!(game.allocate_zone_id(), Some(3));
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{Game, GameWinner};
+use bolt_lang::*;
+
+// Share of all zones, in percent, a player must control to win
+pub const ZONE_CONTROL_PERCENT: u32 = 75;
+// Organ zones a player must hold at the same time to win
+pub const ORGAN_ZONES_TO_WIN: u16 = 3;
+// Infection or immune response level at which that side wins outright
+pub const MAX_LEVEL: u8 = 100;
+
+// A player's position on the board, as seen by the win-condition checks
+#[derive(Clone, Copy)]
+pub struct Standing {
+    pub player: Pubkey,
+    pub controlled_zones: u16,
+    pub organ_zones: u16,
+    pub live_units: u16,
+}
+
+impl Game {
+    /// Ends the game if any win condition is met, checked in this order:
+    /// - elimination: a player with no zones and no live units loses
+    /// - infection_level reaching 100 wins for the pathogen player
+    /// - immune_response_level reaching 100 wins for the immune player
+    /// - controlling 75% of all zones wins
+    /// - holding 3 Organ zones at once wins
+    /// - reaching max_turns ends in a draw
+    ///
+    /// Only the players whose standings are passed in are checked for
+    /// elimination, zone control and organ objectives.
+    pub fn check_victory(&mut self, standings: &[Standing]) -> bool {
+        if !self.is_game_active() {
+            return false;
+        }
+
+        let winner = self.evaluate_victory(standings);
+        if let Some((winner, winner_key)) = winner {
+            self.end_game(winner);
+            // Faction wins have no player slot, so name the player directly
+            if let Some(winner_key) = winner_key {
+                self.winner = winner_key;
+            }
+        }
+        winner.is_some()
+    }
+
+    fn evaluate_victory(&self, standings: &[Standing]) -> Option<(GameWinner, Option<Pubkey>)> {
+        // Standings of accounts that are not seated in this game are ignored
+        let seated = || {
+            standings
+                .iter()
+                .filter(|standing| self.player_index(&standing.player).is_some())
+        };
+
+        // No territory to spawn from and no units left
+        if let Some(winner) = seated()
+            .filter(|standing| standing.controlled_zones == 0 && standing.live_units == 0)
+            .find_map(|standing| self.get_opponent(&standing.player))
+            .and_then(|opponent| self.winner_for(&opponent))
+        {
+            return Some((winner, None));
+        }
+
+        // Whoever's standing is passed in, the seats tell the factions apart
+        if self.infection_level >= MAX_LEVEL {
+            return Some((GameWinner::Infection, self.faction_player(true)));
+        }
+        if self.immune_response_level >= MAX_LEVEL {
+            return Some((GameWinner::ImmuneSystem, self.faction_player(false)));
+        }
+
+        let zones_to_win = self.total_zones * ZONE_CONTROL_PERCENT / 100;
+        if let Some(winner) = seated()
+            .find(|standing| standing.controlled_zones as u32 >= zones_to_win)
+            .and_then(|standing| self.winner_for(&standing.player))
+        {
+            return Some((winner, None));
+        }
+
+        if let Some(winner) = seated()
+            .find(|standing| standing.organ_zones >= ORGAN_ZONES_TO_WIN)
+            .and_then(|standing| self.winner_for(&standing.player))
+        {
+            return Some((winner, None));
+        }
+
+        // A limit of 0 lets the game run until someone wins
+        if self.max_turns > 0 && self.turn_number >= self.max_turns {
+            return Some((GameWinner::Draw, None));
+        }
+
+        None
+    }
+
+    // The player seated as the pathogen, or as the immune system
+    fn faction_player(&self, pathogen: bool) -> Option<Pubkey> {
+        let seat = match self.pathogen_seat {
+            0 => return None,
+            seat if pathogen => seat,
+            seat => 3 - seat,
+        };
+        match seat {
+            1 => Some(self.player1),
+            _ => Some(self.player2),
+        }
+    }
+
+    fn winner_for(&self, player: &Pubkey) -> Option<GameWinner> {
+        if &self.player1 == player {
+            Some(GameWinner::Player1)
+        } else if &self.player2 == player {
+            Some(GameWinner::Player2)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameState;
+
+    // Player 2 plays the pathogen, both players hold a zone and a unit
+    fn midgame() -> (Game, [Standing; 2]) {
+        let game = Game {
+            player1: Pubkey::new_unique(),
+            player2: Pubkey::new_unique(),
+            game_state: GameState::Active,
+            pathogen_seat: 2,
+            ..Game::default()
+        };
+        let standings = [game.player1, game.player2].map(|player| Standing {
+            player,
+            controlled_zones: 1,
+            organ_zones: 0,
+            live_units: 1,
+        });
+        (game, standings)
+    }
+
+    #[test]
+    fn ongoing_games_go_on() {
+        let (mut game, standings) = midgame();
+        assert!(!game.check_victory(&standings));
+        assert!(game.is_game_active());
+    }
+
+    #[test]
+    fn either_standing_can_be_eliminated() {
+        for (loser, winner) in [(0, GameWinner::Player2), (1, GameWinner::Player1)] {
+            let (mut game, mut standings) = midgame();
+            standings[loser].controlled_zones = 0;
+            assert!(!game.check_victory(&standings));
+
+            standings[loser].live_units = 0;
+            assert!(game.check_victory(&standings));
+            assert!(game.game_state == GameState::Finished { winner });
+        }
+    }
+
+    #[test]
+    fn unseated_standings_are_ignored() {
+        let (mut game, mut standings) = midgame();
+        standings[0] = Standing { player: Pubkey::new_unique(), controlled_zones: 16, organ_zones: 3, live_units: 0 };
+
+        assert!(!game.check_victory(&standings));
+    }
+
+    #[test]
+    fn levels_win_for_the_seat_of_their_faction() {
+        let (mut game, standings) = midgame();
+        game.infection_level = MAX_LEVEL;
+        game.check_victory(&[]);
+        assert!(game.game_state == GameState::Finished { winner: GameWinner::Infection });
+        assert_eq!(game.winner, standings[1].player);
+
+        let (mut game, standings) = midgame();
+        game.immune_response_level = MAX_LEVEL;
+        game.check_victory(&[]);
+        assert!(game.game_state == GameState::Finished { winner: GameWinner::ImmuneSystem });
+        assert_eq!(game.winner, standings[0].player);
+    }
+
+    #[test]
+    fn territory_wins() {
+        let (mut game, mut standings) = midgame();
+        standings[1].controlled_zones = 11;
+        assert!(!game.check_victory(&standings));
+        standings[1].controlled_zones = 12;
+        assert!(game.check_victory(&standings));
+        assert!(game.game_state == GameState::Finished { winner: GameWinner::Player2 });
+
+        let (mut game, mut standings) = midgame();
+        standings[0].organ_zones = ORGAN_ZONES_TO_WIN;
+        assert!(game.check_victory(&standings));
+        assert!(game.game_state == GameState::Finished { winner: GameWinner::Player1 });
+    }
+
+    #[test]
+    fn the_turn_limit_ends_in_a_draw() {
+        let (mut game, standings) = midgame();
+        game.turn_number = game.max_turns;
+        assert!(game.check_victory(&standings));
+        assert!(game.game_state == GameState::Finished { winner: GameWinner::Draw });
+    }
+
+    #[test]
+    fn finished_games_stay_finished() {
+        let (mut game, mut standings) = midgame();
+        game.end_game(GameWinner::Draw);
+        standings[0].live_units = 0;
+        standings[0].controlled_zones = 0;
+
+        assert!(!game.check_victory(&standings));
+        assert!(game.game_state == GameState::Finished { winner: GameWinner::Draw });
+    }
+}
@@ -0,0 +1,89 @@
+// Compares (de)serializing a zone in the legacy layout, one enum per cell, with
+// the packed layout, for a zone as busy as a late game gets. Deserializing and
+// serializing every component is what a system pays for before and after its
+// own logic. Account sizes are printed first.
+
+use anchor_lang::{AnchorDeserialize, AnchorSerialize};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use grid::legacy::{LegacyCellContent, LegacyZone, LEGACY_ZONE_SIZE};
+use grid::{CellContent, ResourceType, Zone, GRID_SIZE};
+
+const UNITS: u8 = 32;
+const RESOURCE_NODES: u8 = 12;
+const OBSTACLES: u8 = 24;
+
+// Spreads units, resource nodes and obstacles over the grid
+fn busy_grid() -> [[Option<LegacyCellContent>; GRID_SIZE]; GRID_SIZE] {
+    let mut grid = [[None; GRID_SIZE]; GRID_SIZE];
+    let mut cells = (0..GRID_SIZE * GRID_SIZE).step_by(3).map(|index| (index / GRID_SIZE, index % GRID_SIZE));
+
+    for unit_id in 1..=UNITS as u32 {
+        let (x, y) = cells.next().unwrap();
+        grid[x][y] = Some(match unit_id % 2 {
+            0 => LegacyCellContent::ImmuneCell { unit_id, health: 80 },
+            _ => LegacyCellContent::Pathogen { unit_id, health: 45 },
+        });
+    }
+    for index in 0..RESOURCE_NODES {
+        let (x, y) = cells.next().unwrap();
+        let resource_type = ResourceType::from_index(index % 4).unwrap();
+        grid[x][y] = Some(LegacyCellContent::Resource { resource_type, amount: 120 });
+    }
+    for _ in 0..OBSTACLES {
+        let (x, y) = cells.next().unwrap();
+        grid[x][y] = Some(LegacyCellContent::Obstacle);
+    }
+
+    grid
+}
+
+fn legacy_zone(grid: [[Option<LegacyCellContent>; GRID_SIZE]; GRID_SIZE]) -> LegacyZone {
+    let zone = Zone::default();
+    LegacyZone {
+        zone_id: zone.zone_id,
+        zone_type: zone.zone_type,
+        x: zone.x,
+        y: zone.y,
+        owner: zone.owner,
+        grid,
+        energy: zone.energy,
+        antibodies: zone.antibodies,
+        stem_cells: zone.stem_cells,
+        nutrients: zone.nutrients,
+        unit_count: UNITS as u16,
+        is_border_zone: zone.is_border_zone,
+        is_controlled: zone.is_controlled,
+        connected_zones: zone.connected_zones,
+        bolt_metadata: zone.bolt_metadata,
+    }
+}
+
+fn zone_layout(c: &mut Criterion) {
+    let grid = busy_grid();
+    let legacy = legacy_zone(grid);
+    let mut packed = Zone::default();
+    packed.set_grid(&grid.map(|column| column.map(|content| content.map(CellContent::from)))).unwrap();
+
+    println!("legacy zone account: {LEGACY_ZONE_SIZE} bytes");
+    println!("packed zone account: {} bytes", 8 + <Zone as anchor_lang::Space>::INIT_SPACE);
+
+    let legacy_bytes = legacy.try_to_vec().unwrap();
+    let packed_bytes = packed.try_to_vec().unwrap();
+
+    let mut group = c.benchmark_group("zone_layout");
+    group.bench_function("legacy_deserialize", |b| {
+        b.iter(|| LegacyZone::deserialize(&mut black_box(&legacy_bytes[..])).unwrap())
+    });
+    group.bench_function("packed_deserialize", |b| {
+        b.iter(|| Zone::deserialize(&mut black_box(&packed_bytes[..])).unwrap())
+    });
+    group.bench_function("legacy_serialize", |b| b.iter(|| black_box(&legacy).try_to_vec().unwrap()));
+    group.bench_function("packed_serialize", |b| b.iter(|| black_box(&packed).try_to_vec().unwrap()));
+    group.bench_function("packed_cell_lookup", |b| {
+        b.iter(|| (0..GRID_SIZE as u8).filter(|&x| black_box(&packed).cell(x, x).is_some()).count())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, zone_layout);
+criterion_main!(benches);
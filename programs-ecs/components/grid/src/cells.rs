@@ -0,0 +1,344 @@
+use bolt_lang::*;
+
+use crate::{CellContent, GridError, Occupant, ResourceType, Zone};
+
+pub const GRID_SIZE: usize = 16;
+
+// Two cells per byte
+pub const CELL_BYTES: usize = GRID_SIZE * GRID_SIZE / 2;
+
+// Units and resource nodes a zone holds at once; obstacles take no entry.
+// Generated interiors take at most 6 entries for resource nodes, leaving 58
+// for units, while the list stays at 704 of the account's bytes (11 per entry).
+pub const MAX_OCCUPANTS: usize = 64;
+
+// Cell kinds, one nibble per cell of `Zone.cells`. Units and resource nodes
+// also have an entry in `Zone.occupants` holding their id, health or amount.
+const EMPTY: u8 = 0;
+const IMMUNE_CELL: u8 = 1; // Units of seat 0, placed before cells recorded their owner
+const PATHOGEN: u8 = 2;
+const OBSTACLE: u8 = 3;
+const RESOURCE: u8 = 4; // Plus the ResourceType index, so 4 to 7
+const SEATED_UNIT: u8 = 8; // Plus 2 for a pathogen and the owner's seat - 1, so 8 to 11
+
+fn cell_index(x: u8, y: u8) -> u8 {
+    x * GRID_SIZE as u8 + y
+}
+
+// Seats other than 1 and 2 are stored as seat 0
+fn unit_kind(pathogen: bool, seat: u8) -> u8 {
+    match seat {
+        1 | 2 => SEATED_UNIT + 2 * pathogen as u8 + seat - 1,
+        _ if pathogen => PATHOGEN,
+        _ => IMMUNE_CELL,
+    }
+}
+
+fn is_unit_kind(kind: u8) -> bool {
+    matches!(kind, IMMUNE_CELL | PATHOGEN) || kind >= SEATED_UNIT
+}
+
+/// The grid is stored packed: `cells` holds the kind of every cell and
+/// `occupants` the state of its units and resource nodes. These accessors are
+/// the only way systems read and write it. Coordinates must be below GRID_SIZE.
+impl Zone {
+    pub fn cell(&self, x: u8, y: u8) -> Option<CellContent> {
+        let index = cell_index(x, y);
+        let kind = self.kind(index);
+        let occupant = self
+            .occupants
+            .iter()
+            .find(|occupant| occupant.cell == index)
+            .copied()
+            .unwrap_or_default();
+
+        let (unit_id, health) = (occupant.id, occupant.amount);
+        match kind {
+            EMPTY => None,
+            IMMUNE_CELL => Some(CellContent::ImmuneCell { unit_id, health, seat: 0 }),
+            PATHOGEN => Some(CellContent::Pathogen { unit_id, health, seat: 0 }),
+            OBSTACLE => Some(CellContent::Obstacle),
+            RESOURCE..SEATED_UNIT => ResourceType::from_index(kind - RESOURCE)
+                .map(|resource_type| CellContent::Resource { resource_type, amount: occupant.amount }),
+            _ => {
+                let seat = (kind - SEATED_UNIT) % 2 + 1;
+                match (kind - SEATED_UNIT) / 2 {
+                    0 => Some(CellContent::ImmuneCell { unit_id, health, seat }),
+                    _ => Some(CellContent::Pathogen { unit_id, health, seat }),
+                }
+            }
+        }
+    }
+
+    pub fn is_empty_cell(&self, x: u8, y: u8) -> bool {
+        self.kind(cell_index(x, y)) == EMPTY
+    }
+
+    /// Fails with ZoneFull when a new unit or resource node would need more
+    /// than MAX_OCCUPANTS entries.
+    pub fn set_cell(&mut self, x: u8, y: u8, content: Option<CellContent>) -> Result<()> {
+        let index = cell_index(x, y);
+        let (kind, state) = match content {
+            None => (EMPTY, None),
+            Some(CellContent::ImmuneCell { unit_id, health, seat }) => (unit_kind(false, seat), Some((unit_id, health))),
+            Some(CellContent::Pathogen { unit_id, health, seat }) => (unit_kind(true, seat), Some((unit_id, health))),
+            Some(CellContent::Obstacle) => (OBSTACLE, None),
+            Some(CellContent::Resource { resource_type, amount }) => (RESOURCE + resource_type as u8, Some((0, amount))),
+        };
+
+        let slot = self.occupants.iter().position(|occupant| occupant.cell == index);
+        match (slot, state) {
+            (Some(slot), Some((id, amount))) => {
+                // A unit keeps hiding while only its health changes
+                let occupant = &mut self.occupants[slot];
+                if occupant.id != id {
+                    occupant.hidden_until_turn = 0;
+                }
+                (occupant.id, occupant.amount) = (id, amount);
+            }
+            (Some(slot), None) => {
+                self.occupants.swap_remove(slot);
+            }
+            (None, Some((id, amount))) => {
+                require!(self.occupants.len() < MAX_OCCUPANTS, GridError::ZoneFull);
+                self.occupants.push(Occupant { cell: index, id, amount, hidden_until_turn: 0 });
+            }
+            (None, None) => {}
+        }
+
+        self.set_kind(index, kind);
+        Ok(())
+    }
+
+    pub fn clear_cell(&mut self, x: u8, y: u8) {
+        let index = cell_index(x, y);
+        self.occupants.retain(|occupant| occupant.cell != index);
+        self.set_kind(index, EMPTY);
+    }
+
+    /// Does nothing unless a unit stands on (x, y).
+    pub fn set_unit_health(&mut self, x: u8, y: u8, health: u16) {
+        let index = cell_index(x, y);
+        if !is_unit_kind(self.kind(index)) {
+            return;
+        }
+        if let Some(occupant) = self.occupants.iter_mut().find(|occupant| occupant.cell == index) {
+            occupant.amount = health;
+        }
+    }
+
+    /// Hides the unit on (x, y) while Game.turn_number is below
+    /// `until_turn`, 0 to reveal it. Does nothing unless a unit stands there.
+    pub fn hide_unit(&mut self, x: u8, y: u8, until_turn: u32) {
+        let index = cell_index(x, y);
+        if !is_unit_kind(self.kind(index)) {
+            return;
+        }
+        if let Some(occupant) = self.occupants.iter_mut().find(|occupant| occupant.cell == index) {
+            occupant.hidden_until_turn = until_turn;
+        }
+    }
+
+    pub fn is_hidden(&self, x: u8, y: u8, turn_number: u32) -> bool {
+        let index = cell_index(x, y);
+        self.occupants
+            .iter()
+            .any(|occupant| occupant.cell == index && turn_number < occupant.hidden_until_turn)
+    }
+
+    /// Every non-empty cell as (x, y, content), in grid order: by x, then y.
+    pub fn occupied_cells(&self) -> impl Iterator<Item = (u8, u8, CellContent)> + '_ {
+        (0..GRID_SIZE as u8).flat_map(move |x| {
+            (0..GRID_SIZE as u8).filter_map(move |y| self.cell(x, y).map(|content| (x, y, content)))
+        })
+    }
+
+    /// The whole grid unpacked, indexed [x][y].
+    pub fn grid(&self) -> [[Option<CellContent>; GRID_SIZE]; GRID_SIZE] {
+        let mut grid = [[None; GRID_SIZE]; GRID_SIZE];
+        for (x, y, content) in self.occupied_cells() {
+            grid[x as usize][y as usize] = Some(content);
+        }
+        grid
+    }
+
+    /// Replaces the whole grid, indexed [x][y]. Fails with ZoneFull like
+    /// set_cell, leaving the grid partly written.
+    pub fn set_grid(&mut self, grid: &[[Option<CellContent>; GRID_SIZE]; GRID_SIZE]) -> Result<()> {
+        self.cells = [0; CELL_BYTES];
+        self.occupants.clear();
+        for (x, column) in grid.iter().enumerate() {
+            for (y, content) in column.iter().enumerate() {
+                if content.is_some() {
+                    self.set_cell(x as u8, y as u8, *content)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn kind(&self, index: u8) -> u8 {
+        let byte = self.cells[index as usize / 2];
+        match index % 2 {
+            0 => byte & 0x0F,
+            _ => byte >> 4,
+        }
+    }
+
+    fn set_kind(&mut self, index: u8, kind: u8) {
+        let byte = &mut self.cells[index as usize / 2];
+        *byte = match index % 2 {
+            0 => (*byte & 0xF0) | kind,
+            _ => (*byte & 0x0F) | (kind << 4),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ZoneType;
+
+    const CONTENTS: [CellContent; 4] = [
+        CellContent::ImmuneCell { unit_id: 7, health: 80, seat: 1 },
+        CellContent::Pathogen { unit_id: 9, health: 1, seat: 2 },
+        CellContent::Resource { resource_type: ResourceType::Nutrients, amount: 300 },
+        CellContent::Obstacle,
+    ];
+
+    #[test]
+    fn cells_read_back_what_was_written() {
+        let mut zone = Zone::default();
+        for (index, content) in CONTENTS.into_iter().enumerate() {
+            // Both nibbles of a byte, and the last cell
+            let (x, y) = [(0, 0), (0, 1), (7, 8), (15, 15)][index];
+            zone.set_cell(x, y, Some(content)).unwrap();
+        }
+
+        assert!(zone.cell(0, 0) == Some(CONTENTS[0]));
+        assert!(zone.cell(0, 1) == Some(CONTENTS[1]));
+        assert!(zone.cell(7, 8) == Some(CONTENTS[2]));
+        assert!(zone.cell(15, 15) == Some(CONTENTS[3]));
+        assert!(zone.is_empty_cell(1, 0));
+        assert_eq!(zone.occupants.len(), 3);
+        assert_eq!(zone.occupied_cells().count(), 4);
+    }
+
+    #[test]
+    fn unit_cells_keep_their_faction_and_seat() {
+        let mut zone = Zone::default();
+        let units = [
+            CellContent::ImmuneCell { unit_id: 1, health: 5, seat: 1 },
+            CellContent::ImmuneCell { unit_id: 2, health: 5, seat: 2 },
+            CellContent::Pathogen { unit_id: 3, health: 5, seat: 1 },
+            CellContent::Pathogen { unit_id: 4, health: 5, seat: 2 },
+            CellContent::Pathogen { unit_id: 5, health: 5, seat: 0 },
+        ];
+        for (y, content) in units.into_iter().enumerate() {
+            zone.set_cell(0, y as u8, Some(content)).unwrap();
+            zone.set_unit_health(0, y as u8, 4);
+        }
+
+        for (y, content) in units.into_iter().enumerate() {
+            let expected = match content {
+                CellContent::ImmuneCell { unit_id, seat, .. } => CellContent::ImmuneCell { unit_id, health: 4, seat },
+                CellContent::Pathogen { unit_id, seat, .. } => CellContent::Pathogen { unit_id, health: 4, seat },
+                other => other,
+            };
+            assert!(zone.cell(0, y as u8) == Some(expected));
+        }
+    }
+
+    #[test]
+    fn overwriting_and_clearing_free_the_occupant() {
+        let mut zone = Zone::default();
+        zone.set_cell(3, 4, Some(CONTENTS[0])).unwrap();
+        zone.set_cell(3, 4, Some(CellContent::Obstacle)).unwrap();
+        assert!(zone.occupants.is_empty());
+
+        zone.set_cell(3, 4, Some(CONTENTS[1])).unwrap();
+        zone.clear_cell(3, 4);
+        assert!(zone.occupants.is_empty());
+        assert!(zone.is_empty_cell(3, 4));
+    }
+
+    #[test]
+    fn unit_health_changes_in_place() {
+        let mut zone = Zone::default();
+        zone.set_cell(2, 2, Some(CONTENTS[0])).unwrap();
+        zone.set_cell(2, 3, Some(CONTENTS[2])).unwrap();
+
+        zone.set_unit_health(2, 2, 12);
+        zone.set_unit_health(2, 3, 12);
+
+        assert!(zone.cell(2, 2) == Some(CellContent::ImmuneCell { unit_id: 7, health: 12, seat: 1 }));
+        assert!(zone.cell(2, 3) == Some(CONTENTS[2]));
+    }
+
+    #[test]
+    fn units_hide_until_the_given_turn() {
+        let mut zone = Zone::default();
+        zone.set_cell(2, 2, Some(CONTENTS[0])).unwrap();
+        zone.set_cell(2, 3, Some(CONTENTS[2])).unwrap();
+
+        zone.hide_unit(2, 2, 5);
+        zone.hide_unit(2, 3, 5);
+        zone.set_unit_health(2, 2, 12);
+
+        assert!(zone.is_hidden(2, 2, 4));
+        assert!(!zone.is_hidden(2, 2, 5));
+        assert!(!zone.is_hidden(2, 3, 4));
+
+        // Another unit on the cell is not hidden
+        zone.set_cell(2, 2, Some(CONTENTS[1])).unwrap();
+        assert!(!zone.is_hidden(2, 2, 4));
+    }
+
+    #[test]
+    fn full_zones_reject_new_occupants_only() {
+        let mut zone = Zone::default();
+        for index in 0..MAX_OCCUPANTS as u8 {
+            let content = CellContent::Pathogen { unit_id: index as u32 + 1, health: 10, seat: 2 };
+            zone.set_cell(index / 16, index % 16, Some(content)).unwrap();
+        }
+
+        assert!(zone.set_cell(15, 0, Some(CONTENTS[0])).is_err());
+        assert!(zone.is_empty_cell(15, 0));
+        zone.set_cell(15, 0, Some(CellContent::Obstacle)).unwrap();
+        zone.set_cell(0, 0, Some(CONTENTS[0])).unwrap();
+    }
+
+    #[test]
+    fn generated_interiors_leave_58_entries_to_units() {
+        let zone_types = [
+            ZoneType::Circulatory,
+            ZoneType::Tissue,
+            ZoneType::Lymphatic,
+            ZoneType::Barrier,
+            ZoneType::Organ,
+        ];
+        for zone_type in zone_types {
+            for game_seed in 0..16 {
+                let mut zone = Zone { zone_type, x: game_seed as u8 % 4, ..Zone::default() };
+                zone.generate_interior(game_seed).unwrap();
+                assert!(MAX_OCCUPANTS - zone.occupants.len() >= 58);
+            }
+        }
+        assert_eq!(MAX_OCCUPANTS * Occupant::INIT_SPACE, 704);
+    }
+
+    #[test]
+    fn grids_unpack_to_what_was_packed() {
+        let mut grid = [[None; GRID_SIZE]; GRID_SIZE];
+        grid[1][14] = Some(CONTENTS[0]);
+        grid[9][0] = Some(CONTENTS[2]);
+        grid[15][15] = Some(CONTENTS[3]);
+
+        let mut zone = Zone::default();
+        zone.set_grid(&grid).unwrap();
+
+        assert!(zone.grid() == grid);
+        let order: Vec<_> = zone.occupied_cells().map(|(x, y, _)| (x, y)).collect();
+        assert_eq!(order, [(1, 14), (9, 0), (15, 15)]);
+    }
+}
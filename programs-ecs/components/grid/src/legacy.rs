@@ -0,0 +1,198 @@
+use anchor_lang::Discriminator;
+use bolt_lang::*;
+
+use crate::{CellContent, GridError, ResourceType, Zone, ZoneType};
+
+/// Zone as laid out before the grid was packed, with one enum per cell. Only
+/// used to read accounts created by earlier versions of the program.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct LegacyZone {
+    pub zone_id: u32,
+    pub zone_type: ZoneType,
+    pub x: u8,
+    pub y: u8,
+    pub owner: Pubkey,
+    pub grid: [[Option<LegacyCellContent>; 16]; 16],
+    pub energy: u32,
+    pub antibodies: u32,
+    pub stem_cells: u32,
+    pub nutrients: u32,
+    pub unit_count: u16,
+    pub is_border_zone: bool,
+    pub is_controlled: bool,
+    pub connected_zones: [Option<u32>; 4],
+    pub bolt_metadata: BoltMetadata,
+}
+
+/// CellContent as laid out in legacy zones, before unit cells recorded the
+/// owner's seat.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq)]
+pub enum LegacyCellContent {
+    ImmuneCell { unit_id: u32, health: u16 },
+    Pathogen { unit_id: u32, health: u16 },
+    Resource { resource_type: ResourceType, amount: u16 },
+    Obstacle,
+}
+
+// Legacy units convert to seat 0, their owner unknown
+impl From<LegacyCellContent> for CellContent {
+    fn from(content: LegacyCellContent) -> Self {
+        match content {
+            LegacyCellContent::ImmuneCell { unit_id, health } => CellContent::ImmuneCell { unit_id, health, seat: 0 },
+            LegacyCellContent::Pathogen { unit_id, health } => CellContent::Pathogen { unit_id, health, seat: 0 },
+            LegacyCellContent::Resource { resource_type, amount } => CellContent::Resource { resource_type, amount },
+            LegacyCellContent::Obstacle => CellContent::Obstacle,
+        }
+    }
+}
+
+// Account size, discriminator included, of a legacy zone
+pub const LEGACY_ZONE_SIZE: usize = 8 + LegacyZone::INIT_SPACE;
+
+impl TryFrom<LegacyZone> for Zone {
+    type Error = Error;
+
+    // Fails with ZoneFull when the zone holds more units and resource nodes
+    // than the packed grid has room for
+    fn try_from(legacy: LegacyZone) -> Result<Self> {
+        let mut zone = Zone {
+            zone_id: legacy.zone_id,
+            zone_type: legacy.zone_type,
+            x: legacy.x,
+            y: legacy.y,
+            owner: legacy.owner,
+            energy: legacy.energy,
+            antibodies: legacy.antibodies,
+            stem_cells: legacy.stem_cells,
+            nutrients: legacy.nutrients,
+            unit_count: legacy.unit_count,
+            is_border_zone: legacy.is_border_zone,
+            is_controlled: legacy.is_controlled,
+            connected_zones: legacy.connected_zones,
+            bolt_metadata: legacy.bolt_metadata,
+            ..Zone::default()
+        };
+        zone.set_grid(&legacy.grid.map(|column| column.map(|content| content.map(CellContent::from))))?;
+        Ok(zone)
+    }
+}
+
+impl Zone {
+    /// Reads a zone account in either layout, telling them apart by size.
+    /// Clients and indexers that may meet accounts from before the packed grid
+    /// use this instead of try_deserialize.
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        if data.len() != LEGACY_ZONE_SIZE {
+            return Zone::try_deserialize(&mut &data[..]);
+        }
+
+        // Both layouts share the discriminator
+        require!(data[..8] == Zone::DISCRIMINATOR, ErrorCode::AccountDiscriminatorMismatch);
+        let legacy = LegacyZone::deserialize(&mut &data[8..])?;
+        Zone::try_from(legacy)
+    }
+
+    /// The account data, Zone::size() bytes, that the migrate instruction
+    /// writes over a legacy zone account. The legacy layout has no game_id,
+    /// so the systems treat a migrated zone as belonging to no game.
+    pub fn migrate_account_data(data: &[u8]) -> Result<Vec<u8>> {
+        require!(data.len() == LEGACY_ZONE_SIZE, GridError::NotLegacy);
+        let zone = Zone::from_account_data(data)?;
+
+        let mut migrated = Vec::with_capacity(Zone::size());
+        zone.try_serialize(&mut migrated)?;
+        migrated.resize(Zone::size(), 0);
+        Ok(migrated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy_account(grid: [[Option<LegacyCellContent>; 16]; 16]) -> Vec<u8> {
+        let zone = Zone::default();
+        let legacy = LegacyZone {
+            zone_id: 5,
+            zone_type: ZoneType::Organ,
+            x: 2,
+            y: 3,
+            owner: Pubkey::new_unique(),
+            grid,
+            energy: zone.energy,
+            antibodies: zone.antibodies,
+            stem_cells: zone.stem_cells,
+            nutrients: zone.nutrients,
+            unit_count: 1,
+            is_border_zone: true,
+            is_controlled: true,
+            connected_zones: [Some(1), None, None, Some(4)],
+            bolt_metadata: zone.bolt_metadata,
+        };
+
+        let mut data = Zone::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        data.resize(LEGACY_ZONE_SIZE, 0);
+        data
+    }
+
+    #[test]
+    fn legacy_accounts_convert_to_the_packed_layout() {
+        let mut grid = [[None; 16]; 16];
+        grid[4][4] = Some(LegacyCellContent::ImmuneCell { unit_id: 3, health: 60 });
+        grid[0][9] = Some(LegacyCellContent::Obstacle);
+
+        let zone = Zone::from_account_data(&legacy_account(grid)).unwrap();
+
+        assert_eq!((zone.zone_id, zone.x, zone.y, zone.unit_count), (5, 2, 3, 1));
+        assert!(zone.zone_type == ZoneType::Organ);
+        assert_eq!(zone.connected_zones, [Some(1), None, None, Some(4)]);
+        assert!(zone.cell(4, 4) == Some(CellContent::ImmuneCell { unit_id: 3, health: 60, seat: 0 }));
+        assert!(zone.cell(0, 9) == Some(CellContent::Obstacle));
+        assert_eq!(zone.occupied_cells().count(), 2);
+    }
+
+    #[test]
+    fn packed_accounts_read_as_they_are() {
+        let mut zone = Zone::default();
+        zone.set_cell(6, 7, Some(CellContent::Obstacle)).unwrap();
+        let mut data = Vec::new();
+        zone.try_serialize(&mut data).unwrap();
+        data.resize(8 + Zone::INIT_SPACE, 0);
+
+        let read = Zone::from_account_data(&data).unwrap();
+
+        assert!(read.cell(6, 7) == Some(CellContent::Obstacle));
+    }
+
+    #[test]
+    fn migrated_accounts_hold_the_same_zone() {
+        let mut grid = [[None; 16]; 16];
+        grid[2][7] = Some(LegacyCellContent::Pathogen { unit_id: 8, health: 25 });
+        let data = legacy_account(grid);
+
+        let migrated = Zone::migrate_account_data(&data).unwrap();
+
+        assert_eq!(migrated.len(), Zone::size());
+        let zone = Zone::try_deserialize(&mut migrated.as_slice()).unwrap();
+        let legacy = Zone::from_account_data(&data).unwrap();
+        assert_eq!((zone.zone_id, zone.owner, zone.cells), (legacy.zone_id, legacy.owner, legacy.cells));
+        assert!(zone.occupants == legacy.occupants);
+        assert!(zone.cell(2, 7) == Some(CellContent::Pathogen { unit_id: 8, health: 25, seat: 0 }));
+    }
+
+    #[test]
+    fn packed_accounts_are_not_migrated() {
+        let mut data = Vec::new();
+        Zone::default().try_serialize(&mut data).unwrap();
+        data.resize(Zone::size(), 0);
+
+        assert_eq!(Zone::migrate_account_data(&data).unwrap_err(), GridError::NotLegacy.into());
+    }
+
+    #[test]
+    fn legacy_zones_too_busy_to_pack_are_rejected() {
+        let grid = [[Some(LegacyCellContent::Pathogen { unit_id: 1, health: 1 }); 16]; 16];
+        assert!(Zone::from_account_data(&legacy_account(grid)).is_err());
+    }
+}
@@ -1,15 +1,63 @@
+use bio_rules::generation::Feature;
 use bolt_lang::*;
 
+pub mod cells;
+pub mod legacy;
+
+pub use cells::{CELL_BYTES, GRID_SIZE, MAX_OCCUPANTS};
+
 declare_id!("9EoKMqQqrgRAxVED34q17e466RKme5sTUkuCqUGH4bij");
 
-#[component]
+// Most a zone's resource pools hold, in the order energy, antibodies,
+// stem_cells, nutrients
+pub const POOL_CAPS: [u32; 4] = [1000, 1000, 100, 1000];
+
+#[error_code]
+pub enum GridError {
+    #[msg("Zone has no room for another unit or resource node.")]
+    ZoneFull,
+    #[msg("Zone account is not in the legacy layout.")]
+    NotLegacy,
+}
+
+// What #[component] generates for Zone, written out so the program can carry
+// the migrate instruction as well
+#[bolt_program(Zone)]
+pub mod zone {
+    use super::*;
+
+    /// Rewrites a zone account from before the packed grid in the packed
+    /// layout, shrinking it to Zone::size(). Anyone may call it since the
+    /// zone's content is unchanged; the rent freed stays in the account.
+    pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
+        let account = ctx.accounts.zone.to_account_info();
+        let data = Zone::migrate_account_data(&account.try_borrow_data()?)?;
+        account.realloc(data.len(), false)?;
+        account.try_borrow_mut_data()?.copy_from_slice(&data);
+        Ok(())
+    }
+
+    #[derive(Accounts)]
+    pub struct Migrate<'info> {
+        /// CHECK: read through Zone::migrate_account_data, which only takes
+        /// zone accounts in the legacy layout
+        #[account(mut, owner = crate::ID)]
+        pub zone: UncheckedAccount<'info>,
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
 pub struct Zone {
     pub zone_id: u32,
+    pub game_id: Pubkey, // Game.game_id of the game the zone was placed in
     pub zone_type: ZoneType,
     pub x: u8,
     pub y: u8,
     pub owner: Pubkey,
-    pub grid: [[Option<CellContent>; 16]; 16],
+    pub cells: [u8; CELL_BYTES], // Packed grid, read and written through Zone::cell and Zone::set_cell
+    #[max_len(MAX_OCCUPANTS)]
+    pub occupants: Vec<Occupant>,
     pub energy: u32,
     pub antibodies: u32,
     pub stem_cells: u32,
@@ -18,6 +66,17 @@ pub struct Zone {
     pub is_border_zone: bool,
     pub is_controlled: bool,
     pub connected_zones: [Option<u32>; 4], // North, East, South, West
+    pub bolt_metadata: BoltMetadata,
+}
+
+impl ComponentTraits for Zone {
+    fn seed() -> &'static [u8] {
+        b""
+    }
+
+    fn size() -> usize {
+        8 + Zone::INIT_SPACE
+    }
 }
 
 #[component_deserialize]
@@ -33,12 +92,23 @@ pub enum ZoneType {
 #[component_deserialize]
 #[derive(PartialEq)]
 pub enum CellContent {
-    ImmuneCell { unit_id: u32, health: u16 },
-    Pathogen { unit_id: u32, health: u16 },
+    // `seat` is the owner's player_id, 0 for units placed before cells recorded it
+    ImmuneCell { unit_id: u32, health: u16, seat: u8 },
+    Pathogen { unit_id: u32, health: u16, seat: u8 },
     Resource { resource_type: ResourceType, amount: u16 },
     Obstacle,
 }
 
+// Unit or resource node on a cell of the packed grid
+#[component_deserialize]
+#[derive(PartialEq, Default)]
+pub struct Occupant {
+    pub cell: u8, // x * GRID_SIZE + y
+    pub id: u32, // unit_id, 0 for resource nodes
+    pub amount: u16, // Unit health or resource amount
+    pub hidden_until_turn: u32, // The unit's Unit.stealth_until_turn, so area effects can skip it
+}
+
 #[component_deserialize]
 #[derive(PartialEq)]
 pub enum ResourceType {
@@ -49,47 +119,135 @@ pub enum ResourceType {
 }
 
 impl ZoneType {
-    pub fn get_movement_cost(&self) -> u8 {
+    pub fn rules(&self) -> bio_rules::ZoneType {
         match self {
-            ZoneType::Circulatory => 1,
-            ZoneType::Tissue => 3,
-            ZoneType::Lymphatic => 2,
-            ZoneType::Barrier => 4,
-            ZoneType::Organ => 2,
+            ZoneType::Circulatory => bio_rules::ZoneType::Circulatory,
+            ZoneType::Tissue => bio_rules::ZoneType::Tissue,
+            ZoneType::Lymphatic => bio_rules::ZoneType::Lymphatic,
+            ZoneType::Barrier => bio_rules::ZoneType::Barrier,
+            ZoneType::Organ => bio_rules::ZoneType::Organ,
         }
     }
 
+    pub fn get_movement_cost(&self) -> u8 {
+        self.rules().get_movement_cost()
+    }
+
     pub fn get_resource_generation(&self) -> (u32, u32, u32, u32) {
         // Returns (energy, antibodies, stem_cells, nutrients) per turn
-        match self {
-            ZoneType::Circulatory => (10, 5, 2, 8),
-            ZoneType::Tissue => (5, 15, 1, 10),
-            ZoneType::Lymphatic => (8, 20, 5, 5),
-            ZoneType::Barrier => (3, 25, 1, 3),
-            ZoneType::Organ => (15, 10, 3, 15),
-        }
+        self.rules().get_resource_generation()
     }
 
     pub fn get_defense_bonus(&self) -> u16 {
-        match self {
-            ZoneType::Circulatory => 0,
-            ZoneType::Tissue => 2,
-            ZoneType::Lymphatic => 3,
-            ZoneType::Barrier => 5,
-            ZoneType::Organ => 1,
+        self.rules().get_defense_bonus()
+    }
+}
+
+impl Zone {
+    /// Extra seed of the entity holding the zone `zone_id` of game `game_id`,
+    /// like Unit::entity_seed. Zone ids are known before the zone is placed:
+    /// player_id - 1 for starting zones, then Game.next_zone_id.
+    pub fn entity_seed(game_id: &Pubkey, zone_id: u32) -> [u8; 32] {
+        solana_program::hash::hashv(&[b"zone", game_id.as_ref(), &zone_id.to_le_bytes()]).to_bytes()
+    }
+
+    // Side of this zone, as an index into connected_zones, that the map tile
+    // (x, y) lies on, if it is a neighbouring tile
+    pub fn side_towards(&self, x: u8, y: u8) -> Option<usize> {
+        match (x as i16 - self.x as i16, y as i16 - self.y as i16) {
+            (0, -1) => Some(0),
+            (1, 0) => Some(1),
+            (0, 1) => Some(2),
+            (-1, 0) => Some(3),
+            _ => None,
+        }
+    }
+
+    // Links two zones on neighbouring tiles, each on the side facing the
+    // other. Returns false, linking nothing, if they are not neighbours.
+    pub fn connect(&mut self, neighbour: &mut Zone) -> bool {
+        let Some(side) = self.side_towards(neighbour.x, neighbour.y) else {
+            return false;
+        };
+        self.connected_zones[side] = Some(neighbour.zone_id);
+        neighbour.connected_zones[(side + 2) % 4] = Some(self.zone_id);
+        true
+    }
+
+    /// The resource pools as [energy, antibodies, stem_cells, nutrients].
+    pub fn pools(&self) -> [u32; 4] {
+        [self.energy, self.antibodies, self.stem_cells, self.nutrients]
+    }
+
+    pub fn set_pools(&mut self, [energy, antibodies, stem_cells, nutrients]: [u32; 4]) {
+        (self.energy, self.antibodies, self.stem_cells, self.nutrients) = (energy, antibodies, stem_cells, nutrients);
+    }
+
+    /// Replaces the grid with the interior bio_rules::generation gives this
+    /// zone's type and map tile under `game_seed`.
+    pub fn generate_interior(&mut self, game_seed: u64) -> Result<()> {
+        self.cells = [0; CELL_BYTES];
+        self.occupants.clear();
+        let features = bio_rules::generation::generate_interior(game_seed, self.zone_type.rules(), self.x, self.y);
+        for (x, y, feature) in features {
+            let content = match feature {
+                Feature::Obstacle => CellContent::Obstacle,
+                Feature::Resource { resource_type, amount } => {
+                    CellContent::Resource { resource_type: resource_type.into(), amount }
+                }
+            };
+            self.set_cell(x, y, Some(content))?;
+        }
+        Ok(())
+    }
+}
+
+impl ResourceType {
+    pub fn from_index(index: u8) -> Option<Self> {
+        match index {
+            0 => Some(ResourceType::Energy),
+            1 => Some(ResourceType::Antibodies),
+            2 => Some(ResourceType::StemCells),
+            3 => Some(ResourceType::Nutrients),
+            _ => None,
+        }
+    }
+}
+
+impl From<bio_rules::ZoneType> for ZoneType {
+    fn from(zone_type: bio_rules::ZoneType) -> Self {
+        match zone_type {
+            bio_rules::ZoneType::Circulatory => ZoneType::Circulatory,
+            bio_rules::ZoneType::Tissue => ZoneType::Tissue,
+            bio_rules::ZoneType::Lymphatic => ZoneType::Lymphatic,
+            bio_rules::ZoneType::Barrier => ZoneType::Barrier,
+            bio_rules::ZoneType::Organ => ZoneType::Organ,
+        }
+    }
+}
+
+impl From<bio_rules::ResourceType> for ResourceType {
+    fn from(resource_type: bio_rules::ResourceType) -> Self {
+        match resource_type {
+            bio_rules::ResourceType::Energy => ResourceType::Energy,
+            bio_rules::ResourceType::Antibodies => ResourceType::Antibodies,
+            bio_rules::ResourceType::StemCells => ResourceType::StemCells,
+            bio_rules::ResourceType::Nutrients => ResourceType::Nutrients,
         }
     }
 }
 
 impl Default for Zone {
     fn default() -> Self {
-        Self::new(ZoneInit {
+        Self {
             zone_id: 0,
+            game_id: Pubkey::default(),
             zone_type: ZoneType::Tissue,
             x: 0,
             y: 0,
             owner: Pubkey::default(),
-            grid: [[None; 16]; 16],
+            cells: [0; CELL_BYTES],
+            occupants: Vec::new(),
             energy: 100,
             antibodies: 50,
             stem_cells: 10,
@@ -98,6 +256,70 @@ impl Default for Zone {
             is_border_zone: false,
             is_controlled: false,
             connected_zones: [None; 4],
-        })
+            bolt_metadata: BoltMetadata::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone_at(zone_id: u32, x: u8, y: u8) -> Zone {
+        Zone { zone_id, x, y, ..Zone::default() }
+    }
+
+    #[test]
+    fn neighbours_link_on_facing_sides() {
+        let mut centre = zone_at(20, 2, 2);
+        let mut north = zone_at(21, 2, 1);
+        let mut west = zone_at(22, 1, 2);
+
+        assert!(centre.connect(&mut north));
+        assert!(west.connect(&mut centre));
+
+        assert_eq!(centre.connected_zones, [Some(21), None, None, Some(22)]);
+        assert_eq!(north.connected_zones, [None, None, Some(20), None]);
+        assert_eq!(west.connected_zones, [None, Some(20), None, None]);
+    }
+
+    #[test]
+    fn entity_seeds_use_the_whole_game_address() {
+        let mut address = [7u8; 32];
+        let game = Pubkey::new_from_array(address);
+        address[31] = 8;
+        let other_game = Pubkey::new_from_array(address);
+
+        assert_ne!(Zone::entity_seed(&game, 2), Zone::entity_seed(&other_game, 2));
+        assert_ne!(Zone::entity_seed(&game, 2), Zone::entity_seed(&game, 3));
+    }
+
+    #[test]
+    fn only_neighbouring_tiles_link() {
+        let mut zone = zone_at(20, 2, 2);
+        for (x, y) in [(2, 2), (3, 3), (4, 2), (2, 0)] {
+            let mut other = zone_at(21, x, y);
+            assert!(!zone.connect(&mut other));
+        }
+        assert_eq!(zone.connected_zones, [None; 4]);
+    }
+
+    #[test]
+    fn generated_interiors_fill_the_packed_grid() {
+        let mut zone = Zone { zone_type: ZoneType::Organ, ..zone_at(20, 2, 1) };
+        zone.set_cell(5, 5, Some(CellContent::Obstacle)).unwrap();
+        zone.generate_interior(7).unwrap();
+
+        let features = bio_rules::generation::generate_interior(7, bio_rules::ZoneType::Organ, 2, 1);
+        assert_eq!(zone.occupied_cells().count(), features.len());
+        for (x, y, feature) in features {
+            match (feature, zone.cell(x, y)) {
+                (Feature::Obstacle, Some(CellContent::Obstacle)) => {}
+                (Feature::Resource { resource_type, amount }, Some(CellContent::Resource { resource_type: cell_type, amount: cell_amount })) => {
+                    assert!(cell_type == resource_type.into() && cell_amount == amount);
+                }
+                _ => panic!("cell ({x}, {y}) does not hold its feature"),
+            }
+        }
     }
 }
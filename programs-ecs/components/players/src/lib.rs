@@ -1,4 +1,6 @@
 use bolt_lang::*;
+use game::{Game, Standing};
+use grid::ZoneType;
 
 declare_id!("HLzXXTbMUjemRSQr5LHjtZgBvqyieuhY8wE29xYzhZSX");
 
@@ -6,6 +8,7 @@ declare_id!("HLzXXTbMUjemRSQr5LHjtZgBvqyieuhY8wE29xYzhZSX");
 pub struct Player {
     pub player_id: u8, // 1 or 2
     pub player_key: Pubkey,
+    pub game_id: Pubkey, // Game.game_id of the game the player joined
     pub energy_reserves: u64,
     pub antibody_reserves: u64,
     pub stem_cell_reserves: u64,
@@ -16,6 +19,8 @@ pub struct Player {
     pub faction: Faction,
     pub unlocked_units: [bool; 12], // Track which unit types are unlocked
     pub special_bonuses: [Option<SpecialBonus>; 3],
+    pub zone_income: [u32; 4], // Base income of all controlled zones: energy, antibodies, stem cells, nutrients
+    pub organ_zones: u16, // Controlled zones of type Organ
 }
 
 #[component_deserialize]
@@ -36,6 +41,15 @@ pub enum SpecialBonus {
     ZoneControl,
 }
 
+impl Faction {
+    pub fn rules(&self) -> bio_rules::Faction {
+        match self {
+            Faction::ImmuneSystem => bio_rules::Faction::ImmuneSystem,
+            Faction::Pathogen => bio_rules::Faction::Pathogen,
+        }
+    }
+}
+
 impl Player {
     pub fn can_afford(&self, energy: u64, antibodies: u64, stem_cells: u64, nutrients: u64) -> bool {
         self.energy_reserves >= energy &&
@@ -63,6 +77,82 @@ impl Player {
         self.nutrient_reserves = self.nutrient_reserves.saturating_add(nutrients);
     }
 
+    pub fn gain_zone(&mut self, zone_type: &ZoneType) {
+        let income = zone_type.get_resource_generation();
+        self.controlled_zones += 1;
+        if *zone_type == ZoneType::Organ {
+            self.organ_zones += 1;
+        }
+        self.zone_income[0] = self.zone_income[0].saturating_add(income.0);
+        self.zone_income[1] = self.zone_income[1].saturating_add(income.1);
+        self.zone_income[2] = self.zone_income[2].saturating_add(income.2);
+        self.zone_income[3] = self.zone_income[3].saturating_add(income.3);
+    }
+
+    pub fn lose_zone(&mut self, zone_type: &ZoneType) {
+        let income = zone_type.get_resource_generation();
+        self.controlled_zones = self.controlled_zones.saturating_sub(1);
+        if *zone_type == ZoneType::Organ {
+            self.organ_zones = self.organ_zones.saturating_sub(1);
+        }
+        self.zone_income[0] = self.zone_income[0].saturating_sub(income.0);
+        self.zone_income[1] = self.zone_income[1].saturating_sub(income.1);
+        self.zone_income[2] = self.zone_income[2].saturating_sub(income.2);
+        self.zone_income[3] = self.zone_income[3].saturating_sub(income.3);
+    }
+
+    pub fn standing(&self, game: &Game) -> Standing {
+        Standing {
+            player: self.player_key,
+            controlled_zones: self.controlled_zones,
+            organ_zones: self.organ_zones,
+            // Kills not yet settled into total_units still count as losses
+            live_units: self.total_units.saturating_sub(game.unit_losses(&self.player_key)),
+        }
+    }
+
+    pub fn has_bonus(&self, bonus: SpecialBonus) -> bool {
+        self.special_bonuses.contains(&Some(bonus))
+    }
+
+    pub fn get_turn_income(&self, pooled: Option<&ZoneType>) -> (u64, u64, u64, u64) {
+        // Returns (energy, antibodies, stem_cells, nutrients) earned into the reserves at the end
+        // of a turn. A `pooled` zone's generation goes to its own pools instead, so is left out.
+        let (pooled_energy, pooled_antibodies, pooled_stem_cells, pooled_nutrients) =
+            pooled.map_or((0, 0, 0, 0), |zone_type| zone_type.get_resource_generation());
+        let pooled = [pooled_energy, pooled_antibodies, pooled_stem_cells, pooled_nutrients];
+        let income: [u64; 4] = std::array::from_fn(|index| self.zone_income[index].saturating_sub(pooled[index]) as u64);
+        self.with_income_bonuses(income.into())
+    }
+
+    pub fn get_pooled_income(&self, zone_type: &ZoneType) -> (u32, u32, u32, u32) {
+        // Returns what a zone generates into its own pools at the end of a turn, with the same
+        // bonuses as the reserves. Pools are capped, so u32 always holds the result.
+        let (energy, antibodies, stem_cells, nutrients) = zone_type.get_resource_generation();
+        let income = self.with_income_bonuses((energy as u64, antibodies as u64, stem_cells as u64, nutrients as u64));
+        let clamp = |amount: u64| amount.min(u32::MAX as u64) as u32;
+        (clamp(income.0), clamp(income.1), clamp(income.2), clamp(income.3))
+    }
+
+    fn with_income_bonuses(&self, income: (u64, u64, u64, u64)) -> (u64, u64, u64, u64) {
+        let (mut energy, mut antibodies, mut stem_cells, mut nutrients) = income;
+
+        // +25% on everything
+        if self.has_bonus(SpecialBonus::IncreasedProduction) {
+            energy = energy * 125 / 100;
+            antibodies = antibodies * 125 / 100;
+            stem_cells = stem_cells * 125 / 100;
+            nutrients = nutrients * 125 / 100;
+        }
+
+        // +50% on stem cells, the scarcest resource
+        if self.has_bonus(SpecialBonus::ResourceEfficiency) {
+            stem_cells = stem_cells * 150 / 100;
+        }
+
+        (energy, antibodies, stem_cells, nutrients)
+    }
+
     pub fn get_faction_bonus(&self) -> (u16, u16, u16) {
         // Returns (attack_bonus, defense_bonus, movement_bonus)
         match self.faction {
@@ -96,6 +186,7 @@ impl Default for Player {
         Self::new(PlayerInit {
             player_id: 1,
             player_key: Pubkey::default(),
+            game_id: Pubkey::default(),
             energy_reserves: 1000,
             antibody_reserves: 500,
             stem_cell_reserves: 100,
@@ -106,6 +197,53 @@ impl Default for Player {
             faction: Faction::ImmuneSystem,
             unlocked_units,
             special_bonuses: [None; 3],
+            zone_income: [0; 4],
+            organ_zones: 0,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player_with(bonuses: [Option<SpecialBonus>; 3]) -> Player {
+        let mut player = Player { special_bonuses: bonuses, ..Player::default() };
+        player.gain_zone(&ZoneType::Organ);
+        player.gain_zone(&ZoneType::Lymphatic);
+        player
+    }
+
+    #[test]
+    fn pooled_zones_are_left_out_of_the_reserves() {
+        let player = player_with([None; 3]);
+        let (energy, antibodies, stem_cells, nutrients) = ZoneType::Lymphatic.get_resource_generation();
+        let organ = ZoneType::Organ.get_resource_generation();
+
+        assert_eq!(
+            player.get_turn_income(Some(&ZoneType::Lymphatic)),
+            (organ.0 as u64, organ.1 as u64, organ.2 as u64, organ.3 as u64)
+        );
+        assert_eq!(player.get_pooled_income(&ZoneType::Lymphatic), (energy, antibodies, stem_cells, nutrients));
+    }
+
+    #[test]
+    fn bonuses_apply_to_reserves_and_pools_alike() {
+        let bonuses = [Some(SpecialBonus::IncreasedProduction), Some(SpecialBonus::ResourceEfficiency), None];
+        let player = player_with(bonuses);
+
+        for zone_type in [ZoneType::Organ, ZoneType::Lymphatic] {
+            let (energy, antibodies, stem_cells, nutrients) = zone_type.get_resource_generation();
+            let boosted = (energy * 125 / 100, antibodies * 125 / 100, stem_cells * 125 / 100 * 150 / 100, nutrients * 125 / 100);
+            assert_eq!(player.get_pooled_income(&zone_type), boosted);
+
+            // Only the other zone feeds the reserves, with the same bonuses
+            let other = if zone_type == ZoneType::Organ { ZoneType::Lymphatic } else { ZoneType::Organ };
+            let boosted = player.get_pooled_income(&other);
+            assert_eq!(
+                player.get_turn_income(Some(&zone_type)),
+                (boosted.0 as u64, boosted.1 as u64, boosted.2 as u64, boosted.3 as u64)
+            );
+        }
+    }
+}
@@ -5,6 +5,7 @@ declare_id!("UNiT111111111111111111111111111111111111111");
 #[component]
 pub struct Unit {
     pub unit_id: u32,
+    pub game_id: Pubkey, // Game.game_id of the game the unit was spawned in
     pub unit_type: UnitType,
     pub zone_id: u32,
     pub x: u8,
@@ -18,6 +19,12 @@ pub struct Unit {
     pub special_abilities: [Option<SpecialAbility>; 3],
     pub is_active: bool,
     pub energy_cost: u16,
+    pub stealth_until_turn: u32, // Untargetable while Game.turn_number is below this
+    pub ability_ready_turn: [u32; 3], // Game.turn_number from which each ability slot is usable again
+    pub action_turn: u32, // Game.turn_number the action budget below belongs to
+    pub action_points: u8,
+    pub has_moved: bool,
+    pub has_attacked: bool,
 }
 
 #[component_deserialize]
@@ -61,52 +68,39 @@ pub enum SpecialAbility {
 }
 
 impl UnitType {
-    pub fn get_base_stats(&self) -> (u16, u16, u16, u8, u16) {
-        // Returns (health, attack, defense, movement_range, energy_cost)
+    pub fn rules(&self) -> bio_rules::UnitType {
         match self {
-            // Immune Cells
-            UnitType::TCell => (80, 15, 10, 3, 20),
-            UnitType::BCell => (60, 8, 8, 2, 25),
-            UnitType::Macrophage => (120, 20, 15, 2, 30),
-            UnitType::NeutrophilCell => (70, 18, 8, 4, 15),
-            UnitType::DendriticCell => (50, 5, 12, 3, 35),
-            UnitType::NaturalKillerCell => (90, 25, 10, 3, 40),
-            
-            // Pathogens
-            UnitType::Virus => (40, 12, 5, 4, 10),
-            UnitType::Bacteria => (60, 15, 8, 2, 15),
-            UnitType::Fungus => (80, 10, 12, 1, 20),
-            UnitType::Parasite => (70, 18, 6, 3, 25),
-            UnitType::CancerCell => (100, 20, 10, 2, 30),
-            UnitType::Toxin => (30, 30, 2, 5, 5),
+            UnitType::TCell => bio_rules::UnitType::TCell,
+            UnitType::BCell => bio_rules::UnitType::BCell,
+            UnitType::Macrophage => bio_rules::UnitType::Macrophage,
+            UnitType::NeutrophilCell => bio_rules::UnitType::NeutrophilCell,
+            UnitType::DendriticCell => bio_rules::UnitType::DendriticCell,
+            UnitType::NaturalKillerCell => bio_rules::UnitType::NaturalKillerCell,
+            UnitType::Virus => bio_rules::UnitType::Virus,
+            UnitType::Bacteria => bio_rules::UnitType::Bacteria,
+            UnitType::Fungus => bio_rules::UnitType::Fungus,
+            UnitType::Parasite => bio_rules::UnitType::Parasite,
+            UnitType::CancerCell => bio_rules::UnitType::CancerCell,
+            UnitType::Toxin => bio_rules::UnitType::Toxin,
         }
     }
 
+    pub fn get_base_stats(&self) -> (u16, u16, u16, u8, u16) {
+        // Returns (health, attack, defense, movement_range, energy_cost)
+        self.rules().get_base_stats()
+    }
+
+    pub fn get_attack_range(&self) -> u8 {
+        // Manhattan distance in cells
+        self.rules().get_attack_range()
+    }
+
     pub fn get_default_abilities(&self) -> [Option<SpecialAbility>; 3] {
-        match self {
-            // Immune Cells
-            UnitType::TCell => [Some(SpecialAbility::CytokineRelease), Some(SpecialAbility::MemoryResponse), None],
-            UnitType::BCell => [Some(SpecialAbility::AntibodyProduction), Some(SpecialAbility::MemoryResponse), None],
-            UnitType::Macrophage => [Some(SpecialAbility::Phagocytosis), Some(SpecialAbility::CytokineRelease), None],
-            UnitType::NeutrophilCell => [Some(SpecialAbility::Phagocytosis), Some(SpecialAbility::Infiltration), None],
-            UnitType::DendriticCell => [Some(SpecialAbility::CytokineRelease), Some(SpecialAbility::Infiltration), None],
-            UnitType::NaturalKillerCell => [Some(SpecialAbility::CytokineRelease), Some(SpecialAbility::ZoneHealing), None],
-            
-            // Pathogens
-            UnitType::Virus => [Some(SpecialAbility::Replication), Some(SpecialAbility::ImmuneEvasion), None],
-            UnitType::Bacteria => [Some(SpecialAbility::Replication), Some(SpecialAbility::ToxinRelease), None],
-            UnitType::Fungus => [Some(SpecialAbility::Replication), Some(SpecialAbility::ResourceDrain), None],
-            UnitType::Parasite => [Some(SpecialAbility::ImmuneEvasion), Some(SpecialAbility::ResourceDrain), None],
-            UnitType::CancerCell => [Some(SpecialAbility::Replication), Some(SpecialAbility::Metastasis), None],
-            UnitType::Toxin => [Some(SpecialAbility::ToxinRelease), Some(SpecialAbility::ResourceDrain), None],
-        }
+        self.rules().get_default_abilities().map(|ability| ability.map(SpecialAbility::from))
     }
 
     pub fn is_immune_cell(&self) -> bool {
-        matches!(self, 
-            UnitType::TCell | UnitType::BCell | UnitType::Macrophage | 
-            UnitType::NeutrophilCell | UnitType::DendriticCell | UnitType::NaturalKillerCell
-        )
+        self.rules().is_immune_cell()
     }
 
     pub fn is_pathogen(&self) -> bool {
@@ -114,6 +108,110 @@ impl UnitType {
     }
 }
 
+impl From<bio_rules::UnitType> for UnitType {
+    fn from(unit_type: bio_rules::UnitType) -> Self {
+        match unit_type {
+            bio_rules::UnitType::TCell => UnitType::TCell,
+            bio_rules::UnitType::BCell => UnitType::BCell,
+            bio_rules::UnitType::Macrophage => UnitType::Macrophage,
+            bio_rules::UnitType::NeutrophilCell => UnitType::NeutrophilCell,
+            bio_rules::UnitType::DendriticCell => UnitType::DendriticCell,
+            bio_rules::UnitType::NaturalKillerCell => UnitType::NaturalKillerCell,
+            bio_rules::UnitType::Virus => UnitType::Virus,
+            bio_rules::UnitType::Bacteria => UnitType::Bacteria,
+            bio_rules::UnitType::Fungus => UnitType::Fungus,
+            bio_rules::UnitType::Parasite => UnitType::Parasite,
+            bio_rules::UnitType::CancerCell => UnitType::CancerCell,
+            bio_rules::UnitType::Toxin => UnitType::Toxin,
+        }
+    }
+}
+
+impl SpecialAbility {
+    pub fn rules(&self) -> bio_rules::SpecialAbility {
+        match self {
+            SpecialAbility::AntibodyProduction => bio_rules::SpecialAbility::AntibodyProduction,
+            SpecialAbility::Phagocytosis => bio_rules::SpecialAbility::Phagocytosis,
+            SpecialAbility::CytokineRelease => bio_rules::SpecialAbility::CytokineRelease,
+            SpecialAbility::MemoryResponse => bio_rules::SpecialAbility::MemoryResponse,
+            SpecialAbility::Infiltration => bio_rules::SpecialAbility::Infiltration,
+            SpecialAbility::ZoneHealing => bio_rules::SpecialAbility::ZoneHealing,
+            SpecialAbility::Replication => bio_rules::SpecialAbility::Replication,
+            SpecialAbility::Mutation => bio_rules::SpecialAbility::Mutation,
+            SpecialAbility::ToxinRelease => bio_rules::SpecialAbility::ToxinRelease,
+            SpecialAbility::ImmuneEvasion => bio_rules::SpecialAbility::ImmuneEvasion,
+            SpecialAbility::Metastasis => bio_rules::SpecialAbility::Metastasis,
+            SpecialAbility::ResourceDrain => bio_rules::SpecialAbility::ResourceDrain,
+        }
+    }
+
+    pub fn get_cost(&self) -> (u64, u64, u64, u64) {
+        // Returns (energy, antibodies, stem_cells, nutrients)
+        self.rules().get_cost()
+    }
+
+    pub fn get_cooldown(&self) -> u8 {
+        // Own turns before the ability can be used again
+        self.rules().get_cooldown()
+    }
+}
+
+impl From<bio_rules::SpecialAbility> for SpecialAbility {
+    fn from(ability: bio_rules::SpecialAbility) -> Self {
+        match ability {
+            bio_rules::SpecialAbility::AntibodyProduction => SpecialAbility::AntibodyProduction,
+            bio_rules::SpecialAbility::Phagocytosis => SpecialAbility::Phagocytosis,
+            bio_rules::SpecialAbility::CytokineRelease => SpecialAbility::CytokineRelease,
+            bio_rules::SpecialAbility::MemoryResponse => SpecialAbility::MemoryResponse,
+            bio_rules::SpecialAbility::Infiltration => SpecialAbility::Infiltration,
+            bio_rules::SpecialAbility::ZoneHealing => SpecialAbility::ZoneHealing,
+            bio_rules::SpecialAbility::Replication => SpecialAbility::Replication,
+            bio_rules::SpecialAbility::Mutation => SpecialAbility::Mutation,
+            bio_rules::SpecialAbility::ToxinRelease => SpecialAbility::ToxinRelease,
+            bio_rules::SpecialAbility::ImmuneEvasion => SpecialAbility::ImmuneEvasion,
+            bio_rules::SpecialAbility::Metastasis => SpecialAbility::Metastasis,
+            bio_rules::SpecialAbility::ResourceDrain => SpecialAbility::ResourceDrain,
+        }
+    }
+}
+
+pub const ACTION_POINTS_PER_TURN: u8 = 2;
+
+impl Unit {
+    // Sets up the per-turn budget for `turn_number`. EndTurn does this for
+    // the unit components passed with it, every other unit is reset lazily
+    // the first time it acts after the turn number changed
+    pub fn refresh_actions(&mut self, turn_number: u32) {
+        if self.action_turn != turn_number {
+            self.action_turn = turn_number;
+            self.action_points = ACTION_POINTS_PER_TURN;
+            self.has_moved = false;
+            self.has_attacked = false;
+        }
+    }
+
+    pub fn is_ability_ready(&self, slot: usize, turn_number: u32) -> bool {
+        turn_number >= self.ability_ready_turn[slot]
+    }
+
+    pub fn start_cooldown(&mut self, slot: usize, turn_number: u32, cooldown: u8) {
+        // Turns alternate between players, so one own turn is two turn numbers
+        self.ability_ready_turn[slot] = turn_number + 2 * cooldown as u32;
+    }
+
+    pub fn is_stealthed(&self, turn_number: u32) -> bool {
+        turn_number < self.stealth_until_turn
+    }
+
+    /// Extra seed of the entity holding the unit `unit_id` of game `game_id`.
+    /// Unit entities are created with this seed so any client can derive the
+    /// entity (and from it the Unit component) from the unit id alone. The
+    /// whole game address is hashed in, as seeds are limited to 32 bytes.
+    pub fn entity_seed(game_id: &Pubkey, unit_id: u32) -> [u8; 32] {
+        solana_program::hash::hashv(&[b"unit", game_id.as_ref(), &unit_id.to_le_bytes()]).to_bytes()
+    }
+}
+
 impl Default for Unit {
     fn default() -> Self {
         let unit_type = UnitType::TCell;
@@ -122,6 +220,7 @@ impl Default for Unit {
         
         Self::new(UnitInit {
             unit_id: 0,
+            game_id: Pubkey::default(),
             unit_type,
             zone_id: 0,
             x: 0,
@@ -135,6 +234,25 @@ impl Default for Unit {
             special_abilities,
             is_active: true,
             energy_cost,
+            stealth_until_turn: 0,
+            ability_ready_turn: [0; 3],
+            action_turn: 0,
+            action_points: ACTION_POINTS_PER_TURN,
+            has_moved: false,
+            has_attacked: false,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loadouts_come_from_the_rules() {
+        for unit_type in bio_rules::UnitType::ALL {
+            let abilities = UnitType::from(unit_type).get_default_abilities();
+            assert_eq!(abilities.map(|ability| ability.map(|ability| ability.rules())), unit_type.get_default_abilities());
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use bolt_lang::*;
+use game::MAX_MAP_SIZE;
+
+declare_id!("iNDX111111111111111111111111111111111111111");
+
+// One zone per tile of the largest map
+pub const MAX_ZONES: usize = MAX_MAP_SIZE as usize * MAX_MAP_SIZE as usize;
+
+#[error_code]
+pub enum ZoneIndexError {
+    #[msg("A zone already occupies this position.")]
+    PositionTaken,
+}
+
+// Every zone on the map of a game, kept on the game's entity. The entity of
+// a zone follows from its id, see grid::Zone::entity_seed.
+#[component]
+pub struct ZoneIndex {
+    pub game_id: Pubkey, // Game.game_id of the game whose map this is, set by the first join
+    #[max_len(MAX_ZONES)]
+    pub zones: Vec<IndexedZone>, // In the order the zones were placed
+}
+
+#[component_deserialize]
+#[derive(PartialEq)]
+pub struct IndexedZone {
+    pub x: u8,
+    pub y: u8,
+    pub zone_id: u32,
+}
+
+impl ZoneIndex {
+    pub fn zone_at(&self, x: u8, y: u8) -> Option<u32> {
+        self.zones
+            .iter()
+            .find(|zone| (zone.x, zone.y) == (x, y))
+            .map(|zone| zone.zone_id)
+    }
+
+    pub fn contains(&self, zone_id: u32, x: u8, y: u8) -> bool {
+        self.zone_at(x, y) == Some(zone_id)
+    }
+
+    /// Fails with PositionTaken if a zone is already on (x, y). Callers keep
+    /// (x, y) on the map, so the index never holds more than MAX_ZONES.
+    pub fn insert(&mut self, zone_id: u32, x: u8, y: u8) -> Result<()> {
+        require!(self.zone_at(x, y).is_none(), ZoneIndexError::PositionTaken);
+        self.zones.push(IndexedZone { x, y, zone_id });
+        Ok(())
+    }
+
+    /// Zones on the tiles next to (x, y), in the order of
+    /// Zone.connected_zones: North, East, South, West.
+    pub fn neighbours(&self, x: u8, y: u8) -> [Option<u32>; 4] {
+        let tile = |x: Option<u8>, y: Option<u8>| self.zone_at(x?, y?);
+        [
+            tile(Some(x), y.checked_sub(1)),
+            tile(x.checked_add(1), Some(y)),
+            tile(Some(x), y.checked_add(1)),
+            tile(x.checked_sub(1), Some(y)),
+        ]
+    }
+
+    /// Percentage of the game's zones that `zones` of them make up, rounded
+    /// down, e.g. for a player's Player.controlled_zones.
+    pub fn share(&self, zones: u16) -> u8 {
+        match self.zones.len() {
+            0 => 0,
+            total => (zones as usize * 100 / total).min(100) as u8,
+        }
+    }
+}
+
+impl Default for ZoneIndex {
+    fn default() -> Self {
+        Self::new(ZoneIndexInit { game_id: Pubkey::default(), zones: Vec::new() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_tile_holds_one_zone() {
+        let mut index = ZoneIndex::default();
+        index.insert(0, 0, 0).unwrap();
+        index.insert(16, 1, 0).unwrap();
+
+        assert!(index.insert(17, 1, 0).is_err());
+        assert_eq!(index.zone_at(1, 0), Some(16));
+        assert_eq!(index.zone_at(0, 1), None);
+        assert!(index.contains(0, 0, 0));
+        assert!(!index.contains(16, 0, 0));
+    }
+
+    #[test]
+    fn neighbours_follow_the_connected_zones_order() {
+        let mut index = ZoneIndex::default();
+        for (zone_id, x, y) in [(0, 0, 0), (16, 1, 0), (17, 0, 1), (18, 1, 1), (19, 2, 1)] {
+            index.insert(zone_id, x, y).unwrap();
+        }
+
+        assert_eq!(index.neighbours(1, 1), [Some(16), Some(19), None, Some(17)]);
+        assert_eq!(index.neighbours(0, 0), [None, Some(16), Some(17), None]);
+    }
+
+    #[test]
+    fn shares_are_percentages_of_all_zones() {
+        let mut index = ZoneIndex::default();
+        assert_eq!(index.share(0), 0);
+        for zone_id in 0..3 {
+            index.insert(zone_id, zone_id as u8, 0).unwrap();
+        }
+
+        assert_eq!(index.share(1), 33);
+        assert_eq!(index.share(3), 100);
+    }
+}
@@ -0,0 +1,177 @@
+use bolt_lang::*;
+use game::{Game, GameWinner};
+use players::Player;
+
+declare_id!("TMoUT11111111111111111111111111111111111111");
+
+// A player who lets this many turns in a row run out forfeits the game
+pub const MAX_TIMEOUT_STRIKES: u8 = 3;
+
+#[error_code]
+pub enum ClaimTimeoutError {
+    #[msg("Game is not active.")]
+    NotActive,
+    #[msg("Turn time limit not exceeded.")]
+    TurnNotExpired,
+    #[msg("Player does not hold this seat of the game.")]
+    WrongPlayer,
+}
+
+#[system]
+pub mod claim_timeout {
+
+    pub fn execute(ctx: Context<Components>, _args_p: Vec<u8>) -> Result<Components> {
+        let now = Clock::get()?.unix_timestamp;
+
+        // Permissionless: anyone may end a turn that ran over the limit
+        force_end_turn(
+            &mut ctx.accounts.game,
+            [&ctx.accounts.player1, &ctx.accounts.player2],
+            now,
+        )?;
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub game: Game,
+        pub player1: Player, // Seated as Game.player1
+        pub player2: Player, // Seated as Game.player2
+    }
+}
+
+// `players` are the Player components of the first and second seat
+pub fn force_end_turn(game: &mut Game, players: [&Player; 2], now: i64) -> Result<()> {
+    require!(game.is_game_active(), ClaimTimeoutError::NotActive);
+    require!(game.is_turn_expired(now), ClaimTimeoutError::TurnNotExpired);
+    for (player, seat) in players.iter().zip([game.player1, game.player2]) {
+        require!(
+            player.player_key == seat && player.game_id == game.game_id,
+            ClaimTimeoutError::WrongPlayer
+        );
+    }
+
+    let idle_player = game.get_current_player();
+    let strikes = game.add_timeout_strike(&idle_player);
+
+    if strikes >= MAX_TIMEOUT_STRIKES {
+        // Forfeit: the waiting player wins
+        let winner = match game.current_turn {
+            1 => GameWinner::Player2,
+            _ => GameWinner::Player1,
+        };
+        game.end_game(winner);
+    } else {
+        // Force-end the idle player's turn
+        game.switch_turn();
+        game.last_turn_timestamp = now;
+
+        // Both players are at hand, so an elimination the last action left
+        // unchecked ends the game here as well
+        let standings = players.map(|player| player.standing(game));
+        game.check_victory(&standings);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::GameState;
+
+    // Player 1's turn started at 0 and ran out, both players hold a zone
+    fn expired_turn() -> (Game, [Player; 2]) {
+        let game = Game {
+            game_id: Pubkey::new_unique(),
+            player1: Pubkey::new_unique(),
+            player2: Pubkey::new_unique(),
+            game_state: GameState::Active,
+            ..Game::default()
+        };
+        let players = [game.player1, game.player2].map(|player_key| Player {
+            player_key,
+            game_id: game.game_id,
+            controlled_zones: 1,
+            ..Player::default()
+        });
+        (game, players)
+    }
+
+    fn claim(game: &mut Game, players: &[Player; 2]) -> Result<()> {
+        let now = game.turn_time_limit as i64;
+        force_end_turn(game, [&players[0], &players[1]], now)
+    }
+
+    #[test]
+    fn expired_turns_pass_to_the_waiting_player() {
+        let (mut game, players) = expired_turn();
+
+        claim(&mut game, &players).unwrap();
+
+        assert_eq!((game.current_turn, game.turn_number), (2, 1));
+        assert_eq!(game.last_turn_timestamp, game.turn_time_limit as i64);
+        assert_eq!(game.timeout_strikes, [1, 0]);
+        assert!(game.is_game_active());
+    }
+
+    #[test]
+    fn turns_within_the_limit_cannot_be_claimed() {
+        let (mut game, players) = expired_turn();
+        let now = game.turn_time_limit as i64 - 1;
+        let error = force_end_turn(&mut game, [&players[0], &players[1]], now).unwrap_err();
+        assert_eq!(error, ClaimTimeoutError::TurnNotExpired.into());
+
+        // A limit of 0 turns the timer off
+        game.turn_time_limit = 0;
+        let error = force_end_turn(&mut game, [&players[0], &players[1]], i64::MAX).unwrap_err();
+        assert_eq!(error, ClaimTimeoutError::TurnNotExpired.into());
+    }
+
+    #[test]
+    fn only_active_games_can_be_claimed() {
+        let (mut game, players) = expired_turn();
+        game.game_state = GameState::Paused;
+        assert_eq!(claim(&mut game, &players).unwrap_err(), ClaimTimeoutError::NotActive.into());
+    }
+
+    #[test]
+    fn repeated_timeouts_forfeit_the_game() {
+        let (mut game, players) = expired_turn();
+        game.timeout_strikes = [MAX_TIMEOUT_STRIKES - 1, 0];
+
+        claim(&mut game, &players).unwrap();
+
+        assert!(game.game_state == GameState::Finished { winner: GameWinner::Player2 });
+        assert_eq!(game.winner, game.player2);
+    }
+
+    #[test]
+    fn players_must_hold_their_seats() {
+        let mismatched: [fn(&mut Player); 2] = [
+            |player| player.player_key = Pubkey::new_unique(),
+            |player| player.game_id = Pubkey::new_unique(),
+        ];
+        for mismatch in mismatched {
+            let (mut game, mut players) = expired_turn();
+            mismatch(&mut players[1]);
+            assert_eq!(claim(&mut game, &players).unwrap_err(), ClaimTimeoutError::WrongPlayer.into());
+
+            // Passed in the wrong order, neither holds its seat
+            let (mut game, players) = expired_turn();
+            let swapped = [players[1].clone(), players[0].clone()];
+            assert_eq!(claim(&mut game, &swapped).unwrap_err(), ClaimTimeoutError::WrongPlayer.into());
+        }
+    }
+
+    #[test]
+    fn claims_check_both_players_for_elimination() {
+        let (mut game, mut players) = expired_turn();
+        players[1].controlled_zones = 0;
+
+        claim(&mut game, &players).unwrap();
+
+        assert!(game.game_state == GameState::Finished { winner: GameWinner::Player1 });
+    }
+}
@@ -0,0 +1,54 @@
+use bolt_lang::*;
+use grid::Zone;
+use zone_index::ZoneIndex;
+
+declare_id!("CoNN111111111111111111111111111111111111111");
+
+#[error_code]
+pub enum ConnectZonesError {
+    #[msg("Zone is not on the map.")]
+    NotPlaced,
+    #[msg("Zones are not neighbours.")]
+    NotNeighbours,
+    #[msg("Zone belongs to another game.")]
+    ZoneNotInGame,
+}
+
+// Creating a zone links it with the zone it grew from. Any other zones
+// already on neighbouring tiles are linked here, usually in the same
+// transaction, as a system takes too few components to link all four sides.
+#[system]
+pub mod connect_zones {
+
+    pub fn execute(ctx: Context<Components>, _args_p: Vec<u8>) -> Result<Components> {
+        link_zones(&mut ctx.accounts.zone, &mut ctx.accounts.neighbour, &ctx.accounts.zone_index)?;
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub zone: Zone,
+        pub neighbour: Zone,
+        pub zone_index: ZoneIndex, // On the game's entity
+    }
+}
+
+// Anyone may link two zones: the map decides which zones are neighbours.
+// Both zones must belong to the game of the index, whose map places them.
+pub fn link_zones(zone: &mut Zone, neighbour: &mut Zone, zone_index: &ZoneIndex) -> Result<()> {
+    require!(
+        zone_index.game_id != Pubkey::default()
+            && zone.game_id == zone_index.game_id
+            && neighbour.game_id == zone_index.game_id,
+        ConnectZonesError::ZoneNotInGame
+    );
+    require!(
+        zone_index.contains(zone.zone_id, zone.x, zone.y)
+            && zone_index.contains(neighbour.zone_id, neighbour.x, neighbour.y),
+        ConnectZonesError::NotPlaced
+    );
+    require!(zone.connect(neighbour), ConnectZonesError::NotNeighbours);
+
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use bolt_lang::*;
+use grid::{CellContent, Zone};
+use bio_rules::costs;
+use players::{Player, Faction};
+use game::Game;
+
+declare_id!("CoNQ111111111111111111111111111111111111111");
+
+#[error_code]
+pub enum ConquerZoneError {
+    #[msg("Player is not in the game.")]
+    NotInGame,
+    #[msg("Game is not active.")]
+    NotActive,
+    #[msg("Not player's turn.")]
+    NotPlayersTurn,
+    #[msg("Insufficient resources.")]
+    InsufficientResources,
+    #[msg("Zone already controlled.")]
+    ZoneAlreadyControlled,
+    #[msg("Zone not adjacent.")]
+    ZoneNotAdjacent,
+    #[msg("Defender does not own the target zone.")]
+    DefenderMismatch,
+    #[msg("Zone belongs to another game.")]
+    ZoneNotInGame,
+}
+
+// A conquest also updates the defender, which leaves expand-zone no room for
+// the zone index, so it runs as its own system
+#[system]
+pub mod conquer_zone {
+
+    pub fn execute(ctx: Context<Components>, _args_p: Vec<u8>) -> Result<Components> {
+        let authority = *ctx.accounts.authority.key;
+
+        // Validate player is acting for themselves
+        require!(ctx.accounts.player.player_key == authority, ConquerZoneError::NotInGame);
+
+        apply_conquest(
+            &mut ctx.accounts.game,
+            &mut ctx.accounts.player,
+            &mut ctx.accounts.defender,
+            &ctx.accounts.source_zone,
+            &mut ctx.accounts.target_zone,
+        )?;
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub game: Game,
+        pub player: Player,
+        pub source_zone: Zone,
+        pub target_zone: Zone,
+        pub defender: Player, // Owner of target_zone
+    }
+}
+
+// Takes `target_zone` from `defender` for `player`, who must already be
+// checked against the transaction signer
+pub fn apply_conquest(
+    game: &mut Game,
+    player: &mut Player,
+    defender: &mut Player,
+    source_zone: &Zone,
+    target_zone: &mut Zone,
+) -> Result<()> {
+    // Validate it's the player's turn
+    require!(game.is_player_turn(&player.player_key), ConquerZoneError::NotPlayersTurn);
+    require!(game.is_game_active(), ConquerZoneError::NotActive);
+    require!(
+        source_zone.game_id == game.game_id && target_zone.game_id == game.game_id,
+        ConquerZoneError::ZoneNotInGame
+    );
+
+    conquer_zone(game, player, defender, source_zone, target_zone)?;
+
+    // A conquest changes both zone counts, so the defender is re-evaluated too
+    let standings = [player.standing(game), defender.standing(game)];
+    game.check_victory(&standings);
+
+    Ok(())
+}
+
+fn conquer_zone(
+    game: &mut Game,
+    player: &mut Player,
+    defender: &mut Player,
+    source_zone: &Zone,
+    target_zone: &mut Zone,
+) -> Result<()> {
+    // Check if source zone is controlled by player
+    require!(source_zone.owner == player.player_key, ConquerZoneError::NotInGame);
+
+    // Check if target zone is controlled by enemy
+    require!(
+        target_zone.owner != Pubkey::default() && target_zone.owner != player.player_key,
+        ConquerZoneError::ZoneAlreadyControlled
+    );
+
+    // The defender must be the current owner, seated in this game
+    require!(
+        defender.player_key == target_zone.owner && defender.game_id == game.game_id,
+        ConquerZoneError::DefenderMismatch
+    );
+
+    // Check if zones are adjacent
+    require!(
+        source_zone.side_towards(target_zone.x, target_zone.y).is_some(),
+        ConquerZoneError::ZoneNotAdjacent
+    );
+
+    // Check conquest cost
+    let conquest_cost = costs::conquest_cost(target_zone.zone_type.rules()).ok_or(ProgramError::ArithmeticOverflow)?;
+    require!(
+        player.can_afford(conquest_cost.0, conquest_cost.1, conquest_cost.2, conquest_cost.3),
+        ConquerZoneError::InsufficientResources
+    );
+
+    // Deduct resources
+    player.spend_resources(conquest_cost.0, conquest_cost.1, conquest_cost.2, conquest_cost.3);
+
+    // Conquer the zone
+    target_zone.owner = player.player_key;
+    target_zone.is_controlled = true;
+
+    // Update player counts
+    defender.lose_zone(&target_zone.zone_type);
+    player.gain_zone(&target_zone.zone_type);
+
+    // Reduce resources in conquered zone (battle damage)
+    target_zone.energy /= 2;
+    target_zone.nutrients /= 2;
+
+    // Half of the defending units are lost in battle
+    remove_defending_units(game, target_zone, defender);
+
+    // Update infection/immune levels based on conquest
+    match player.faction {
+        Faction::Pathogen => game.update_infection_level(3),
+        Faction::ImmuneSystem => game.update_immune_response_level(3),
+    }
+
+    Ok(())
+}
+
+// Destroys every second unit the defender's seat owns, in grid order. Like
+// every other kill, the losses settle into the defender's total_units the
+// next time they act.
+fn remove_defending_units(game: &mut Game, zone: &mut Zone, defender: &Player) {
+    let defending: Vec<(u8, u8)> = zone
+        .occupied_cells()
+        .filter(|(_, _, content)| match content {
+            CellContent::ImmuneCell { seat, .. } | CellContent::Pathogen { seat, .. } => *seat == defender.player_id,
+            _ => false,
+        })
+        .map(|(x, y, _)| (x, y))
+        .collect();
+
+    for &(x, y) in defending.iter().step_by(2) {
+        play::destroy_unit(game, zone, x, y, &defender.player_key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::{GameState, GameWinner};
+    use grid::ZoneType;
+
+    // Player 1 attacks from (0, 0) into the neighbouring circulatory zone of
+    // player 2, which holds three pathogens
+    fn front() -> (Game, Player, Player, Zone, Zone) {
+        let (attacker_key, defender_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let game = Game {
+            game_id: Pubkey::new_unique(),
+            player1: attacker_key,
+            player2: defender_key,
+            current_turn: 1,
+            game_state: GameState::Active,
+            ..Game::default()
+        };
+        let mut player = Player { player_key: attacker_key, ..Player::default() };
+        let mut defender = Player {
+            player_id: 2,
+            player_key: defender_key,
+            game_id: game.game_id,
+            faction: Faction::Pathogen,
+            total_units: 3,
+            ..Player::default()
+        };
+        let zone = |owner: &mut Player, x: u8| {
+            owner.gain_zone(&ZoneType::Circulatory);
+            Zone { game_id: game.game_id, owner: owner.player_key, is_controlled: true, x, ..Zone::default() }
+        };
+        let source_zone = zone(&mut player, 0);
+        let mut target_zone = zone(&mut defender, 1);
+        for (unit_id, y) in [(1, 2), (2, 4), (3, 6)] {
+            target_zone.set_cell(5, y, Some(CellContent::Pathogen { unit_id, health: 40, seat: 2 })).unwrap();
+        }
+        target_zone.unit_count = 3;
+        (game, player, defender, source_zone, target_zone)
+    }
+
+    #[test]
+    fn every_second_defending_unit_is_destroyed() {
+        let (mut game, mut player, mut defender, source_zone, mut target_zone) = front();
+
+        apply_conquest(&mut game, &mut player, &mut defender, &source_zone, &mut target_zone).unwrap();
+
+        assert_eq!(target_zone.owner, player.player_key);
+        assert!(target_zone.is_empty_cell(5, 2) && target_zone.is_empty_cell(5, 6));
+        assert!(!target_zone.is_empty_cell(5, 4));
+        assert_eq!(target_zone.unit_count, 1);
+
+        // The losses wait for the defender to act, but already count
+        assert_eq!(game.unit_losses(&defender.player_key), 2);
+        let standing = defender.standing(&game);
+        assert_eq!((standing.live_units, standing.controlled_zones), (1, 0));
+        assert_eq!(player.standing(&game).controlled_zones, 2);
+        assert!(game.is_game_active());
+    }
+
+    #[test]
+    fn taking_the_last_zone_of_a_unitless_defender_wins() {
+        let (mut game, mut player, mut defender, source_zone, mut target_zone) = front();
+        target_zone.clear_cell(5, 4);
+        target_zone.clear_cell(5, 6);
+        target_zone.unit_count = 1;
+        defender.total_units = 1;
+
+        apply_conquest(&mut game, &mut player, &mut defender, &source_zone, &mut target_zone).unwrap();
+
+        assert!(game.game_state == GameState::Finished { winner: GameWinner::Player1 });
+    }
+
+    #[test]
+    fn the_defender_must_own_the_target_zone() {
+        let mismatched: [fn(&mut Player); 2] = [
+            |defender| defender.player_key = Pubkey::new_unique(),
+            |defender| defender.game_id = Pubkey::new_unique(),
+        ];
+        for mismatch in mismatched {
+            let (mut game, mut player, mut defender, source_zone, mut target_zone) = front();
+            mismatch(&mut defender);
+
+            let error = apply_conquest(&mut game, &mut player, &mut defender, &source_zone, &mut target_zone).unwrap_err();
+            assert_eq!(error, ConquerZoneError::DefenderMismatch.into());
+        }
+    }
+
+    #[test]
+    fn only_enemy_zones_next_to_the_player_are_conquered() {
+        let (mut game, mut player, mut defender, source_zone, mut target_zone) = front();
+        target_zone.x = 2;
+        let error = apply_conquest(&mut game, &mut player, &mut defender, &source_zone, &mut target_zone).unwrap_err();
+        assert_eq!(error, ConquerZoneError::ZoneNotAdjacent.into());
+
+        target_zone.x = 1;
+        target_zone.owner = player.player_key;
+        let error = apply_conquest(&mut game, &mut player, &mut defender, &source_zone, &mut target_zone).unwrap_err();
+        assert_eq!(error, ConquerZoneError::ZoneAlreadyControlled.into());
+    }
+
+    #[test]
+    fn conquests_cost_resources() {
+        let (mut game, mut player, mut defender, source_zone, mut target_zone) = front();
+        player.stem_cell_reserves = 49;
+
+        let error = apply_conquest(&mut game, &mut player, &mut defender, &source_zone, &mut target_zone).unwrap_err();
+        assert_eq!(error, ConquerZoneError::InsufficientResources.into());
+        assert_eq!(target_zone.owner, defender.player_key);
+    }
+}
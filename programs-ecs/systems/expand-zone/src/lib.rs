@@ -1,7 +1,9 @@
 use bolt_lang::*;
-use grid::{Zone, ZoneType};
+use grid::{Zone, ZoneType, POOL_CAPS};
+use bio_rules::costs;
 use players::{Player, Faction};
-use game::{Game, GameState};
+use game::Game;
+use zone_index::ZoneIndex;
 
 declare_id!("EXPa111111111111111111111111111111111111111");
 
@@ -25,37 +27,36 @@ pub enum ExpandZoneError {
     ExpansionNotPossible,
     #[msg("Max zones reached.")]
     MaxZonesReached,
+    #[msg("Position is off the map.")]
+    InvalidPosition,
+    #[msg("A zone already occupies this position.")]
+    PositionTaken,
+    #[msg("Zone is not on the map.")]
+    NotPlaced,
+    #[msg("Zone belongs to another game.")]
+    ZoneNotInGame,
+    #[msg("Zone index belongs to another game.")]
+    WrongZoneIndex,
 }
 
 #[system]
 pub mod expand_zone {
 
     pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
-        let game = &mut ctx.accounts.game;
-        let player = &mut ctx.accounts.player;
-        let source_zone = &ctx.accounts.source_zone;
-        let target_zone = &mut ctx.accounts.target_zone;
         let authority = *ctx.accounts.authority.key;
 
-        // Validate player is in game and it's their turn
-        require!(game.is_player_turn(&authority), ExpandZoneError::NotPlayersTurn);
-        require!(game.is_game_active(), ExpandZoneError::NotActive);
-        require!(player.player_key == authority, ExpandZoneError::NotInGame);
-
-        match args.expansion_type {
-            ExpansionType::InfectionSpread => {
-                infection_spread_expansion(game, player, source_zone, target_zone)?;
-            }
-            ExpansionType::ImmuneResponse => {
-                immune_response_expansion(game, player, source_zone, target_zone)?;
-            }
-            ExpansionType::CreateNewZone => {
-                create_new_zone(game, player, target_zone, args.new_zone_type)?;
-            }
-            ExpansionType::ConquerZone => {
-                conquer_zone(game, player, source_zone, target_zone)?;
-            }
-        }
+        // Validate player is acting for themselves
+        require!(ctx.accounts.player.player_key == authority, ExpandZoneError::NotInGame);
+
+        apply_expansion(
+            &mut ctx.accounts.game,
+            &mut ctx.accounts.player,
+            &mut ctx.accounts.source_zone,
+            &mut ctx.accounts.target_zone,
+            &mut ctx.accounts.zone_index,
+            args.expansion_type,
+            NewZone { zone_type: args.new_zone_type, x: args.x, y: args.y },
+        )?;
 
         Ok(ctx.accounts)
     }
@@ -66,28 +67,85 @@ pub mod expand_zone {
         pub player: Player,
         pub source_zone: Zone,
         pub target_zone: Zone,
+        pub zone_index: ZoneIndex, // On the game's entity
     }
 
     #[arguments]
     struct Args {
         expansion_type: ExpansionType,
         new_zone_type: u8, // Used for CreateNewZone
+        x: u8, // Map tile of the new zone, used for CreateNewZone
+        y: u8,
     }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+#[derive(AnchorSerialize, AnchorDeserialize, serde::Deserialize, Clone, Copy)]
 pub enum ExpansionType {
     InfectionSpread,    // Pathogens spreading to adjacent zones
     ImmuneResponse,     // Immune system establishing staging areas
     CreateNewZone,      // Player-initiated zone creation
-    ConquerZone,        // Taking control of existing zone
 }
 
+// Type and map tile of the zone CreateNewZone places
+#[derive(Clone, Copy, Default)]
+pub struct NewZone {
+    pub zone_type: u8,
+    pub x: u8,
+    pub y: u8,
+}
+
+// Runs one expansion for `player`, who must already be checked against the
+// transaction signer
+pub fn apply_expansion(
+    game: &mut Game,
+    player: &mut Player,
+    source_zone: &mut Zone,
+    target_zone: &mut Zone,
+    zone_index: &mut ZoneIndex,
+    expansion_type: ExpansionType,
+    new_zone: NewZone,
+) -> Result<()> {
+    // Validate it's the player's turn
+    require!(game.is_player_turn(&player.player_key), ExpandZoneError::NotPlayersTurn);
+    require!(game.is_game_active(), ExpandZoneError::NotActive);
+
+    // Zones and the index carry the id of their game. The zone CreateNewZone
+    // places is still fresh, and joins the game here.
+    require!(source_zone.game_id == game.game_id, ExpandZoneError::ZoneNotInGame);
+    require!(zone_index.game_id == game.game_id, ExpandZoneError::WrongZoneIndex);
+    if !matches!(expansion_type, ExpansionType::CreateNewZone) {
+        require!(target_zone.game_id == game.game_id, ExpandZoneError::ZoneNotInGame);
+    }
+
+    match expansion_type {
+        ExpansionType::InfectionSpread => {
+            infection_spread_expansion(game, player, source_zone, target_zone, zone_index)?;
+        }
+        ExpansionType::ImmuneResponse => {
+            immune_response_expansion(game, player, source_zone, target_zone, zone_index)?;
+        }
+        ExpansionType::CreateNewZone => {
+            create_new_zone(game, player, source_zone, target_zone, zone_index, new_zone)?;
+        }
+    }
+
+    // Expansions never change who owns a zone or unit, so the opponent's
+    // standing is as it was
+    let standing = player.standing(game);
+    game.check_victory(&[standing]);
+
+    Ok(())
+}
+
+// Drains the pools of an enemy or neutral zone next to one of the pathogen
+// player's zones. Both must be on the map: a zone component no expansion placed has no
+// tile, so it cannot be claimed by moving it next to the player.
 fn infection_spread_expansion(
     game: &mut Game,
     player: &mut Player,
     source_zone: &Zone,
     target_zone: &mut Zone,
+    zone_index: &ZoneIndex,
 ) -> Result<()> {
     // Only pathogen players can use infection spread
     require!(
@@ -98,14 +156,18 @@ fn infection_spread_expansion(
     // Check if source zone is controlled by player
     require!(source_zone.owner == player.player_key, ExpandZoneError::NotInGame);
 
-    // Check if zones are adjacent
+    // Expansions reach beyond the player's own territory
+    require!(target_zone.owner != player.player_key, ExpandZoneError::ZoneAlreadyControlled);
+
+    // Check if zones are on the map and adjacent
+    require_placed(source_zone, target_zone, zone_index)?;
     require!(
         is_adjacent_zone(source_zone, target_zone),
         ExpandZoneError::ZoneNotAdjacent
     );
 
     // Check expansion cost
-    let expansion_cost = calculate_infection_spread_cost(source_zone, target_zone);
+    let expansion_cost = costs::infection_spread_cost(target_zone.zone_type.rules()).ok_or(ProgramError::ArithmeticOverflow)?;
     require!(
         player.can_afford(expansion_cost.0, expansion_cost.1, expansion_cost.2, expansion_cost.3),
         ExpandZoneError::InsufficientResources
@@ -115,17 +177,8 @@ fn infection_spread_expansion(
     player.spend_resources(expansion_cost.0, expansion_cost.1, expansion_cost.2, expansion_cost.3);
 
     // Spread infection to target zone
-    if target_zone.owner == Pubkey::default() {
-        // Unclaimed zone - take control
-        target_zone.owner = player.player_key;
-        target_zone.is_controlled = true;
-        target_zone.zone_type = ZoneType::Tissue; // Infected tissue
-        player.controlled_zones += 1;
-    } else {
-        // Enemy zone - start infection process
-        target_zone.energy = target_zone.energy.saturating_sub(50);
-        target_zone.nutrients = target_zone.nutrients.saturating_sub(30);
-    }
+    target_zone.energy = target_zone.energy.saturating_sub(50);
+    target_zone.nutrients = target_zone.nutrients.saturating_sub(30);
 
     // Update game infection level
     game.update_infection_level(5);
@@ -133,11 +186,15 @@ fn infection_spread_expansion(
     Ok(())
 }
 
+// Boosts the pools of an enemy or neutral zone next to one of the immune
+// player's zones, as a staging area. Both must be on the map, as for
+// infection_spread_expansion.
 fn immune_response_expansion(
     game: &mut Game,
     player: &mut Player,
     source_zone: &Zone,
     target_zone: &mut Zone,
+    zone_index: &ZoneIndex,
 ) -> Result<()> {
     // Only immune system players can use immune response
     require!(
@@ -148,14 +205,18 @@ fn immune_response_expansion(
     // Check if source zone is controlled by player
     require!(source_zone.owner == player.player_key, ExpandZoneError::NotInGame);
 
-    // Check if zones are adjacent
+    // Expansions reach beyond the player's own territory
+    require!(target_zone.owner != player.player_key, ExpandZoneError::ZoneAlreadyControlled);
+
+    // Check if zones are on the map and adjacent
+    require_placed(source_zone, target_zone, zone_index)?;
     require!(
         is_adjacent_zone(source_zone, target_zone),
         ExpandZoneError::ZoneNotAdjacent
     );
 
     // Check expansion cost
-    let expansion_cost = calculate_immune_response_cost(source_zone, target_zone);
+    let expansion_cost = costs::immune_response_cost(target_zone.zone_type.rules()).ok_or(ProgramError::ArithmeticOverflow)?;
     require!(
         player.can_afford(expansion_cost.0, expansion_cost.1, expansion_cost.2, expansion_cost.3),
         ExpandZoneError::InsufficientResources
@@ -165,17 +226,8 @@ fn immune_response_expansion(
     player.spend_resources(expansion_cost.0, expansion_cost.1, expansion_cost.2, expansion_cost.3);
 
     // Establish immune staging area
-    if target_zone.owner == Pubkey::default() {
-        // Unclaimed zone - take control
-        target_zone.owner = player.player_key;
-        target_zone.is_controlled = true;
-        target_zone.zone_type = ZoneType::Lymphatic; // Immune staging area
-        player.controlled_zones += 1;
-    } else {
-        // Enemy zone - boost immune defenses
-        target_zone.antibodies = (target_zone.antibodies + 100).min(1000);
-        target_zone.energy = (target_zone.energy + 50).min(1000);
-    }
+    target_zone.antibodies = (target_zone.antibodies + 100).min(POOL_CAPS[1]);
+    target_zone.energy = (target_zone.energy + 50).min(POOL_CAPS[0]);
 
     // Update game immune response level
     game.update_immune_response_level(5);
@@ -183,30 +235,42 @@ fn immune_response_expansion(
     Ok(())
 }
 
+// Places a new zone on its map tile, next to the player's `source_zone` and
+// linked with it. Other zones already on neighbouring tiles are linked with
+// the connect-zones system.
 fn create_new_zone(
     game: &mut Game,
     player: &mut Player,
+    source_zone: &mut Zone,
     target_zone: &mut Zone,
-    zone_type_index: u8,
+    zone_index: &mut ZoneIndex,
+    new_zone: NewZone,
 ) -> Result<()> {
+    let (x, y) = (new_zone.x, new_zone.y);
+
     // Check if we've reached max zones
-    require!(game.total_zones < 64, ExpandZoneError::MaxZonesReached);
+    require!(zone_index.zones.len() < zone_index::MAX_ZONES, ExpandZoneError::MaxZonesReached);
 
     // Check if zone is unclaimed
     require!(target_zone.owner == Pubkey::default(), ExpandZoneError::ZoneAlreadyControlled);
 
+    // The tile must be free and next to a zone the player holds on this map
+    require!(game.is_on_map(x, y), ExpandZoneError::InvalidPosition);
+    require!(zone_index.zone_at(x, y).is_none(), ExpandZoneError::PositionTaken);
+    require!(
+        source_zone.owner == player.player_key
+            && zone_index.contains(source_zone.zone_id, source_zone.x, source_zone.y),
+        ExpandZoneError::NotInGame
+    );
+    require!(source_zone.side_towards(x, y).is_some(), ExpandZoneError::ZoneNotAdjacent);
+
     // Get zone type
-    let zone_type = match zone_type_index {
-        0 => ZoneType::Circulatory,
-        1 => ZoneType::Tissue,
-        2 => ZoneType::Lymphatic,
-        3 => ZoneType::Barrier,
-        4 => ZoneType::Organ,
-        _ => return Err(ExpandZoneError::InvalidZoneType.into()),
-    };
+    let zone_type: ZoneType = bio_rules::ZoneType::from_index(new_zone.zone_type)
+        .ok_or(ExpandZoneError::InvalidZoneType)?
+        .into();
 
     // Check creation cost
-    let creation_cost = calculate_zone_creation_cost(&zone_type, player.faction);
+    let creation_cost = costs::zone_creation_cost(zone_type.rules(), player.faction.rules()).ok_or(ProgramError::ArithmeticOverflow)?;
     require!(
         player.can_afford(creation_cost.0, creation_cost.1, creation_cost.2, creation_cost.3),
         ExpandZoneError::InsufficientResources
@@ -216,11 +280,17 @@ fn create_new_zone(
     player.spend_resources(creation_cost.0, creation_cost.1, creation_cost.2, creation_cost.3);
 
     // Create new zone
-    target_zone.zone_id = game.total_zones;
+    target_zone.zone_id = game.allocate_zone_id().ok_or(ExpandZoneError::MaxZonesReached)?;
+    target_zone.game_id = game.game_id;
     target_zone.zone_type = zone_type;
     target_zone.owner = player.player_key;
     target_zone.is_controlled = true;
-    target_zone.is_border_zone = true; // New zones are typically border zones
+    target_zone.x = x;
+    target_zone.y = y;
+    target_zone.is_border_zone = game.is_border_tile(x, y);
+    target_zone.generate_interior(game.seed)?;
+    source_zone.connect(target_zone);
+    zone_index.insert(target_zone.zone_id, x, y)?;
 
     // Set initial resources based on zone type
     let (energy, antibodies, stem_cells, nutrients) = zone_type.get_resource_generation();
@@ -229,123 +299,23 @@ fn create_new_zone(
     target_zone.stem_cells = stem_cells * 5;
     target_zone.nutrients = nutrients * 5;
 
-    // Update game and player state
-    game.total_zones += 1;
-    player.controlled_zones += 1;
+    // Update player state
+    player.gain_zone(&target_zone.zone_type);
 
     Ok(())
 }
 
-fn conquer_zone(
-    game: &mut Game,
-    player: &mut Player,
-    source_zone: &Zone,
-    target_zone: &mut Zone,
-) -> Result<()> {
-    // Check if source zone is controlled by player
-    require!(source_zone.owner == player.player_key, ExpandZoneError::NotInGame);
-
-    // Check if target zone is controlled by enemy
-    require!(
-        target_zone.owner != Pubkey::default() && target_zone.owner != player.player_key,
-        ExpandZoneError::ZoneAlreadyControlled
-    );
-
-    // Check if zones are adjacent
-    require!(
-        is_adjacent_zone(source_zone, target_zone),
-        ExpandZoneError::ZoneNotAdjacent
-    );
-
-    // Check conquest cost
-    let conquest_cost = calculate_conquest_cost(source_zone, target_zone);
+// Zones on the map are in the index at their tile, every other zone
+// component is unplaced
+fn require_placed(source_zone: &Zone, target_zone: &Zone, zone_index: &ZoneIndex) -> Result<()> {
     require!(
-        player.can_afford(conquest_cost.0, conquest_cost.1, conquest_cost.2, conquest_cost.3),
-        ExpandZoneError::InsufficientResources
+        zone_index.contains(source_zone.zone_id, source_zone.x, source_zone.y)
+            && zone_index.contains(target_zone.zone_id, target_zone.x, target_zone.y),
+        ExpandZoneError::NotPlaced
     );
-
-    // Deduct resources
-    player.spend_resources(conquest_cost.0, conquest_cost.1, conquest_cost.2, conquest_cost.3);
-
-    // Conquer the zone
-    let old_owner = target_zone.owner;
-    target_zone.owner = player.player_key;
-    target_zone.is_controlled = true;
-
-    // Update player counts
-    player.controlled_zones += 1;
-
-    // Reduce resources in conquered zone (battle damage)
-    target_zone.energy = target_zone.energy / 2;
-    target_zone.nutrients = target_zone.nutrients / 2;
-    target_zone.unit_count = target_zone.unit_count / 2; // Some units lost in battle
-
-    // Update infection/immune levels based on conquest
-    match player.faction {
-        Faction::Pathogen => game.update_infection_level(3),
-        Faction::ImmuneSystem => game.update_immune_response_level(3),
-    }
-
     Ok(())
 }
 
 fn is_adjacent_zone(zone1: &Zone, zone2: &Zone) -> bool {
-    let dx = (zone1.x as i16 - zone2.x as i16).abs();
-    let dy = (zone1.y as i16 - zone2.y as i16).abs();
-    (dx == 1 && dy == 0) || (dx == 0 && dy == 1)
-}
-
-fn calculate_infection_spread_cost(source: &Zone, target: &Zone) -> (u64, u64, u64, u64) {
-    let base_cost = 100u64;
-    let zone_resistance = match target.zone_type {
-        ZoneType::Barrier => 3,
-        ZoneType::Lymphatic => 2,
-        _ => 1,
-    };
-    
-    let adjusted_cost = base_cost * zone_resistance;
-    (adjusted_cost * 2, 0, 0, adjusted_cost) // High energy and nutrient cost
-}
-
-fn calculate_immune_response_cost(source: &Zone, target: &Zone) -> (u64, u64, u64, u64) {
-    let base_cost = 80u64;
-    let zone_difficulty = match target.zone_type {
-        ZoneType::Tissue => 2, // Harder to establish in infected tissue
-        ZoneType::Organ => 3,  // Very difficult in organs
-        _ => 1,
-    };
-    
-    let adjusted_cost = base_cost * zone_difficulty;
-    (adjusted_cost, adjusted_cost * 2, adjusted_cost / 4, adjusted_cost / 2)
-}
-
-fn calculate_zone_creation_cost(zone_type: &ZoneType, faction: Faction) -> (u64, u64, u64, u64) {
-    let base_cost = match zone_type {
-        ZoneType::Circulatory => 200,
-        ZoneType::Tissue => 150,
-        ZoneType::Lymphatic => 300,
-        ZoneType::Barrier => 400,
-        ZoneType::Organ => 500,
-    };
-
-    let faction_modifier = match faction {
-        Faction::ImmuneSystem => 1.0,
-        Faction::Pathogen => 1.2, // Slightly more expensive for pathogens
-    };
-
-    let adjusted_cost = (base_cost as f64 * faction_modifier) as u64;
-    (adjusted_cost, adjusted_cost / 2, adjusted_cost / 10, adjusted_cost / 3)
-}
-
-fn calculate_conquest_cost(source: &Zone, target: &Zone) -> (u64, u64, u64, u64) {
-    let base_cost = 250u64;
-    let defense_multiplier = match target.zone_type {
-        ZoneType::Barrier => 2,
-        ZoneType::Organ => 3,
-        ZoneType::Lymphatic => 2,
-        _ => 1,
-    };
-    
-    let adjusted_cost = base_cost * defense_multiplier;
-    (adjusted_cost * 3, adjusted_cost, adjusted_cost / 5, adjusted_cost * 2)
+    zone1.side_towards(zone2.x, zone2.y).is_some()
 }
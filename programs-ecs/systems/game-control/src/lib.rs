@@ -0,0 +1,220 @@
+use bolt_lang::*;
+use game::{Game, GameState, GameWinner};
+
+declare_id!("CTRL111111111111111111111111111111111111111");
+
+#[error_code]
+pub enum GameControlError {
+    #[msg("Player is not in the game.")]
+    NotInGame,
+    #[msg("Game is not active.")]
+    NotActive,
+    #[msg("Game is not paused.")]
+    NotPaused,
+    #[msg("Game is already finished.")]
+    GameFinished,
+    #[msg("No pending draw offer from the opponent.")]
+    NoDrawOffer,
+}
+
+#[system]
+pub mod game_control {
+
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        let authority = *ctx.accounts.authority.key;
+        let now = Clock::get()?.unix_timestamp;
+
+        apply_control(&mut ctx.accounts.game, &authority, args.action, now)?;
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub game: Game,
+    }
+
+    #[arguments]
+    struct Args {
+        action: ControlAction,
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, serde::Deserialize, Clone, Copy)]
+pub enum ControlAction {
+    RequestPause, // Pauses once both players have requested it
+    Resume,       // Either player may end a pause, so neither can hold the game
+    Resign,
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+}
+
+// Runs one control action for `player`, the transaction signer
+pub fn apply_control(game: &mut Game, player: &Pubkey, action: ControlAction, now: i64) -> Result<()> {
+    // Validate player is in game
+    require!(game.get_opponent(player).is_some(), GameControlError::NotInGame);
+
+    match action {
+        ControlAction::RequestPause => request_pause(game, player),
+        ControlAction::Resume => resume(game, player, now),
+        ControlAction::Resign => resign(game, player),
+        ControlAction::OfferDraw => offer_draw(game, player),
+        ControlAction::AcceptDraw => accept_draw(game, player),
+        ControlAction::DeclineDraw => decline_draw(game, player),
+    }
+}
+
+pub fn request_pause(game: &mut Game, player: &Pubkey) -> Result<()> {
+    require!(game.is_game_active(), GameControlError::NotActive);
+
+    if game.pause_requested_by != Pubkey::default() && game.pause_requested_by != *player {
+        // The opponent asked first, so both players agree
+        game.game_state = GameState::Paused;
+        game.pause_requested_by = Pubkey::default();
+    } else {
+        game.pause_requested_by = *player;
+    }
+
+    Ok(())
+}
+
+// A pause takes both players, but either may end it: needing both would let
+// one player stall the game forever
+pub fn resume(game: &mut Game, _player: &Pubkey, now: i64) -> Result<()> {
+    require!(game.game_state == GameState::Paused, GameControlError::NotPaused);
+
+    game.game_state = GameState::Active;
+
+    // Time spent paused does not count against the current turn
+    game.last_turn_timestamp = now;
+
+    Ok(())
+}
+
+pub fn resign(game: &mut Game, player: &Pubkey) -> Result<()> {
+    require!(
+        matches!(game.game_state, GameState::Active | GameState::Paused),
+        GameControlError::GameFinished
+    );
+
+    let winner = if *player == game.player1 {
+        GameWinner::Player2
+    } else {
+        GameWinner::Player1
+    };
+    game.end_game(winner);
+
+    Ok(())
+}
+
+pub fn offer_draw(game: &mut Game, player: &Pubkey) -> Result<()> {
+    require!(game.is_game_active(), GameControlError::NotActive);
+
+    game.draw_offered_by = *player;
+
+    Ok(())
+}
+
+pub fn accept_draw(game: &mut Game, player: &Pubkey) -> Result<()> {
+    require!(game.is_game_active(), GameControlError::NotActive);
+    require!(
+        game.draw_offered_by != Pubkey::default() && game.draw_offered_by != *player,
+        GameControlError::NoDrawOffer
+    );
+
+    game.draw_offered_by = Pubkey::default();
+    game.end_game(GameWinner::Draw);
+
+    Ok(())
+}
+
+pub fn decline_draw(game: &mut Game, player: &Pubkey) -> Result<()> {
+    require!(
+        game.draw_offered_by != Pubkey::default() && game.draw_offered_by != *player,
+        GameControlError::NoDrawOffer
+    );
+
+    game.draw_offered_by = Pubkey::default();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn active_game() -> (Game, Pubkey, Pubkey) {
+        let (player1, player2) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let game = Game { player1, player2, game_state: GameState::Active, ..Game::default() };
+        (game, player1, player2)
+    }
+
+    fn paused_game() -> (Game, Pubkey, Pubkey) {
+        let (mut game, player1, player2) = active_game();
+        apply_control(&mut game, &player1, ControlAction::RequestPause, 0).unwrap();
+        apply_control(&mut game, &player2, ControlAction::RequestPause, 0).unwrap();
+        (game, player1, player2)
+    }
+
+    #[test]
+    fn only_players_of_the_game_control_it() {
+        let (mut game, _, _) = active_game();
+        let error = apply_control(&mut game, &Pubkey::new_unique(), ControlAction::Resign, 0).unwrap_err();
+        assert_eq!(error, GameControlError::NotInGame.into());
+        assert!(game.is_game_active());
+    }
+
+    #[test]
+    fn pausing_takes_both_players() {
+        let (mut game, player1, _) = active_game();
+        apply_control(&mut game, &player1, ControlAction::RequestPause, 0).unwrap();
+        apply_control(&mut game, &player1, ControlAction::RequestPause, 0).unwrap();
+        assert!(game.is_game_active());
+
+        let (game, _, _) = paused_game();
+        assert!(game.game_state == GameState::Paused);
+    }
+
+    #[test]
+    fn either_player_ends_a_pause() {
+        for resumer in 0..2 {
+            let (mut game, player1, player2) = paused_game();
+            apply_control(&mut game, &[player1, player2][resumer], ControlAction::Resume, 70).unwrap();
+
+            assert!(game.is_game_active());
+            assert_eq!(game.last_turn_timestamp, 70);
+        }
+    }
+
+    #[test]
+    fn only_paused_games_resume() {
+        let (mut game, player1, _) = active_game();
+        let error = apply_control(&mut game, &player1, ControlAction::Resume, 0).unwrap_err();
+        assert_eq!(error, GameControlError::NotPaused.into());
+    }
+
+    #[test]
+    fn resigning_hands_the_win_to_the_opponent() {
+        let (mut game, _, player2) = paused_game();
+        apply_control(&mut game, &player2, ControlAction::Resign, 0).unwrap();
+
+        assert!(game.game_state == GameState::Finished { winner: GameWinner::Player1 });
+        assert_eq!(game.winner, game.player1);
+        let player1 = game.player1;
+        assert!(apply_control(&mut game, &player1, ControlAction::Resign, 0).is_err());
+    }
+
+    #[test]
+    fn draws_are_accepted_or_declined_by_the_opponent() {
+        let (mut game, player1, player2) = active_game();
+        apply_control(&mut game, &player1, ControlAction::OfferDraw, 0).unwrap();
+        assert!(apply_control(&mut game, &player1, ControlAction::AcceptDraw, 0).is_err());
+        apply_control(&mut game, &player2, ControlAction::DeclineDraw, 0).unwrap();
+        assert!(apply_control(&mut game, &player2, ControlAction::AcceptDraw, 0).is_err());
+
+        apply_control(&mut game, &player2, ControlAction::OfferDraw, 0).unwrap();
+        apply_control(&mut game, &player1, ControlAction::AcceptDraw, 0).unwrap();
+        assert!(game.game_state == GameState::Finished { winner: GameWinner::Draw });
+    }
+}
@@ -2,6 +2,7 @@ use bolt_lang::*;
 use players::{Player, Faction};
 use game::{Game, GameState};
 use grid::Zone;
+use zone_index::ZoneIndex;
 
 declare_id!("7TsTc97MB21EKbh2RetcWsGWRJ4xuMkPKKD4DcMJ2Sms");
 
@@ -15,102 +16,36 @@ pub enum JoinGameError {
     GameAlreadyStarted,
     #[msg("Invalid faction choice.")]
     InvalidFaction,
+    #[msg("Starting zone already claimed.")]
+    StartingZoneTaken,
+    #[msg("The other player already chose this faction.")]
+    FactionTaken,
+    #[msg("Zone index belongs to another game.")]
+    WrongZoneIndex,
 }
 
 #[system]
 pub mod join_game {
 
     pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
-        let game = &mut ctx.accounts.game;
-        let player = &mut ctx.accounts.player;
         let authority = *ctx.accounts.authority.key;
+        let now = Clock::get()?.unix_timestamp;
 
-        // Check if game is in waiting state
-        require!(
-            matches!(game.game_state, GameState::WaitingForPlayers),
-            JoinGameError::GameAlreadyStarted
-        );
-
-        // Check if player already in game
-        require!(
-            game.player1 != authority && game.player2 != authority,
-            JoinGameError::PlayerAlreadyInGame
-        );
-
-        // Assign player to game
-        let player_id = if game.player1 == Pubkey::default() {
-            game.player1 = authority;
-            1
-        } else if game.player2 == Pubkey::default() {
-            game.player2 = authority;
-            2
-        } else {
-            return Err(JoinGameError::GameFull.into());
-        };
-
-        // Initialize player with selected faction
-        let faction = match args.faction {
-            0 => Faction::ImmuneSystem,
-            1 => Faction::Pathogen,
-            _ => return Err(JoinGameError::InvalidFaction.into()),
-        };
-
-        // Set up player data
-        player.player_id = player_id;
-        player.player_key = authority;
-        player.faction = faction;
-        
-        // Set faction-specific starting resources
-        match faction {
-            Faction::ImmuneSystem => {
-                player.energy_reserves = 1200;
-                player.antibody_reserves = 800;
-                player.stem_cell_reserves = 150;
-                player.nutrient_reserves = 900;
-            }
-            Faction::Pathogen => {
-                player.energy_reserves = 1500;
-                player.antibody_reserves = 200;
-                player.stem_cell_reserves = 50;
-                player.nutrient_reserves = 1200;
-            }
+        // Units and entity seeds carry the game id. The component address is
+        // unique to the game, so no two games can share one.
+        if ctx.accounts.game.player1 == Pubkey::default() {
+            ctx.accounts.game.game_id = ctx.accounts.game.key();
         }
 
-        // Unlock starting units based on faction
-        match faction {
-            Faction::ImmuneSystem => {
-                player.unlock_unit(0); // TCell
-                player.unlock_unit(1); // BCell
-                player.unlock_unit(2); // Macrophage
-            }
-            Faction::Pathogen => {
-                player.unlock_unit(6); // Virus
-                player.unlock_unit(7); // Bacteria
-                player.unlock_unit(8); // Fungus
-            }
-        }
-
-        // If both players joined, start the game
-        if game.player1 != Pubkey::default() && game.player2 != Pubkey::default() {
-            game.game_state = GameState::Active;
-            game.current_turn = 1; // Player 1 starts
-            
-            // Initialize starting zones
-            if let Some(zone) = &mut ctx.accounts.starting_zone {
-                zone.zone_id = 0;
-                zone.x = 2; // Center of 4x4 map
-                zone.y = 2;
-                zone.owner = authority;
-                zone.is_controlled = true;
-                zone.zone_type = match faction {
-                    Faction::ImmuneSystem => grid::ZoneType::Lymphatic,
-                    Faction::Pathogen => grid::ZoneType::Tissue,
-                };
-                
-                // Give player control of this zone
-                player.controlled_zones = 1;
-            }
-        }
+        seat_player(
+            &mut ctx.accounts.game,
+            &mut ctx.accounts.player,
+            &mut ctx.accounts.starting_zone,
+            &mut ctx.accounts.zone_index,
+            authority,
+            args.faction,
+            now,
+        )?;
 
         Ok(ctx.accounts)
     }
@@ -119,7 +54,8 @@ pub mod join_game {
     pub struct Components {
         pub game: Game,
         pub player: Player,
-        pub starting_zone: Option<Zone>,
+        pub starting_zone: Zone,
+        pub zone_index: ZoneIndex, // On the game's entity
     }
 
     #[arguments]
@@ -127,3 +63,142 @@ pub mod join_game {
         faction: u8, // 0 = ImmuneSystem, 1 = Pathogen
     }
 }
+
+// Seats `authority` in the game with their starting zone, and starts the game
+// once both players have joined
+pub fn seat_player(
+    game: &mut Game,
+    player: &mut Player,
+    zone: &mut Zone,
+    zone_index: &mut ZoneIndex,
+    authority: Pubkey,
+    faction_index: u8,
+    now: i64,
+) -> Result<()> {
+    // Check if game is in waiting state
+    require!(
+        matches!(game.game_state, GameState::WaitingForPlayers),
+        JoinGameError::GameAlreadyStarted
+    );
+
+    // Check if player already in game
+    require!(
+        game.player1 != authority && game.player2 != authority,
+        JoinGameError::PlayerAlreadyInGame
+    );
+
+    // Assign player to game
+    let player_id = if game.player1 == Pubkey::default() {
+        game.player1 = authority;
+        1
+    } else if game.player2 == Pubkey::default() {
+        game.player2 = authority;
+        2
+    } else {
+        return Err(JoinGameError::GameFull.into());
+    };
+
+    // Initialize player with selected faction
+    let faction = match faction_index {
+        0 => Faction::ImmuneSystem,
+        1 => Faction::Pathogen,
+        _ => return Err(JoinGameError::InvalidFaction.into()),
+    };
+
+    // One player per faction: infection and immune response wins go to the
+    // faction's seat
+    let faction_taken = match faction {
+        Faction::ImmuneSystem => player_id == 2 && game.pathogen_seat == 0,
+        Faction::Pathogen => game.pathogen_seat != 0,
+    };
+    require!(!faction_taken, JoinGameError::FactionTaken);
+    if faction == Faction::Pathogen {
+        game.pathogen_seat = player_id;
+    }
+
+    // Set up player data
+    player.player_id = player_id;
+    player.player_key = authority;
+    player.game_id = game.game_id;
+    player.faction = faction;
+    
+    // Set faction-specific starting resources
+    match faction {
+        Faction::ImmuneSystem => {
+            player.energy_reserves = 1200;
+            player.antibody_reserves = 800;
+            player.stem_cell_reserves = 150;
+            player.nutrient_reserves = 900;
+        }
+        Faction::Pathogen => {
+            player.energy_reserves = 1500;
+            player.antibody_reserves = 200;
+            player.stem_cell_reserves = 50;
+            player.nutrient_reserves = 1200;
+        }
+    }
+
+    // Unlock starting units based on faction
+    match faction {
+        Faction::ImmuneSystem => {
+            player.unlock_unit(0); // TCell
+            player.unlock_unit(1); // BCell
+            player.unlock_unit(2); // Macrophage
+        }
+        Faction::Pathogen => {
+            player.unlock_unit(6); // Virus
+            player.unlock_unit(7); // Bacteria
+            player.unlock_unit(8); // Fungus
+        }
+    }
+
+    // Each player starts from their own zone, in opposite corners of the map
+    require!(zone.owner == Pubkey::default(), JoinGameError::StartingZoneTaken);
+    zone.zone_id = player_id as u32 - 1;
+    zone.game_id = game.game_id;
+    (zone.x, zone.y) = match player_id {
+        1 => (0, 0),
+        _ => (game.map_width.saturating_sub(1), game.map_height.saturating_sub(1)),
+    };
+    zone.is_border_zone = game.is_border_tile(zone.x, zone.y);
+
+    // The first join binds the index to the game, the second must use it
+    if player_id == 1 && zone_index.game_id == Pubkey::default() {
+        zone_index.game_id = game.game_id;
+    }
+    require!(zone_index.game_id == game.game_id, JoinGameError::WrongZoneIndex);
+    zone_index.insert(zone.zone_id, zone.x, zone.y)?;
+    zone.owner = authority;
+    zone.is_controlled = true;
+    zone.zone_type = match faction {
+        Faction::ImmuneSystem => grid::ZoneType::Lymphatic,
+        Faction::Pathogen => grid::ZoneType::Tissue,
+    };
+
+    // Give player control of this zone
+    player.gain_zone(&zone.zone_type);
+
+    // If both players joined, start the game
+    if game.player1 != Pubkey::default() && game.player2 != Pubkey::default() {
+        game.game_state = GameState::Active;
+        game.current_turn = 1; // Player 1 starts
+        game.last_turn_timestamp = now;
+        game.seed = game_seed(game, now);
+    }
+
+    Ok(())
+}
+
+// Fixed when the second player joins, and public from then on so clients can
+// check the zones generated from it
+fn game_seed(game: &Game, now: i64) -> u64 {
+    let hash = solana_program::hash::hashv(&[
+        game.game_id.as_ref(),
+        game.player1.as_ref(),
+        game.player2.as_ref(),
+        &now.to_le_bytes(),
+    ]);
+    let mut seed = [0u8; 8];
+    seed.copy_from_slice(&hash.to_bytes()[..8]);
+    u64::from_le_bytes(seed)
+}
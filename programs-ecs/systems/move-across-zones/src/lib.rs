@@ -0,0 +1,51 @@
+use bolt_lang::*;
+use game::Game;
+use grid::Zone;
+use play::{BioCommanderError, Direction};
+use players::Player;
+use unit::Unit;
+
+declare_id!("MoVE111111111111111111111111111111111111111");
+
+// A crossing touches two zones besides the unit, more components than play
+// takes, so it runs as its own system over play's rules
+#[system]
+pub mod move_across_zones {
+
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        let authority = *ctx.accounts.authority.key;
+
+        // Validate player is acting for themselves
+        require!(ctx.accounts.player.player_key == authority, BioCommanderError::NotInGame);
+
+        let cross = match args.infiltrate {
+            true => play::infiltrate_across_zones,
+            false => play::move_unit_across_zones,
+        };
+        cross(
+            &mut ctx.accounts.game,
+            &mut ctx.accounts.player,
+            &mut ctx.accounts.source_zone,
+            &mut ctx.accounts.destination_zone,
+            &mut ctx.accounts.unit,
+            args.direction,
+        )?;
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub game: Game,
+        pub player: Player,
+        pub source_zone: Zone,
+        pub destination_zone: Zone, // Connected to source_zone on the `direction` side
+        pub unit: Unit,
+    }
+
+    #[arguments]
+    struct Args {
+        direction: Direction,
+        infiltrate: bool, // Cross with the unit's Infiltration ability instead of its move
+    }
+}
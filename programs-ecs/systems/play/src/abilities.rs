@@ -0,0 +1,192 @@
+use bolt_lang::*;
+use game::Game;
+use grid::{CellContent, Zone, POOL_CAPS};
+use players::Player;
+use unit::{SpecialAbility, Unit};
+
+use crate::{bind_unit, destroy_unit, economy, relocate_unit, BioCommanderError};
+
+// Area effects hit every visible enemy cell within this Chebyshev radius
+pub const AREA_RADIUS: u8 = 1;
+pub const CYTOKINE_DAMAGE: u16 = 10;
+pub const TOXIN_DAMAGE: u16 = 15;
+
+// MemoryResponse and Mutation stop growing a stat this far above its base value
+pub const MAX_STAT_BONUS: u16 = 10;
+
+// ImmuneEvasion hides the unit through the opponent's next turn
+pub const STEALTH_TURNS: u32 = 2;
+
+// Replicas start with this share of their type's base health
+pub const REPLICA_HEALTH_PERCENT: u16 = 50;
+
+// Order in which Replication looks for a free cell: N, E, S, W, then diagonals
+const NEIGHBOURS: [(i8, i8); 8] = [(0, -1), (1, 0), (0, 1), (-1, 0), (1, -1), (1, 1), (-1, 1), (-1, -1)];
+
+// Metastasis moves the unit at most this many cells, by Manhattan distance
+pub const METASTASIS_RANGE: u8 = 6;
+
+// ResourceDrain steals at most this much from the zone pools per use
+pub const DRAIN_ENERGY: u32 = 30;
+pub const DRAIN_NUTRIENTS: u32 = 20;
+
+/// Effects, by ability:
+/// - AntibodyProduction: +50 antibody reserves.
+/// - Phagocytosis: heals the unit by 20 and digests 10 energy and 5 nutrients.
+/// - CytokineRelease: deals 10 damage to every enemy cell around the unit,
+///   hidden units aside, and raises the immune response level.
+/// - MemoryResponse: +2 attack and +2 defense, up to MAX_STAT_BONUS above base.
+/// - Infiltration: slips the unit into a connected zone, see
+///   crossing::infiltrate_across_zones. It cannot be used through play.
+/// - ZoneHealing: restores 50 energy and 30 nutrients to the zone pools.
+/// - Replication: places a child of the same type, at half health, on the first
+///   free cell around the unit, bound to the fresh `child` component.
+/// - Mutation: +3 attack and +5 max health, up to MAX_STAT_BONUS above base.
+/// - ToxinRelease: deals 15 damage to every enemy cell around the unit,
+///   hidden units aside, and raises the infection level.
+/// - ImmuneEvasion: the unit cannot be targeted, nor hit by area effects, until
+///   the opponent's turn ends.
+/// - Metastasis: moves the unit to a free cell (x, y) of the zone up to
+///   METASTASIS_RANGE away and raises the infection level.
+/// - ResourceDrain: steals up to 30 energy and 20 nutrients from the pools of
+///   a zone the player does not own.
+pub fn use_special_ability(
+    game: &mut Game,
+    player: &mut Player,
+    unit: &mut Unit,
+    child: &mut Unit,
+    zone: &mut Zone,
+    ability_index: u8,
+    (x, y): (u8, u8),
+) -> Result<()> {
+    let ability = ready_ability(game, player, unit, zone, ability_index)?;
+
+    match ability {
+        SpecialAbility::AntibodyProduction => {
+            player.add_resources(0, 50, 0, 0);
+        }
+        SpecialAbility::Phagocytosis => {
+            // Heal unit and gain resources
+            unit.health = (unit.health + 20).min(unit.max_health);
+            player.add_resources(10, 0, 0, 5);
+        }
+        SpecialAbility::CytokineRelease => {
+            damage_enemies_around(game, player, unit, zone, CYTOKINE_DAMAGE);
+            game.update_immune_response_level(1);
+        }
+        SpecialAbility::MemoryResponse => {
+            let (_, base_attack, base_defense, _, _) = unit.unit_type.get_base_stats();
+            unit.attack = (unit.attack + 2).min(base_attack + MAX_STAT_BONUS);
+            unit.defense = (unit.defense + 2).min(base_defense + MAX_STAT_BONUS);
+        }
+        SpecialAbility::Infiltration => {
+            // Needs the zone it crosses into, which play does not take
+            return Err(BioCommanderError::InvalidAction.into());
+        }
+        SpecialAbility::ZoneHealing => {
+            // Restore zone resources
+            zone.energy = (zone.energy + 50).min(POOL_CAPS[0]);
+            zone.nutrients = (zone.nutrients + 30).min(POOL_CAPS[3]);
+        }
+        SpecialAbility::Replication => {
+            let (child_x, child_y) = find_free_neighbour(zone, unit.x, unit.y)
+                .ok_or(BioCommanderError::NoFreeCell)?;
+            bind_unit(game, player, zone, child, unit.unit_type, child_x, child_y)?;
+            child.health = child.max_health * REPLICA_HEALTH_PERCENT / 100;
+            zone.set_unit_health(child_x, child_y, child.health);
+        }
+        SpecialAbility::Mutation => {
+            let (base_health, base_attack, _, _, _) = unit.unit_type.get_base_stats();
+            unit.attack = (unit.attack + 3).min(base_attack + MAX_STAT_BONUS);
+            let max_health = (unit.max_health + 5).min(base_health + MAX_STAT_BONUS).max(unit.max_health);
+            unit.health += max_health - unit.max_health;
+            unit.max_health = max_health;
+        }
+        SpecialAbility::ToxinRelease => {
+            damage_enemies_around(game, player, unit, zone, TOXIN_DAMAGE);
+            game.update_infection_level(1);
+        }
+        SpecialAbility::ImmuneEvasion => {
+            unit.stealth_until_turn = game.turn_number + STEALTH_TURNS;
+            zone.hide_unit(unit.x, unit.y, unit.stealth_until_turn);
+        }
+        SpecialAbility::Metastasis => {
+            let distance = (x as i16 - unit.x as i16).abs() + (y as i16 - unit.y as i16).abs();
+            require!(distance <= METASTASIS_RANGE as i16, BioCommanderError::TargetOutOfRange);
+            relocate_unit(unit, zone, x, y)?;
+            game.update_infection_level(2);
+        }
+        SpecialAbility::ResourceDrain => {
+            require!(zone.owner != player.player_key, BioCommanderError::InvalidAction);
+            let energy = zone.energy.min(DRAIN_ENERGY);
+            let nutrients = zone.nutrients.min(DRAIN_NUTRIENTS);
+            zone.energy -= energy;
+            zone.nutrients -= nutrients;
+            player.add_resources(energy as u64, 0, 0, nutrients as u64);
+        }
+    }
+
+    // Keep the grid cell in step with the unit
+    zone.set_unit_health(unit.x, unit.y, unit.health);
+
+    Ok(())
+}
+
+// Looks up the ability in `ability_index`, starts its cooldown and pays for
+// it, from the zone's pools first
+pub(crate) fn ready_ability(
+    game: &Game,
+    player: &mut Player,
+    unit: &mut Unit,
+    zone: &mut Zone,
+    ability_index: u8,
+) -> Result<SpecialAbility> {
+    let ability = unit
+        .special_abilities
+        .get(ability_index as usize)
+        .and_then(|a| *a)
+        .ok_or(BioCommanderError::InvalidAction)?;
+    require!(
+        unit.is_ability_ready(ability_index as usize, game.turn_number),
+        BioCommanderError::AbilityOnCooldown
+    );
+    unit.start_cooldown(ability_index as usize, game.turn_number, ability.get_cooldown());
+
+    economy::pay(player, zone, ability.get_cost())?;
+
+    Ok(ability)
+}
+
+fn find_free_neighbour(zone: &Zone, x: u8, y: u8) -> Option<(u8, u8)> {
+    NEIGHBOURS.iter().find_map(|&(dx, dy)| {
+        let nx = x.checked_add_signed(dx).filter(|nx| *nx < 16)?;
+        let ny = y.checked_add_signed(dy).filter(|ny| *ny < 16)?;
+        zone.is_empty_cell(nx, ny).then_some((nx, ny))
+    })
+}
+
+// Enemies are told apart by the seat their cell records. Hidden units are
+// skipped. Units destroyed here keep an active component, see destroy_unit.
+fn damage_enemies_around(game: &mut Game, player: &Player, unit: &Unit, zone: &mut Zone, damage: u16) {
+    let opponent = game.get_opponent(&player.player_key).unwrap_or_default();
+    let min_x = unit.x.saturating_sub(AREA_RADIUS);
+    let max_x = (unit.x + AREA_RADIUS).min(15);
+    let min_y = unit.y.saturating_sub(AREA_RADIUS);
+    let max_y = (unit.y + AREA_RADIUS).min(15);
+
+    for x in min_x..=max_x {
+        for y in min_y..=max_y {
+            let health = match zone.cell(x, y) {
+                Some(CellContent::ImmuneCell { health, seat, .. }) | Some(CellContent::Pathogen { health, seat, .. })
+                    if seat != player.player_id && !zone.is_hidden(x, y, game.turn_number) => health,
+                _ => continue,
+            };
+            let health = health.saturating_sub(damage);
+            zone.set_unit_health(x, y, health);
+
+            if health == 0 {
+                destroy_unit(game, zone, x, y, &opponent);
+            }
+        }
+    }
+}
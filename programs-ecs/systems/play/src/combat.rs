@@ -0,0 +1,258 @@
+use bolt_lang::*;
+use game::Game;
+use grid::{CellContent, Zone};
+use players::Player;
+use unit::Unit;
+
+use crate::BioCommanderError;
+
+// Every hit that lands does at least this much damage, so heavily
+// fortified units can still be worn down
+pub const MIN_DAMAGE: u16 = 1;
+
+#[event]
+pub struct CombatResolved {
+    pub zone_id: u32,
+    pub attacker_id: u32,
+    pub target_id: u32,
+    pub attacker_owner: Pubkey,
+    pub target_owner: Pubkey,
+    pub damage: u16,
+    pub target_health: u16,
+    pub target_destroyed: bool,
+}
+
+pub fn calculate_damage(attacker: &Unit, attacker_player: &Player, target: &Unit, zone: &Zone) -> u16 {
+    let (attack_bonus, _, _) = attacker_player.get_faction_bonus();
+    let attack = attacker.attack.saturating_add(attack_bonus);
+    let defense = target.defense.saturating_add(zone.zone_type.get_defense_bonus());
+    attack.saturating_sub(defense).max(MIN_DAMAGE)
+}
+
+pub fn resolve_attack(
+    game: &Game,
+    attacker: &Unit,
+    attacker_player: &Player,
+    target: &mut Unit,
+    zone: &mut Zone,
+    target_x: u8,
+    target_y: u8,
+) -> Result<CombatResolved> {
+    // Validate target position
+    require!(target_x < 16 && target_y < 16, BioCommanderError::PositionOutOfBounds);
+
+    // Check range
+    let distance = (target_x as i16 - attacker.x as i16).abs() + (target_y as i16 - attacker.y as i16).abs();
+    require!(
+        distance <= attacker.unit_type.get_attack_range() as i16,
+        BioCommanderError::TargetOutOfRange
+    );
+
+    // The cell must hold a unit, and the target component must be that unit
+    let cell_health = match zone.cell(target_x, target_y) {
+        Some(CellContent::ImmuneCell { unit_id, health, .. }) | Some(CellContent::Pathogen { unit_id, health, .. })
+            if unit_id == target.unit_id => health,
+        Some(CellContent::ImmuneCell { .. }) | Some(CellContent::Pathogen { .. }) => {
+            return Err(BioCommanderError::UnitNotFound.into())
+        }
+        Some(_) => return Err(BioCommanderError::InvalidAction.into()),
+        None => return Err(BioCommanderError::NoTarget.into()),
+    };
+    require!(
+        target.is_active
+            && target.game_id == game.game_id
+            && target.zone_id == zone.zone_id
+            && (target.x, target.y) == (target_x, target_y),
+        BioCommanderError::UnitNotFound
+    );
+    require!(target.owner != attacker.owner, BioCommanderError::FriendlyFire);
+    require!(!target.is_stealthed(game.turn_number), BioCommanderError::TargetHidden);
+
+    // The grid is authoritative for health, area effects only touch the cell
+    target.health = cell_health;
+
+    let damage = calculate_damage(attacker, attacker_player, target, zone);
+    target.health = target.health.saturating_sub(damage);
+
+    // A destroyed unit is taken off the grid by the caller, see destroy_unit
+    let target_destroyed = target.health == 0;
+    if target_destroyed {
+        target.is_active = false;
+    } else {
+        zone.set_unit_health(target_x, target_y, target.health);
+    }
+
+    Ok(CombatResolved {
+        zone_id: zone.zone_id,
+        attacker_id: attacker.unit_id,
+        target_id: target.unit_id,
+        attacker_owner: attacker.owner,
+        target_owner: target.owner,
+        damage,
+        target_health: target.health,
+        target_destroyed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grid::ZoneType;
+    use players::Faction;
+    use unit::UnitType;
+
+    // An immune T cell on (4, 4) of a circulatory zone, facing a virus of the
+    // other player on (5, 4)
+    fn duel() -> (Game, Player, Unit, Unit, Zone) {
+        let game = Game { game_id: Pubkey::new_unique(), turn_number: 3, ..Game::default() };
+        let player = Player { player_key: Pubkey::new_unique(), ..Player::default() };
+        let attacker = Unit {
+            unit_id: 1,
+            game_id: game.game_id,
+            owner: player.player_key,
+            x: 4,
+            y: 4,
+            ..Unit::default()
+        };
+        let (health, attack, defense, movement_range, _) = UnitType::Virus.get_base_stats();
+        let target = Unit {
+            unit_id: 2,
+            game_id: game.game_id,
+            unit_type: UnitType::Virus,
+            owner: Pubkey::new_unique(),
+            x: 5,
+            y: 4,
+            health,
+            max_health: health,
+            attack,
+            defense,
+            movement_range,
+            ..Unit::default()
+        };
+        let mut zone = Zone { zone_type: ZoneType::Circulatory, ..Zone::default() };
+        zone.set_cell(5, 4, Some(CellContent::Pathogen { unit_id: 2, health, seat: 2 })).unwrap();
+        (game, player, attacker, target, zone)
+    }
+
+    fn attack(game: &Game, player: &Player, attacker: &Unit, target: &mut Unit, zone: &mut Zone, (x, y): (u8, u8)) -> Error {
+        resolve_attack(game, attacker, player, target, zone, x, y).err().unwrap()
+    }
+
+    #[test]
+    fn damage_adds_the_attackers_faction_bonus() {
+        let (_, mut player, attacker, target, zone) = duel();
+
+        // 15 attack against 5 defense, plus 2 for the immune system
+        assert_eq!(calculate_damage(&attacker, &player, &target, &zone), 12);
+        player.faction = Faction::Pathogen;
+        assert_eq!(calculate_damage(&attacker, &player, &target, &zone), 13);
+    }
+
+    #[test]
+    fn zones_add_their_defense_bonus() {
+        let (_, player, attacker, target, mut zone) = duel();
+        zone.zone_type = ZoneType::Barrier;
+
+        // 17 attack against 5 defense plus 5 for the barrier
+        assert_eq!(calculate_damage(&attacker, &player, &target, &zone), 7);
+    }
+
+    #[test]
+    fn hits_do_at_least_min_damage() {
+        let (_, player, attacker, mut target, zone) = duel();
+        target.defense = 100;
+
+        assert_eq!(calculate_damage(&attacker, &player, &target, &zone), MIN_DAMAGE);
+    }
+
+    #[test]
+    fn hits_update_the_target_and_its_cell() {
+        let (game, player, attacker, mut target, mut zone) = duel();
+
+        let result = resolve_attack(&game, &attacker, &player, &mut target, &mut zone, 5, 4).unwrap();
+
+        assert_eq!((result.damage, result.target_health, result.target_destroyed), (12, 28, false));
+        assert_eq!(target.health, 28);
+        assert!(zone.cell(5, 4) == Some(CellContent::Pathogen { unit_id: 2, health: 28, seat: 2 }));
+    }
+
+    #[test]
+    fn destroyed_targets_are_deactivated() {
+        let (game, player, attacker, mut target, mut zone) = duel();
+        zone.set_unit_health(5, 4, 12);
+
+        let result = resolve_attack(&game, &attacker, &player, &mut target, &mut zone, 5, 4).unwrap();
+
+        assert!(result.target_destroyed);
+        assert!(!target.is_active);
+    }
+
+    #[test]
+    fn targets_off_the_grid_are_rejected() {
+        let (game, player, attacker, mut target, mut zone) = duel();
+        let error = attack(&game, &player, &attacker, &mut target, &mut zone, (16, 4));
+        assert_eq!(error, BioCommanderError::PositionOutOfBounds.into());
+    }
+
+    #[test]
+    fn targets_out_of_range_are_rejected() {
+        let (game, player, attacker, mut target, mut zone) = duel();
+        let error = attack(&game, &player, &attacker, &mut target, &mut zone, (6, 4));
+        assert_eq!(error, BioCommanderError::TargetOutOfRange.into());
+    }
+
+    #[test]
+    fn empty_cells_are_rejected() {
+        let (game, player, attacker, mut target, mut zone) = duel();
+        let error = attack(&game, &player, &attacker, &mut target, &mut zone, (4, 5));
+        assert_eq!(error, BioCommanderError::NoTarget.into());
+    }
+
+    #[test]
+    fn cells_without_a_unit_are_rejected() {
+        let (game, player, attacker, mut target, mut zone) = duel();
+        zone.set_cell(4, 5, Some(CellContent::Obstacle)).unwrap();
+        let error = attack(&game, &player, &attacker, &mut target, &mut zone, (4, 5));
+        assert_eq!(error, BioCommanderError::InvalidAction.into());
+    }
+
+    #[test]
+    fn target_components_of_another_unit_are_rejected() {
+        let (game, player, attacker, mut target, mut zone) = duel();
+        zone.set_cell(4, 5, Some(CellContent::Pathogen { unit_id: 3, health: 40, seat: 2 })).unwrap();
+        let error = attack(&game, &player, &attacker, &mut target, &mut zone, (4, 5));
+        assert_eq!(error, BioCommanderError::UnitNotFound.into());
+    }
+
+    #[test]
+    fn stale_target_components_are_rejected() {
+        let stale: [fn(&mut Unit); 4] = [
+            |target| target.is_active = false,
+            |target| target.game_id = Pubkey::new_unique(),
+            |target| target.zone_id = 7,
+            |target| target.y = 5,
+        ];
+        for make_stale in stale {
+            let (game, player, attacker, mut target, mut zone) = duel();
+            make_stale(&mut target);
+            let error = attack(&game, &player, &attacker, &mut target, &mut zone, (5, 4));
+            assert_eq!(error, BioCommanderError::UnitNotFound.into());
+        }
+    }
+
+    #[test]
+    fn own_units_are_rejected() {
+        let (game, player, attacker, mut target, mut zone) = duel();
+        target.owner = attacker.owner;
+        let error = attack(&game, &player, &attacker, &mut target, &mut zone, (5, 4));
+        assert_eq!(error, BioCommanderError::FriendlyFire.into());
+    }
+
+    #[test]
+    fn hidden_targets_are_rejected() {
+        let (game, player, attacker, mut target, mut zone) = duel();
+        target.stealth_until_turn = game.turn_number + 1;
+        let error = attack(&game, &player, &attacker, &mut target, &mut zone, (5, 4));
+        assert_eq!(error, BioCommanderError::TargetHidden.into());
+    }
+}
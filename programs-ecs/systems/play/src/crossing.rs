@@ -0,0 +1,166 @@
+use bolt_lang::*;
+use bio_rules::costs;
+use game::Game;
+use grid::{Zone, GRID_SIZE};
+use players::Player;
+use unit::{SpecialAbility, Unit};
+
+use crate::{abilities, require_in_game, spend_action, start_action, validate_unit, ActionType, BioCommanderError};
+
+const LAST: u8 = GRID_SIZE as u8 - 1;
+
+// Sides of a zone, in the order of Zone.connected_zones. North is towards
+// y = 0, both on the map and inside a zone.
+#[derive(AnchorSerialize, AnchorDeserialize, serde::Deserialize, Clone, Copy, PartialEq)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+
+    // Whether (x, y) lies on the edge of the zone on this side
+    pub fn is_on_edge(self, x: u8, y: u8) -> bool {
+        match self {
+            Direction::North => y == 0,
+            Direction::East => x == LAST,
+            Direction::South => y == LAST,
+            Direction::West => x == 0,
+        }
+    }
+
+    // Number of cells between (x, y) and the edge of the zone on this side
+    pub fn edge_distance(self, x: u8, y: u8) -> u8 {
+        match self {
+            Direction::North => y,
+            Direction::East => LAST - x,
+            Direction::South => LAST - y,
+            Direction::West => x,
+        }
+    }
+
+    // Cell of the neighbouring zone on this side that a unit leaving from
+    // (x, y) arrives on: the facing cell across the border
+    pub fn arrival(self, x: u8, y: u8) -> (u8, u8) {
+        match self {
+            Direction::North => (x, LAST),
+            Direction::East => (0, y),
+            Direction::South => (x, 0),
+            Direction::West => (LAST, y),
+        }
+    }
+}
+
+/// Moves `unit` from the `direction` edge of `source_zone` to the facing cell
+/// of `destination_zone`. The zones must list each other in
+/// `connected_zones`, and the crossing uses up the unit's move for the turn.
+/// The player pays energy for the destination's movement cost.
+pub fn move_unit_across_zones(
+    game: &mut Game,
+    player: &mut Player,
+    source_zone: &mut Zone,
+    destination_zone: &mut Zone,
+    unit: &mut Unit,
+    direction: Direction,
+) -> Result<()> {
+    start_action(game, player)?;
+    require_in_game(game, source_zone)?;
+    require_in_game(game, destination_zone)?;
+    validate_unit(game, unit, source_zone, &player.player_key)?;
+    require!(direction.is_on_edge(unit.x, unit.y), BioCommanderError::NotOnBorder);
+
+    spend_action(unit, game.turn_number, ActionType::MoveUnit)?;
+
+    let cost = costs::crossing_cost(destination_zone.zone_type.rules()).ok_or(ProgramError::ArithmeticOverflow)?;
+    require!(
+        player.can_afford(cost.0, cost.1, cost.2, cost.3),
+        BioCommanderError::InsufficientResources
+    );
+    player.spend_resources(cost.0, cost.1, cost.2, cost.3);
+
+    cross(source_zone, destination_zone, unit, direction)?;
+
+    let standing = player.standing(game);
+    game.check_victory(&[standing]);
+
+    Ok(())
+}
+
+/// Uses the unit's Infiltration ability to slip into `destination_zone` from
+/// up to twice its movement range away from the `direction` edge of
+/// `source_zone`. It lands on the cell facing its row or column across the
+/// border, as a crossing from the edge would, but leaves the unit's move for
+/// the turn unused: the ability's cost and cooldown apply instead of the
+/// crossing cost.
+pub fn infiltrate_across_zones(
+    game: &mut Game,
+    player: &mut Player,
+    source_zone: &mut Zone,
+    destination_zone: &mut Zone,
+    unit: &mut Unit,
+    direction: Direction,
+) -> Result<()> {
+    start_action(game, player)?;
+    require_in_game(game, source_zone)?;
+    require_in_game(game, destination_zone)?;
+    validate_unit(game, unit, source_zone, &player.player_key)?;
+    require!(
+        direction.edge_distance(unit.x, unit.y) <= unit.movement_range.saturating_mul(2),
+        BioCommanderError::NotOnBorder
+    );
+
+    let ability_index = unit
+        .special_abilities
+        .iter()
+        .position(|ability| *ability == Some(SpecialAbility::Infiltration))
+        .ok_or(BioCommanderError::InvalidAction)?;
+    spend_action(unit, game.turn_number, ActionType::UseSpecialAbility)?;
+    abilities::ready_ability(game, player, unit, source_zone, ability_index as u8)?;
+
+    cross(source_zone, destination_zone, unit, direction)?;
+
+    let standing = player.standing(game);
+    game.check_victory(&[standing]);
+
+    Ok(())
+}
+
+// Takes `unit` off `source_zone` and puts it on the cell of
+// `destination_zone` facing it across the `direction` border
+fn cross(source_zone: &mut Zone, destination_zone: &mut Zone, unit: &mut Unit, direction: Direction) -> Result<()> {
+    require!(
+        source_zone.connected_zones[direction.index()] == Some(destination_zone.zone_id)
+            && destination_zone.connected_zones[direction.opposite().index()] == Some(source_zone.zone_id),
+        BioCommanderError::ZonesNotConnected
+    );
+
+    let (x, y) = direction.arrival(unit.x, unit.y);
+    require!(destination_zone.is_empty_cell(x, y), BioCommanderError::PositionOccupied);
+
+    let content = source_zone.cell(unit.x, unit.y);
+    source_zone.clear_cell(unit.x, unit.y);
+    source_zone.unit_count = source_zone.unit_count.saturating_sub(1);
+
+    unit.zone_id = destination_zone.zone_id;
+    unit.x = x;
+    unit.y = y;
+    destination_zone.set_cell(x, y, content)?;
+    destination_zone.hide_unit(x, y, unit.stealth_until_turn);
+    destination_zone.unit_count += 1;
+
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use bolt_lang::*;
+use bio_rules::costs::Cost;
+use game::Game;
+use grid::{Zone, POOL_CAPS};
+use players::Player;
+
+use crate::{require_in_game, start_action, BioCommanderError};
+
+// Where TransferResources moves resources. `zone` is always one of the
+// player's zones, and resources always leave or reach its pools.
+#[derive(AnchorSerialize, AnchorDeserialize, serde::Deserialize, Clone, Copy, PartialEq)]
+pub enum Transfer {
+    ToZone,       // From the pools of `zone` to those of `other_zone`
+    ToReserves,   // From the pools of `zone` to the player's reserves
+    FromReserves, // From the player's reserves to the pools of `zone`
+}
+
+/// Pays `cost` for an action in `zone`: from the zone's pools as far as they
+/// go when the player controls the zone, and the rest from the reserves.
+pub(crate) fn pay(player: &mut Player, zone: &mut Zone, cost: Cost) -> Result<()> {
+    let cost = [cost.0, cost.1, cost.2, cost.3];
+    let mut pools = zone.pools();
+    if zone.owner != player.player_key {
+        pools = [0; 4];
+    }
+
+    let from_pools: [u32; 4] = std::array::from_fn(|index| cost[index].min(pools[index] as u64) as u32);
+    let rest: [u64; 4] = std::array::from_fn(|index| cost[index] - from_pools[index] as u64);
+    require!(
+        player.can_afford(rest[0], rest[1], rest[2], rest[3]),
+        BioCommanderError::InsufficientResources
+    );
+    player.spend_resources(rest[0], rest[1], rest[2], rest[3]);
+
+    if zone.owner == player.player_key {
+        zone.set_pools(std::array::from_fn(|index| pools[index] - from_pools[index]));
+    }
+    Ok(())
+}
+
+/// Moves `amounts`, as [energy, antibodies, stem_cells, nutrients], between
+/// pools of the player's zones and their reserves, during their turn:
+/// - ToZone needs the two zones linked to each other in `connected_zones`,
+///   and `other_zone` held by the player as well.
+/// - ToReserves and FromReserves need `zone` to be the player's starting zone,
+///   where the reserves are kept, or linked to it as `other_zone`.
+///
+/// The two zones must be different components, as only one copy of a
+/// component passed twice would be written back. No pool may end up above
+/// POOL_CAPS.
+pub fn transfer_resources(
+    game: &mut Game,
+    player: &mut Player,
+    zone: &mut Zone,
+    other_zone: &mut Zone,
+    transfer: Transfer,
+    amounts: [u32; 4],
+) -> Result<()> {
+    start_action(game, player)?;
+    require_in_game(game, zone)?;
+    require_in_game(game, other_zone)?;
+    require!(zone.owner == player.player_key, BioCommanderError::ZoneNotControlled);
+    require!(zone.zone_id != other_zone.zone_id, BioCommanderError::InvalidAction);
+
+    let linked = zone.connected_zones.contains(&Some(other_zone.zone_id))
+        && other_zone.connected_zones.contains(&Some(zone.zone_id));
+    let [energy, antibodies, stem_cells, nutrients] = amounts.map(|amount| amount as u64);
+
+    match transfer {
+        Transfer::ToZone => {
+            require!(other_zone.owner == player.player_key, BioCommanderError::ZoneNotControlled);
+            require!(linked, BioCommanderError::ZonesNotConnected);
+            take_from_pools(zone, amounts)?;
+            add_to_pools(other_zone, amounts)?;
+        }
+        Transfer::ToReserves => {
+            require!(
+                is_starting_zone(zone, player) || (linked && is_starting_zone(other_zone, player)),
+                BioCommanderError::ZonesNotConnected
+            );
+            take_from_pools(zone, amounts)?;
+            player.add_resources(energy, antibodies, stem_cells, nutrients);
+        }
+        Transfer::FromReserves => {
+            require!(
+                is_starting_zone(zone, player) || (linked && is_starting_zone(other_zone, player)),
+                BioCommanderError::ZonesNotConnected
+            );
+            require!(
+                player.can_afford(energy, antibodies, stem_cells, nutrients),
+                BioCommanderError::InsufficientResources
+            );
+            player.spend_resources(energy, antibodies, stem_cells, nutrients);
+            add_to_pools(zone, amounts)?;
+        }
+    }
+
+    Ok(())
+}
+
+// The zone a player joined with, while they still hold it
+fn is_starting_zone(zone: &Zone, player: &Player) -> bool {
+    zone.owner == player.player_key && zone.zone_id + 1 == player.player_id as u32
+}
+
+fn take_from_pools(zone: &mut Zone, amounts: [u32; 4]) -> Result<()> {
+    let pools = zone.pools();
+    require!(
+        (0..4).all(|index| pools[index] >= amounts[index]),
+        BioCommanderError::InsufficientResources
+    );
+    zone.set_pools(std::array::from_fn(|index| pools[index] - amounts[index]));
+    Ok(())
+}
+
+fn add_to_pools(zone: &mut Zone, amounts: [u32; 4]) -> Result<()> {
+    let pools = zone.pools();
+    require!(
+        (0..4).all(|index| pools[index].checked_add(amounts[index]).is_some_and(|pool| pool <= POOL_CAPS[index])),
+        BioCommanderError::PoolFull
+    );
+    zone.set_pools(std::array::from_fn(|index| pools[index] + amounts[index]));
+    Ok(())
+}
@@ -1,8 +1,17 @@
 use bolt_lang::*;
-use grid::{Zone, ZoneType, CellContent};
-use players::{Player, Faction};
-use game::{Game, GameState};
-use unit::{Unit, UnitType, SpecialAbility};
+use grid::{Zone, CellContent, ResourceType, POOL_CAPS};
+use bio_rules::costs;
+use players::Player;
+use game::Game;
+use unit::{Unit, UnitType};
+
+mod abilities;
+mod combat;
+mod crossing;
+mod economy;
+
+pub use crossing::{infiltrate_across_zones, move_unit_across_zones, Direction};
+pub use economy::{transfer_resources, Transfer};
 
 declare_id!("EFLfG5icLgcUYwuSnuScoYptcrgh8WYLHx33M4wvTPFv");
 
@@ -30,48 +39,70 @@ pub enum BioCommanderError {
     ZoneNotControlled,
     #[msg("Invalid action.")]
     InvalidAction,
+    #[msg("Unit already spawned.")]
+    UnitAlreadySpawned,
+    #[msg("No unit ids left.")]
+    UnitIdsExhausted,
+    #[msg("Unit is not owned by the player.")]
+    NotUnitOwner,
+    #[msg("Unit is not in this zone.")]
+    UnitNotInZone,
+    #[msg("No unit at the target position.")]
+    NoTarget,
+    #[msg("Target out of range.")]
+    TargetOutOfRange,
+    #[msg("Cannot attack own units.")]
+    FriendlyFire,
+    #[msg("Target is hidden.")]
+    TargetHidden,
+    #[msg("Unit has no action points left this turn.")]
+    NoActionPoints,
+    #[msg("Unit already moved this turn.")]
+    AlreadyMoved,
+    #[msg("Unit already attacked this turn.")]
+    AlreadyAttacked,
+    #[msg("Ability is on cooldown.")]
+    AbilityOnCooldown,
+    #[msg("No free cell around the unit.")]
+    NoFreeCell,
+    #[msg("Zones are not connected in that direction.")]
+    ZonesNotConnected,
+    #[msg("Unit is not on the border facing that zone.")]
+    NotOnBorder,
+    #[msg("No resource node at the target position.")]
+    NoResource,
+    #[msg("Zone pool cannot hold that much.")]
+    PoolFull,
+    #[msg("Zone belongs to another game.")]
+    ZoneNotInGame,
 }
 
 #[system]
 pub mod play {
 
     pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
-        let game = &mut ctx.accounts.game;
-        let player = &mut ctx.accounts.player;
-        let zone = &mut ctx.accounts.zone;
         let authority = *ctx.accounts.authority.key;
+        let now = Clock::get()?.unix_timestamp;
 
-        // Validate player is in game and it's their turn
-        require!(game.is_player_turn(&authority), BioCommanderError::NotPlayersTurn);
-        require!(game.is_game_active(), BioCommanderError::NotActive);
-        require!(player.player_key == authority, BioCommanderError::NotInGame);
-
-        match args.action {
-            ActionType::SpawnUnit => {
-                spawn_unit(player, zone, args.unit_type, args.x, args.y)?;
-            }
-            ActionType::MoveUnit => {
-                if let Some(unit) = &mut ctx.accounts.unit {
-                    move_unit(unit, zone, args.x, args.y)?;
-                }
-            }
-            ActionType::AttackPosition => {
-                if let Some(unit) = &ctx.accounts.unit {
-                    attack_position(unit, zone, args.x, args.y)?;
-                }
-            }
-            ActionType::UseSpecialAbility => {
-                if let Some(unit) = &mut ctx.accounts.unit {
-                    use_special_ability(unit, player, zone, args.ability_index)?;
-                }
-            }
-            ActionType::EndTurn => {
-                end_turn(game, player, zone)?;
-            }
-        }
+        // Validate player is acting for themselves
+        require!(ctx.accounts.player.player_key == authority, BioCommanderError::NotInGame);
 
-        // Check win conditions
-        check_win_conditions(game, player)?;
+        let command = Command {
+            action: args.action,
+            x: args.x,
+            y: args.y,
+            unit_type: args.unit_type,
+            ability_index: args.ability_index,
+        };
+        apply_action(
+            &mut ctx.accounts.game,
+            &mut ctx.accounts.player,
+            &mut ctx.accounts.zone,
+            &mut ctx.accounts.unit,
+            &mut ctx.accounts.target_unit,
+            command,
+            now,
+        )?;
 
         Ok(ctx.accounts)
     }
@@ -81,7 +112,8 @@ pub mod play {
         pub game: Game,
         pub player: Player,
         pub zone: Zone,
-        pub unit: Option<Unit>,
+        pub unit: Unit, // Freshly initialized component for SpawnUnit, the acting unit otherwise
+        pub target_unit: Unit, // Unit under attack for AttackPosition, fresh component for a Replication child
     }
 
     #[arguments]
@@ -94,19 +126,159 @@ pub mod play {
     }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+#[derive(AnchorSerialize, AnchorDeserialize, serde::Deserialize, Clone, Copy)]
 pub enum ActionType {
     SpawnUnit,
     MoveUnit,
     AttackPosition,
     UseSpecialAbility,
     EndTurn,
+    Harvest,
 }
 
-fn spawn_unit(player: &mut Player, zone: &mut Zone, unit_type_index: u8, x: u8, y: u8) -> Result<()> {
+// The play arguments, as taken by apply_action
+#[derive(Clone, Copy)]
+pub struct Command {
+    pub action: ActionType,
+    pub x: u8,
+    pub y: u8,
+    pub unit_type: u8,
+    pub ability_index: u8,
+}
+
+// Runs one play action for `player`, who must already be checked against the
+// transaction signer
+pub fn apply_action(
+    game: &mut Game,
+    player: &mut Player,
+    zone: &mut Zone,
+    unit: &mut Unit,
+    target_unit: &mut Unit,
+    command: Command,
+    now: i64,
+) -> Result<()> {
+    let authority = player.player_key;
+    start_action(game, player)?;
+    require_in_game(game, zone)?;
+
+    // Only one copy of a component passed twice is written back, which would
+    // undo the acting unit's spent action points and cooldowns
+    require!(
+        target_unit.unit_id != unit.unit_id || unit.unit_id == 0,
+        BioCommanderError::InvalidAction
+    );
+
+    match command.action {
+        ActionType::SpawnUnit => {
+            spawn_unit(game, player, zone, unit, command.unit_type, command.x, command.y)?;
+        }
+        ActionType::MoveUnit => {
+            validate_unit(game, unit, zone, &authority)?;
+            spend_action(unit, game.turn_number, command.action)?;
+            move_unit(unit, zone, command.x, command.y)?;
+        }
+        ActionType::AttackPosition => {
+            validate_unit(game, unit, zone, &authority)?;
+            spend_action(unit, game.turn_number, command.action)?;
+            attack_position(game, player, unit, target_unit, zone, command.x, command.y)?;
+        }
+        ActionType::UseSpecialAbility => {
+            validate_unit(game, unit, zone, &authority)?;
+            spend_action(unit, game.turn_number, command.action)?;
+            abilities::use_special_ability(
+                game,
+                player,
+                unit,
+                target_unit,
+                zone,
+                command.ability_index,
+                (command.x, command.y),
+            )?;
+        }
+        ActionType::EndTurn => {
+            end_turn(game, player, zone, [unit, target_unit], now)?;
+        }
+        ActionType::Harvest => {
+            validate_unit(game, unit, zone, &authority)?;
+            spend_action(unit, game.turn_number, command.action)?;
+            harvest(player, unit, zone, command.x, command.y)?;
+        }
+    }
+
+    // Check win conditions. The opponent's Player would be a sixth component,
+    // so a kill that eliminates them is settled on their next action or by
+    // claim-timeout, which see both players.
+    let standing = player.standing(game);
+    game.check_victory(&[standing]);
+
+    Ok(())
+}
+
+fn start_action(game: &mut Game, player: &mut Player) -> Result<()> {
+    // Validate player is in game and it's their turn
+    require!(game.is_player_turn(&player.player_key), BioCommanderError::NotPlayersTurn);
+    require!(game.is_game_active(), BioCommanderError::NotActive);
+
+    // Settle units this player lost during the opponent's turn
+    let losses = game.take_unit_losses(&player.player_key);
+    player.total_units = player.total_units.saturating_sub(losses);
+
+    Ok(())
+}
+
+// Zones carry the id of the game that placed them, so the zones of another
+// game cannot pay for, host or end a turn of this one
+fn require_in_game(game: &Game, zone: &Zone) -> Result<()> {
+    require!(zone.game_id == game.game_id, BioCommanderError::ZoneNotInGame);
+    Ok(())
+}
+
+fn validate_unit(game: &Game, unit: &mut Unit, zone: &Zone, authority: &Pubkey) -> Result<()> {
+    // An unbound or dead unit component cannot act, nor can a unit of another game
+    require!(unit.owner != Pubkey::default() && unit.is_active, BioCommanderError::UnitNotFound);
+    require!(unit.game_id == game.game_id, BioCommanderError::UnitNotFound);
+    require!(unit.owner == *authority, BioCommanderError::NotUnitOwner);
+    require!(unit.zone_id == zone.zone_id, BioCommanderError::UnitNotInZone);
+    
+    // The grid cell must still hold this unit
+    let cell_health = match zone.cell(unit.x, unit.y) {
+        Some(CellContent::ImmuneCell { unit_id, health, .. }) | Some(CellContent::Pathogen { unit_id, health, .. })
+            if unit_id == unit.unit_id => health,
+        _ => return Err(BioCommanderError::UnitNotInZone.into()),
+    };
+    
+    // Pick up damage taken from area effects, which only touch the grid
+    unit.health = cell_health;
+    
+    Ok(())
+}
+
+fn spend_action(unit: &mut Unit, turn_number: u32, action: ActionType) -> Result<()> {
+    unit.refresh_actions(turn_number);
+    require!(unit.action_points > 0, BioCommanderError::NoActionPoints);
+    
+    // At most one move and one attack per turn
+    match action {
+        ActionType::MoveUnit => {
+            require!(!unit.has_moved, BioCommanderError::AlreadyMoved);
+            unit.has_moved = true;
+        }
+        ActionType::AttackPosition => {
+            require!(!unit.has_attacked, BioCommanderError::AlreadyAttacked);
+            unit.has_attacked = true;
+        }
+        _ => {}
+    }
+    
+    unit.action_points -= 1;
+    
+    Ok(())
+}
+
+fn spawn_unit(game: &mut Game, player: &mut Player, zone: &mut Zone, unit: &mut Unit, unit_type_index: u8, x: u8, y: u8) -> Result<()> {
     // Validate position
     require!(x < 16 && y < 16, BioCommanderError::PositionOutOfBounds);
-    require!(zone.grid[x as usize][y as usize].is_none(), BioCommanderError::PositionOccupied);
+    require!(zone.is_empty_cell(x, y), BioCommanderError::PositionOccupied);
     
     // Validate zone ownership
     require!(zone.owner == player.player_key, BioCommanderError::ZoneNotControlled);
@@ -115,40 +287,47 @@ fn spawn_unit(player: &mut Player, zone: &mut Zone, unit_type_index: u8, x: u8,
     require!(player.is_unit_unlocked(unit_type_index as usize), BioCommanderError::UnitTypeNotUnlocked);
     
     // Get unit type and costs
-    let unit_type = match unit_type_index {
-        0 => UnitType::TCell,
-        1 => UnitType::BCell,
-        2 => UnitType::Macrophage,
-        3 => UnitType::NeutrophilCell,
-        4 => UnitType::DendriticCell,
-        5 => UnitType::NaturalKillerCell,
-        6 => UnitType::Virus,
-        7 => UnitType::Bacteria,
-        8 => UnitType::Fungus,
-        9 => UnitType::Parasite,
-        10 => UnitType::CancerCell,
-        11 => UnitType::Toxin,
-        _ => return Err(BioCommanderError::InvalidAction.into()),
-    };
+    let unit_type: UnitType = bio_rules::UnitType::from_index(unit_type_index)
+        .ok_or(BioCommanderError::InvalidAction)?
+        .into();
+    
+    let spawn_cost = costs::spawn_cost(unit_type.rules(), zone.zone_type.rules()).ok_or(ProgramError::ArithmeticOverflow)?;
+    
+    // The zone's pools pay first, the player's reserves the rest
+    economy::pay(player, zone, spawn_cost)?;
+    
+    bind_unit(game, player, zone, unit, unit_type, x, y)
+}
+
+// Binds a freshly initialized unit component and places it on the grid
+fn bind_unit(game: &mut Game, player: &mut Player, zone: &mut Zone, unit: &mut Unit, unit_type: UnitType, x: u8, y: u8) -> Result<()> {
+    // The unit component must be freshly initialized, not bound to an existing unit
+    require!(unit.owner == Pubkey::default(), BioCommanderError::UnitAlreadySpawned);
     
     let (health, attack, defense, movement_range, energy_cost) = unit_type.get_base_stats();
-    let spawn_cost = calculate_spawn_cost(&unit_type, &zone.zone_type);
     
-    // Check if player can afford the unit
-    require!(
-        player.can_afford(spawn_cost.0, spawn_cost.1, spawn_cost.2, spawn_cost.3),
-        BioCommanderError::InsufficientResources
-    );
+    unit.unit_id = game.allocate_unit_id().ok_or(BioCommanderError::UnitIdsExhausted)?;
+    unit.game_id = game.game_id;
+    unit.unit_type = unit_type;
+    unit.zone_id = zone.zone_id;
+    unit.x = x;
+    unit.y = y;
+    unit.health = health;
+    unit.max_health = health;
+    unit.attack = attack;
+    unit.defense = defense;
+    unit.movement_range = movement_range;
+    unit.owner = player.player_key;
+    unit.special_abilities = unit_type.get_default_abilities();
+    unit.is_active = true;
+    unit.energy_cost = energy_cost;
     
-    // Deduct resources
-    player.spend_resources(spawn_cost.0, spawn_cost.1, spawn_cost.2, spawn_cost.3);
+    // Units act from their owner's next turn
+    unit.action_turn = game.turn_number;
+    unit.action_points = 0;
     
     // Create unit on the grid
-    let unit_id = zone.unit_count as u32 + (zone.zone_id * 1000); // Simple ID generation
-    zone.grid[x as usize][y as usize] = Some(match unit_type.is_immune_cell() {
-        true => CellContent::ImmuneCell { unit_id, health },
-        false => CellContent::Pathogen { unit_id, health },
-    });
+    zone.set_cell(x, y, Some(unit_cell_content(unit, player.player_id)))?;
     
     zone.unit_count += 1;
     player.total_units += 1;
@@ -157,135 +336,296 @@ fn spawn_unit(player: &mut Player, zone: &mut Zone, unit_type_index: u8, x: u8,
 }
 
 fn move_unit(unit: &mut Unit, zone: &mut Zone, new_x: u8, new_y: u8) -> Result<()> {
-    // Validate new position
-    require!(new_x < 16 && new_y < 16, BioCommanderError::PositionOutOfBounds);
-    require!(zone.grid[new_x as usize][new_y as usize].is_none(), BioCommanderError::PositionOccupied);
-    
     // Calculate movement distance
-    let distance = ((new_x as i16 - unit.x as i16).abs() + (new_y as i16 - unit.y as i16).abs()) as u8;
-    require!(distance <= unit.movement_range, BioCommanderError::InvalidMove);
+    let distance = (new_x as i16 - unit.x as i16).abs() + (new_y as i16 - unit.y as i16).abs();
+    require!(distance <= unit.movement_range as i16, BioCommanderError::InvalidMove);
     
-    // Clear old position
-    zone.grid[unit.x as usize][unit.y as usize] = None;
+    relocate_unit(unit, zone, new_x, new_y)
+}
+
+fn relocate_unit(unit: &mut Unit, zone: &mut Zone, new_x: u8, new_y: u8) -> Result<()> {
+    // Validate new position
+    require!(new_x < 16 && new_y < 16, BioCommanderError::PositionOutOfBounds);
+    require!(zone.is_empty_cell(new_x, new_y), BioCommanderError::PositionOccupied);
     
-    // Set new position
-    let cell_content = match unit.unit_type.is_immune_cell() {
-        true => CellContent::ImmuneCell { unit_id: unit.unit_id, health: unit.health },
-        false => CellContent::Pathogen { unit_id: unit.unit_id, health: unit.health },
-    };
-    zone.grid[new_x as usize][new_y as usize] = Some(cell_content);
+    // Clear old position, keeping the cell's owner seat
+    let content = zone.cell(unit.x, unit.y);
+    zone.clear_cell(unit.x, unit.y);
     
     // Update unit position
     unit.x = new_x;
     unit.y = new_y;
     
+    // Set new position, still hidden if the unit was
+    zone.set_cell(new_x, new_y, content)?;
+    zone.hide_unit(new_x, new_y, unit.stealth_until_turn);
+    
     Ok(())
 }
 
-fn attack_position(unit: &Unit, zone: &mut Zone, target_x: u8, target_y: u8) -> Result<()> {
-    // Validate target position
-    require!(target_x < 16 && target_y < 16, BioCommanderError::PositionOutOfBounds);
-    
-    // Check if there's a target at the position
-    if let Some(target) = &mut zone.grid[target_x as usize][target_y as usize] {
-        match target {
-            CellContent::ImmuneCell { health, .. } | CellContent::Pathogen { health, .. } => {
-                // Calculate damage (simplified combat)
-                let damage = unit.attack.saturating_sub(zone.zone_type.get_defense_bonus());
-                *health = health.saturating_sub(damage);
-                
-                // Remove unit if health reaches 0
-                if *health == 0 {
-                    zone.grid[target_x as usize][target_y as usize] = None;
-                    zone.unit_count = zone.unit_count.saturating_sub(1);
-                }
-            }
-            _ => return Err(BioCommanderError::InvalidAction.into()),
-        }
+// `seat` is the owner's player_id
+fn unit_cell_content(unit: &Unit, seat: u8) -> CellContent {
+    match unit.unit_type.is_immune_cell() {
+        true => CellContent::ImmuneCell { unit_id: unit.unit_id, health: unit.health, seat },
+        false => CellContent::Pathogen { unit_id: unit.unit_id, health: unit.health, seat },
+    }
+}
+
+fn attack_position(
+    game: &mut Game,
+    player: &Player,
+    unit: &mut Unit,
+    target_unit: &mut Unit,
+    zone: &mut Zone,
+    target_x: u8,
+    target_y: u8,
+) -> Result<()> {
+    let result = combat::resolve_attack(game, unit, player, target_unit, zone, target_x, target_y)?;
+    
+    // Attacking gives away a hidden unit
+    unit.stealth_until_turn = 0;
+    zone.hide_unit(unit.x, unit.y, 0);
+    
+    if result.target_destroyed {
+        destroy_unit(game, zone, target_x, target_y, &result.target_owner);
     }
     
+    emit!(result);
+    
     Ok(())
 }
 
-fn use_special_ability(unit: &mut Unit, player: &mut Player, zone: &mut Zone, ability_index: u8) -> Result<()> {
-    if let Some(ability) = unit.special_abilities.get(ability_index as usize).and_then(|a| *a) {
-        match ability {
-            SpecialAbility::AntibodyProduction => {
-                player.add_resources(0, 50, 0, 0);
-            }
-            SpecialAbility::Phagocytosis => {
-                // Heal unit and gain resources
-                unit.health = (unit.health + 20).min(unit.max_health);
-                player.add_resources(10, 0, 0, 5);
-            }
-            SpecialAbility::Replication => {
-                // Spawn a new unit nearby (simplified)
-                zone.unit_count += 1;
-                player.total_units += 1;
-            }
-            SpecialAbility::ZoneHealing => {
-                // Restore zone resources
-                zone.energy = (zone.energy + 50).min(1000);
-                zone.nutrients = (zone.nutrients + 30).min(1000);
-            }
-            _ => {} // Other abilities can be implemented later
-        }
+/// Takes the destroyed unit on (x, y) off the grid and counts it against
+/// `owner`, whose Player settles the loss the next time they act. Every kill
+/// goes through here: combat, area effects and conquest. Only combat has the
+/// unit's component at hand to mark it inactive. Elsewhere the component stays
+/// active, but it can no longer act or be attacked, as both require the grid
+/// cell to still hold the unit.
+pub fn destroy_unit(game: &mut Game, zone: &mut Zone, x: u8, y: u8, owner: &Pubkey) {
+    zone.clear_cell(x, y);
+    zone.unit_count = zone.unit_count.saturating_sub(1);
+    game.record_unit_loss(owner);
+}
+
+// Moves up to HARVEST_AMOUNT from the resource node on (x, y) into the
+// player's reserves, clearing the cell once the node runs dry. A cell holds
+// either a unit or a node, so the node must be next to the unit.
+fn harvest(player: &mut Player, unit: &Unit, zone: &mut Zone, x: u8, y: u8) -> Result<()> {
+    require!(x < 16 && y < 16, BioCommanderError::PositionOutOfBounds);
+    let distance = (x as i16 - unit.x as i16).abs() + (y as i16 - unit.y as i16).abs();
+    require!(distance <= 1, BioCommanderError::TargetOutOfRange);
+
+    let Some(CellContent::Resource { resource_type, amount }) = zone.cell(x, y) else {
+        return Err(BioCommanderError::NoResource.into());
+    };
+    let harvested = amount.min(bio_rules::HARVEST_AMOUNT);
+    match amount - harvested {
+        0 => zone.clear_cell(x, y),
+        left => zone.set_cell(x, y, Some(CellContent::Resource { resource_type, amount: left }))?,
     }
-    
+
+    let harvested = harvested as u64;
+    match resource_type {
+        ResourceType::Energy => player.add_resources(harvested, 0, 0, 0),
+        ResourceType::Antibodies => player.add_resources(0, harvested, 0, 0),
+        ResourceType::StemCells => player.add_resources(0, 0, harvested, 0),
+        ResourceType::Nutrients => player.add_resources(0, 0, 0, harvested),
+    }
+
     Ok(())
 }
 
-fn end_turn(game: &mut Game, player: &mut Player, zone: &mut Zone) -> Result<()> {
-    // Generate resources for controlled zones
-    let (energy_gen, antibody_gen, stem_gen, nutrient_gen) = zone.zone_type.get_resource_generation();
-    
-    if zone.owner == player.player_key {
-        player.add_resources(
-            energy_gen as u64,
-            antibody_gen as u64,
-            stem_gen as u64,
-            nutrient_gen as u64,
-        );
+// `units` are the unit components passed with the turn, whose action budgets
+// are reset for their owner's next turn. Every other unit is reset the first
+// time it acts, see Unit::refresh_actions.
+fn end_turn(game: &mut Game, player: &mut Player, zone: &mut Zone, units: [&mut Unit; 2], now: i64) -> Result<()> {
+    // Only the zone passed in is written, so only its local pools grow. Every
+    // other controlled zone generates into the reserves.
+    let pooled = zone.owner == player.player_key;
+    let (energy, antibodies, stem_cells, nutrients) = player.get_turn_income(pooled.then_some(&zone.zone_type));
+    player.add_resources(energy, antibodies, stem_cells, nutrients);
+    
+    if pooled {
+        let (energy_gen, antibody_gen, stem_gen, nutrient_gen) = player.get_pooled_income(&zone.zone_type);
         
         // Update zone resources
-        zone.energy = (zone.energy + energy_gen).min(1000);
-        zone.antibodies = (zone.antibodies + antibody_gen).min(1000);
-        zone.stem_cells = (zone.stem_cells + stem_gen).min(100);
-        zone.nutrients = (zone.nutrients + nutrient_gen).min(1000);
+        zone.energy = (zone.energy + energy_gen).min(POOL_CAPS[0]);
+        zone.antibodies = (zone.antibodies + antibody_gen).min(POOL_CAPS[1]);
+        zone.stem_cells = (zone.stem_cells + stem_gen).min(POOL_CAPS[2]);
+        zone.nutrients = (zone.nutrients + nutrient_gen).min(POOL_CAPS[3]);
+    }
+    
+    // Ending the turn in time clears any timeout strikes
+    game.clear_timeout_strikes(&player.player_key);
+    
+    // Turns alternate, so the player's next turn is two turn numbers away
+    for unit in units {
+        if unit.owner == player.player_key && unit.game_id == game.game_id && unit.is_active {
+            unit.refresh_actions(game.turn_number + 2);
+        }
     }
     
     // Switch to next player
     game.switch_turn();
+    game.last_turn_timestamp = now;
     
     Ok(())
 }
 
-fn check_win_conditions(game: &mut Game, player: &Player) -> Result<()> {
-    // Simple win condition: control 75% of zones or eliminate all enemy units
-    if player.controlled_zones >= (game.total_zones * 3 / 4) as u16 {
-        let winner = match player.player_id {
-            1 => game::GameWinner::Player1,
-            2 => game::GameWinner::Player2,
-            _ => game::GameWinner::Draw,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::GameState;
+    use grid::ZoneType;
+
+    const T_CELL: u8 = 0;
+
+    // Player 1's turn in an active game, on a circulatory zone they hold
+    fn setup() -> (Game, Player, Zone) {
+        let game = Game {
+            game_id: Pubkey::new_unique(),
+            player1: Pubkey::new_unique(),
+            player2: Pubkey::new_unique(),
+            game_state: GameState::Active,
+            ..Game::default()
         };
-        game.end_game(winner);
+        let player = Player { player_key: game.player1, game_id: game.game_id, ..Player::default() };
+        let zone = Zone {
+            zone_type: ZoneType::Circulatory,
+            game_id: game.game_id,
+            owner: player.player_key,
+            ..Zone::default()
+        };
+        (game, player, zone)
     }
-    
-    Ok(())
-}
 
-fn calculate_spawn_cost(unit_type: &UnitType, zone_type: &ZoneType) -> (u64, u64, u64, u64) {
-    let (_, _, _, _, base_cost) = unit_type.get_base_stats();
-    let zone_multiplier = match zone_type {
-        ZoneType::Lymphatic => 0.8, // Cheaper in lymphatic zones
-        ZoneType::Barrier => 1.5,   // More expensive in barrier zones
-        _ => 1.0,
-    };
-    
-    let adjusted_cost = (base_cost as f64 * zone_multiplier) as u64;
-    
-    match unit_type.is_immune_cell() {
-        true => (adjusted_cost, adjusted_cost / 2, adjusted_cost / 10, adjusted_cost / 3),
-        false => (adjusted_cost * 2, 0, 0, adjusted_cost),
+    fn act(game: &mut Game, player: &mut Player, zone: &mut Zone, unit: &mut Unit, action: ActionType, (x, y): (u8, u8)) -> Result<()> {
+        let command = Command { action, x, y, unit_type: T_CELL, ability_index: 0 };
+        apply_action(game, player, zone, unit, &mut Unit::default(), command, 0)
+    }
+
+    // A T cell of the player on (4, 4), spawned last turn so it can act
+    fn ready_unit(game: &mut Game, player: &mut Player, zone: &mut Zone) -> Unit {
+        let mut unit = Unit::default();
+        act(game, player, zone, &mut unit, ActionType::SpawnUnit, (4, 4)).unwrap();
+        game.turn_number += 2;
+        unit
+    }
+
+    const UNIT_ACTIONS: [ActionType; 4] =
+        [ActionType::MoveUnit, ActionType::AttackPosition, ActionType::UseSpecialAbility, ActionType::Harvest];
+
+    #[test]
+    fn spawned_units_are_bound_to_their_component() {
+        let (mut game, mut player, mut zone) = setup();
+        let mut unit = Unit::default();
+
+        act(&mut game, &mut player, &mut zone, &mut unit, ActionType::SpawnUnit, (4, 4)).unwrap();
+
+        let (health, attack, defense, movement_range, energy_cost) = UnitType::TCell.get_base_stats();
+        assert_eq!((unit.unit_id, unit.game_id, unit.owner), (1, game.game_id, player.player_key));
+        assert_eq!((unit.zone_id, unit.x, unit.y), (zone.zone_id, 4, 4));
+        assert_eq!((unit.health, unit.max_health, unit.attack, unit.defense), (health, health, attack, defense));
+        assert_eq!((unit.movement_range, unit.energy_cost), (movement_range, energy_cost));
+        assert!(unit.is_active && unit.unit_type == UnitType::TCell);
+        assert!(unit.special_abilities == UnitType::TCell.get_default_abilities());
+
+        assert!(zone.cell(4, 4) == Some(CellContent::ImmuneCell { unit_id: 1, health, seat: player.player_id }));
+        assert_eq!((zone.unit_count, player.total_units, game.next_unit_id), (1, 1, 2));
+    }
+
+    #[test]
+    fn spawned_units_act_from_their_owners_next_turn() {
+        let (mut game, mut player, mut zone) = setup();
+        let mut unit = Unit::default();
+        act(&mut game, &mut player, &mut zone, &mut unit, ActionType::SpawnUnit, (4, 4)).unwrap();
+
+        let error = act(&mut game, &mut player, &mut zone, &mut unit, ActionType::MoveUnit, (4, 5)).unwrap_err();
+        assert_eq!(error, BioCommanderError::NoActionPoints.into());
+
+        game.turn_number += 2;
+        act(&mut game, &mut player, &mut zone, &mut unit, ActionType::MoveUnit, (4, 5)).unwrap();
+        assert!(zone.is_empty_cell(4, 4));
+        assert_eq!((unit.x, unit.y), (4, 5));
+    }
+
+    #[test]
+    fn bound_components_cannot_spawn_again() {
+        let (mut game, mut player, mut zone) = setup();
+        let mut unit = Unit::default();
+        act(&mut game, &mut player, &mut zone, &mut unit, ActionType::SpawnUnit, (4, 4)).unwrap();
+
+        let error = act(&mut game, &mut player, &mut zone, &mut unit, ActionType::SpawnUnit, (5, 5)).unwrap_err();
+
+        assert_eq!(error, BioCommanderError::UnitAlreadySpawned.into());
+        assert!(zone.is_empty_cell(5, 5));
+        assert_eq!((unit.x, unit.y, player.total_units), (4, 4, 1));
+    }
+    #[test]
+    fn unit_actions_need_a_bound_unit() {
+        for action in UNIT_ACTIONS {
+            let (mut game, mut player, mut zone) = setup();
+            let error = act(&mut game, &mut player, &mut zone, &mut Unit::default(), action, (4, 5)).unwrap_err();
+            assert_eq!(error, BioCommanderError::UnitNotFound.into());
+        }
+    }
+
+    #[test]
+    fn units_of_the_opponent_cannot_act() {
+        for action in UNIT_ACTIONS {
+            let (mut game, mut player, mut zone) = setup();
+            let mut unit = ready_unit(&mut game, &mut player, &mut zone);
+            unit.owner = game.player2;
+
+            let error = act(&mut game, &mut player, &mut zone, &mut unit, action, (4, 5)).unwrap_err();
+
+            assert_eq!(error, BioCommanderError::NotUnitOwner.into());
+            assert_eq!((unit.x, unit.y, unit.action_points), (4, 4, 0));
+        }
+    }
+
+    #[test]
+    fn units_act_only_from_their_cell_of_the_zone() {
+        let misplaced: [fn(&mut Unit, &mut Zone); 3] = [
+            |unit, _| unit.zone_id = 7,
+            |unit, _| unit.y = 6,
+            |_, zone| zone.clear_cell(4, 4),
+        ];
+        for misplace in misplaced {
+            let (mut game, mut player, mut zone) = setup();
+            let mut unit = ready_unit(&mut game, &mut player, &mut zone);
+            misplace(&mut unit, &mut zone);
+
+            let error = act(&mut game, &mut player, &mut zone, &mut unit, ActionType::MoveUnit, (4, 5)).unwrap_err();
+
+            assert_eq!(error, BioCommanderError::UnitNotInZone.into());
+        }
+    }
+
+    #[test]
+    fn actions_need_the_players_turn_in_an_active_game() {
+        let (mut game, mut player, mut zone) = setup();
+        let mut unit = ready_unit(&mut game, &mut player, &mut zone);
+
+        game.current_turn = 2;
+        let error = act(&mut game, &mut player, &mut zone, &mut unit, ActionType::MoveUnit, (4, 5)).unwrap_err();
+        assert_eq!(error, BioCommanderError::NotPlayersTurn.into());
+
+        game.current_turn = 1;
+        game.game_state = GameState::Paused;
+        let error = act(&mut game, &mut player, &mut zone, &mut unit, ActionType::MoveUnit, (4, 5)).unwrap_err();
+        assert_eq!(error, BioCommanderError::NotActive.into());
+    }
+    #[test]
+    fn ending_a_turn_stamps_it_and_clears_timeout_strikes() {
+        let (mut game, mut player, mut zone) = setup();
+        game.timeout_strikes = [2, 1];
+        let command = Command { action: ActionType::EndTurn, x: 0, y: 0, unit_type: 0, ability_index: 0 };
+
+        let (mut unit, mut target_unit) = (Unit::default(), Unit::default());
+        apply_action(&mut game, &mut player, &mut zone, &mut unit, &mut target_unit, command, 1234).unwrap();
+
+        assert_eq!((game.current_turn, game.last_turn_timestamp), (2, 1234));
+        assert_eq!(game.timeout_strikes, [0, 1]);
     }
 }
@@ -0,0 +1,48 @@
+use bolt_lang::*;
+use game::Game;
+use grid::Zone;
+use play::{BioCommanderError, Transfer};
+use players::Player;
+
+declare_id!("TRaNS11111111111111111111111111111111111111");
+
+// A transfer between zones touches two of them, which play cannot take, so
+// transfers run as their own system over play's rules
+#[system]
+pub mod transfer_resources {
+
+    pub fn execute(ctx: Context<Components>, args: Args) -> Result<Components> {
+        let authority = *ctx.accounts.authority.key;
+
+        // Validate player is acting for themselves
+        require!(ctx.accounts.player.player_key == authority, BioCommanderError::NotInGame);
+
+        play::transfer_resources(
+            &mut ctx.accounts.game,
+            &mut ctx.accounts.player,
+            &mut ctx.accounts.zone,
+            &mut ctx.accounts.other_zone,
+            args.transfer,
+            [args.energy, args.antibodies, args.stem_cells, args.nutrients],
+        )?;
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub game: Game,
+        pub player: Player,
+        pub zone: Zone,
+        pub other_zone: Zone, // Destination of ToZone, else the starting zone linked to `zone` or any other zone
+    }
+
+    #[arguments]
+    struct Args {
+        transfer: Transfer,
+        energy: u32,
+        antibodies: u32,
+        stem_cells: u32,
+        nutrients: u32,
+    }
+}